@@ -0,0 +1,375 @@
+#API contract checks for the endpoints the Leptos client depends on
+#Run the server against a throwaway DB first so the checks don't touch db.sqlite:
+#  $env:DB_PATH = "contract-test.sqlite"; cargo run
+#Needs PowerShell 7+ (for -SkipHttpErrorCheck)
+#Set API_BASE to check another address, e.g. a server started with TLS_CERT/TLS_KEY:
+#  $env:API_BASE = "https://localhost:3000"  (self-signed certificates are accepted)
+
+$base = if ($env:API_BASE) { $env:API_BASE } else { "http://localhost:3000" }
+$failed = 0
+
+function Check($name, $method, $path, $body, $expectedStatus) {
+    $params = @{ Uri = "$base$path"; Method = $method; SkipHttpErrorCheck = $true }
+    if ($base.StartsWith("https")) {
+        $params.SkipCertificateCheck = $true
+    }
+    if ($null -ne $body) {
+        $params.Body = $body
+        $params.ContentType = "application/json"
+    }
+    $res = Invoke-WebRequest @params
+    if ($res.StatusCode -ne $expectedStatus) {
+        Write-Host "FAIL $name - expected $expectedStatus, got $($res.StatusCode)"
+        $script:failed++
+    } else {
+        Write-Host "ok   $name"
+    }
+    return $res
+}
+
+function CheckField($name, $obj, $field) {
+    if ($null -eq $obj.$field) {
+        Write-Host "FAIL $name - missing field '$field'"
+        $script:failed++
+    }
+}
+
+Check "health" Get "/health" $null 200 | Out-Null
+#Migrations finish before the server listens; the poll only waits for the server to come up
+$ready = $null
+for ($i = 0; $i -lt 50 -and $ready.StatusCode -ne 200; $i++) {
+    if ($i -gt 0) { Start-Sleep -Milliseconds 100 }
+    $ready = Invoke-WebRequest -Uri "$base/ready" -SkipHttpErrorCheck -SkipCertificateCheck:$base.StartsWith("https")
+}
+$res = Check "ready" Get "/ready" $null 200
+$state = $res.Content | ConvertFrom-Json
+if ($state.schema_version -ne $state.latest_version) {
+    Write-Host "FAIL ready - schema_version $($state.schema_version), latest $($state.latest_version)"
+    $failed++
+}
+#The UI, or the built-in page when the static directory is missing - never a 500
+Check "index" Get "/" $null 200 | Out-Null
+
+#Create -> {"id": "..."}
+$res = Check "create" Post "/api/create" '{"name":"contract","description":"first"}' 201
+$id = ($res.Content | ConvertFrom-Json).id
+CheckField "create" ($res.Content | ConvertFrom-Json) "id"
+
+#Read all -> array of items
+$res = Check "read all" Get "/api/read" $null 200
+$items = $res.Content | ConvertFrom-Json
+if (-not ($items | Where-Object { $_.id -eq $id })) {
+    Write-Host "FAIL read all - created item missing"
+    $failed++
+}
+
+#?fields=id projects each item down to its id
+$projected = (Check "read projected" Get "/api/read?fields=id" $null 200).Content | ConvertFrom-Json
+foreach ($p in $projected) {
+    if ($p.PSObject.Properties.Name -contains "name" -or $p.PSObject.Properties.Name -contains "description") {
+        Write-Host "FAIL read projected - item has fields beyond id"
+        $failed++
+        break
+    }
+}
+Check "read projected unknown field" Get "/api/read?fields=id,secret" $null 400 | Out-Null
+
+#Read one -> {id, name, description}
+$res = Check "read one" Get "/api/read/$id" $null 200
+$item = $res.Content | ConvertFrom-Json
+foreach ($f in "id", "name", "description") { CheckField "read one" $item $f }
+
+#?with_timing=true wraps the same item in an envelope
+$wrapped = (Check "read one with timing" Get "/api/read/${id}?with_timing=true" $null 200).Content | ConvertFrom-Json
+foreach ($f in "data", "timing") { CheckField "read one with timing" $wrapped $f }
+if ($null -ne $item.timing) {
+    Write-Host "FAIL read one - envelope present without with_timing"
+    $failed++
+}
+
+#Update -> 200 {"affected": 1}, change visible on re-read
+$res = (Check "update" Patch "/api/update/$id" '{"description":"second"}' 200).Content | ConvertFrom-Json
+if ($res.affected -ne 1) {
+    Write-Host "FAIL update - affected $($res.affected)"
+    $failed++
+}
+$item = (Check "read after update" Get "/api/read/$id" $null 200).Content | ConvertFrom-Json
+if ($item.description -ne "second") {
+    Write-Host "FAIL update - description is '$($item.description)'"
+    $failed++
+}
+#PATCH merges: a name-only patch keeps the description, an explicit null clears it
+Check "patch name" Patch "/api/update/$id" '{"name":"patched"}' 200 | Out-Null
+$item = (Check "read after patch name" Get "/api/read/$id" $null 200).Content | ConvertFrom-Json
+if ($item.name -ne "patched" -or $item.description -ne "second") {
+    Write-Host "FAIL patch name - name '$($item.name)', description '$($item.description)'"
+    $failed++
+}
+$res = (Check "patch clear description" Patch "/api/update/$id" '{"description":null}' 200).Content | ConvertFrom-Json
+$item = (Check "read after clear" Get "/api/read/$id" $null 200).Content | ConvertFrom-Json
+if ($item.name -ne "patched" -or $null -ne $item.description) {
+    Write-Host "FAIL patch clear description - name '$($item.name)', description '$($item.description)'"
+    $failed++
+}
+Check "patch null name" Patch "/api/update/$id" '{"name":null}' 422 | Out-Null
+#PUT replaces: name is required and a missing description is stored as null
+Check "put without name" Put "/api/update/$id" '{"description":"d"}' 422 | Out-Null
+Check "put set description" Put "/api/update/$id" '{"name":"replaced","description":"d"}' 200 | Out-Null
+Check "put replace" Put "/api/update/$id" '{"name":"replaced"}' 200 | Out-Null
+$item = (Check "read after put" Get "/api/read/$id" $null 200).Content | ConvertFrom-Json
+if ($item.name -ne "replaced" -or $null -ne $item.description) {
+    Write-Host "FAIL put replace - name '$($item.name)', description '$($item.description)'"
+    $failed++
+}
+
+#Tags -> {"id": "...", "tags": [...]}, visible on read, removable one at a time
+$res = Check "add tags" Post "/api/items/$id/tags" '{"tags":["red","blue"]}' 200
+CheckField "add tags" ($res.Content | ConvertFrom-Json) "tags"
+$item = (Check "read with tags" Get "/api/read/$id" $null 200).Content | ConvertFrom-Json
+if (@($item.tags).Count -ne 2) {
+    Write-Host "FAIL read with tags - expected 2 tags, got $(@($item.tags).Count)"
+    $failed++
+}
+Check "remove tag" Delete "/api/items/$id/tags/red" $null 200 | Out-Null
+Check "remove tag again" Delete "/api/items/$id/tags/red" $null 404 | Out-Null
+Check "add tags empty" Post "/api/items/$id/tags" '{"tags":[]}' 400 | Out-Null
+
+#Random sample -> n distinct items, or every item when n exceeds the table
+foreach ($i in 1..10) { Check "create for random" Post "/api/create" "{`"name`":`"random $i`"}" 201 | Out-Null }
+$sample = @((Check "random" Get "/api/items/random?n=3" $null 200).Content | ConvertFrom-Json)
+if ($sample.Count -ne 3 -or @($sample.id | Sort-Object -Unique).Count -ne 3) {
+    Write-Host "FAIL random - expected 3 distinct items, got $($sample.Count)"
+    $failed++
+}
+$total = ((Check "count for random" Get "/api/items/count" $null 200).Content | ConvertFrom-Json).count
+$sample = @((Check "random above count" Get "/api/items/random?n=$($total + 5)" $null 200).Content | ConvertFrom-Json)
+if ($sample.Count -ne $total) {
+    Write-Host "FAIL random above count - expected $total items, got $($sample.Count)"
+    $failed++
+}
+Check "random zero" Get "/api/items/random?n=0" $null 400 | Out-Null
+
+#Count -> {"count": n}, matching the number of rows /api/read returns
+$count = ((Check "count" Get "/api/items/count" $null 200).Content | ConvertFrom-Json).count
+$all = @((Check "read all for count" Get "/api/read" $null 200).Content | ConvertFrom-Json).Count
+if ($count -ne $all) {
+    Write-Host "FAIL count - got $count, /api/read has $all items"
+    $failed++
+}
+
+#Search id -> same position from binary, linear and by-key search
+$bin = (Check "search id binary" Get "/api/items/search_id?id=$id&algo=binary" $null 200).Content | ConvertFrom-Json
+$lin = (Check "search id linear" Get "/api/items/search_id?id=$id&algo=linear" $null 200).Content | ConvertFrom-Json
+$key = (Check "search id by key" Get "/api/items/search_id?id=$id&algo=by_key" $null 200).Content | ConvertFrom-Json
+if ($null -eq $bin.position -or $bin.position -ne $lin.position -or $bin.position -ne $key.position) {
+    Write-Host "FAIL search id - binary position $($bin.position), linear position $($lin.position), by key position $($key.position)"
+    $failed++
+}
+Check "search id missing" Get "/api/items/search_id?id=no-such-id" $null 404 | Out-Null
+Check "search id bad algo" Get "/api/items/search_id?id=$id&algo=quantum" $null 400 | Out-Null
+
+#Bulk create -> {"inserted": [...], "failed": [...]}
+$res = Check "bulk create" Post "/api/bulk_create" '[{"name":"b1"},{"name":"b2","description":"d"}]' 201
+foreach ($f in "inserted", "failed") { CheckField "bulk create" ($res.Content | ConvertFrom-Json) $f }
+#One valid and one invalid element: rolled back by default, the valid one kept with ?partial=true
+$before = ((Check "count before bulk" Get "/api/items/count" $null 200).Content | ConvertFrom-Json).count
+$res = (Check "bulk create rollback" Post "/api/bulk_create" '[{"name":"ok"},{"description":"no name"}]' 422).Content | ConvertFrom-Json
+$after = ((Check "count after rollback" Get "/api/items/count" $null 200).Content | ConvertFrom-Json).count
+if (@($res.inserted).Count -ne 0 -or @($res.failed).Count -ne 1 -or $res.failed[0].index -ne 1 -or $after -ne $before) {
+    Write-Host "FAIL bulk create rollback - inserted $(@($res.inserted).Count), failed $(@($res.failed).Count), count $before -> $after"
+    $failed++
+}
+$res = (Check "bulk create partial" Post "/api/bulk_create?partial=true" '[{"name":"ok"},{"description":"no name"}]' 201).Content | ConvertFrom-Json
+$after = ((Check "count after partial" Get "/api/items/count" $null 200).Content | ConvertFrom-Json).count
+if (@($res.inserted).Count -ne 1 -or @($res.failed).Count -ne 1 -or $res.failed[0].index -ne 1 -or $after -ne $before + 1) {
+    Write-Host "FAIL bulk create partial - inserted $(@($res.inserted).Count), failed $(@($res.failed).Count), count $before -> $after"
+    $failed++
+}
+#?batch=250 commits 1000 items in four transactions and times each one
+$body = ConvertTo-Json -Compress @(1..1000 | ForEach-Object { @{ name = "batch-$_" } })
+$res = (Check "bulk create batched" Post "/api/bulk_create?batch=250" $body 201).Content | ConvertFrom-Json
+if (@($res.inserted).Count -ne 1000 -or @($res.batches).Count -ne 4 -or @($res.batches | Where-Object { $_.size -ne 250 -or $null -eq $_.time_ms }).Count -ne 0) {
+    Write-Host "FAIL bulk create batched - inserted $(@($res.inserted).Count), batches $(@($res.batches).Count)"
+    $failed++
+}
+Check "bulk create batch 0" Post "/api/bulk_create?batch=0" '[{"name":"ok"}]' 400 | Out-Null
+
+#Analytics: three new items with two new names add 3 to count and 2 to distinct_names
+$before = (Check "analytics" Get "/api/items/analytics" $null 200).Content | ConvertFrom-Json
+foreach ($f in "count", "distinct_names", "null_descriptions", "load_time_ms", "compute_time_ms") { CheckField "analytics" $before $f }
+$tag = [guid]::NewGuid().ToString("N")
+Check "analytics insert" Post "/api/bulk_create" "[{`"name`":`"an-$tag-1`"},{`"name`":`"an-$tag-1`"},{`"name`":`"an-$tag-2`",`"description`":`"d`"}]" 201 | Out-Null
+$after = (Check "analytics after insert" Get "/api/items/analytics" $null 200).Content | ConvertFrom-Json
+if ($after.count -ne $before.count + 3 -or $after.distinct_names -ne $before.distinct_names + 2 -or $after.null_descriptions -ne $before.null_descriptions + 2) {
+    Write-Host "FAIL analytics - count $($before.count) -> $($after.count), distinct names $($before.distinct_names) -> $($after.distinct_names), null descriptions $($before.null_descriptions) -> $($after.null_descriptions)"
+    $failed++
+}
+#The result is cached until the next write (assumes the default 5s ANALYTICS_CACHE_TTL_MS)
+$again = (Check "analytics cached" Get "/api/items/analytics" $null 200).Content | ConvertFrom-Json
+if ($again.cache -ne "hit" -or $again.count -ne $after.count) {
+    Write-Host "FAIL analytics cached - cache $($again.cache), count $($again.count)"
+    $failed++
+}
+Check "analytics invalidate" Post "/api/create" "{`"name`":`"an-$tag-3`"}" 201 | Out-Null
+$fresh = (Check "analytics after write" Get "/api/items/analytics" $null 200).Content | ConvertFrom-Json
+if ($fresh.cache -ne "miss" -or $fresh.count -ne $after.count + 1) {
+    Write-Host "FAIL analytics after write - cache $($fresh.cache), count $($fresh.count)"
+    $failed++
+}
+
+#Metric ingest: a valid metric is accepted, NaN (null after JSON.stringify, or a string) is rejected
+Check "ingest metric" Post "/api/metrics_ingest" '{"operation":"PY_READ","execution_time_ms":1.25,"status_code":200}' 201 | Out-Null
+Check "ingest null execution time" Post "/api/metrics_ingest" '{"operation":"PY_READ","execution_time_ms":null}' 400 | Out-Null
+Check "ingest NaN execution time" Post "/api/metrics_ingest" '{"operation":"PY_READ","execution_time_ms":"NaN"}' 400 | Out-Null
+Check "ingest empty operation" Post "/api/metrics_ingest" '{"operation":"","execution_time_ms":1}' 400 | Out-Null
+Check "ingest bad request id" Post "/api/metrics_ingest" '{"operation":"PY_READ","request_id":5}' 400 | Out-Null
+
+#X-Request-Id: a client's id is echoed back and lands in that request's metric; without one the server makes one up
+$requestId = "api-test-$([guid]::NewGuid())"
+$res = Invoke-WebRequest -Uri "$base/api/create" -Method Post -ContentType "application/json" -Body '{"name":"request id"}' -Headers @{ "X-Request-Id" = $requestId } -SkipHttpErrorCheck -SkipCertificateCheck
+$metric = @(((Check "metrics after request id" Get "/api/metrics" $null 200).Content | ConvertFrom-Json) | Where-Object { $_.request_id -eq $requestId })
+if ($res.StatusCode -ne 201 -or "$($res.Headers["X-Request-Id"])" -ne $requestId -or $metric.Count -ne 1 -or $metric[0].operation -ne "CREATE") {
+    Write-Host "FAIL request id - status $($res.StatusCode), echoed '$($res.Headers["X-Request-Id"])', $($metric.Count) matching metrics"
+    $failed++
+}
+$res = Check "generated request id" Get "/health" $null 200
+if ([string]::IsNullOrEmpty("$($res.Headers["X-Request-Id"])")) {
+    Write-Host "FAIL generated request id - no X-Request-Id header"
+    $failed++
+}
+
+#Database / metrics / stats shapes
+$db = (Check "database" Get "/api/database" $null 200).Content | ConvertFrom-Json
+foreach ($f in "total_items", "items", "column_stats", "database_uri") { CheckField "database" $db $f }
+#Metric floats are serialized with at most METRIC_DECIMALS (default 4) decimals
+$res = Check "metrics" Get "/api/metrics" $null 200
+$long = [regex]::Matches($res.Content, '"execution_time_ms":-?\d+\.\d{5,}')
+if ($long.Count -gt 0) {
+    Write-Host "FAIL metrics - execution_time_ms with more than 4 decimals: $($long[0].Value)"
+    $failed++
+}
+#Prefix match: BULK_CREATE covers BULK_CREATE_2 and BULK_CREATE_1, and nothing else
+$ops = ((Check "metrics by operation" Get "/api/metrics/by_operation/BULK_CREATE" $null 200).Content | ConvertFrom-Json) | ForEach-Object { $_.operation }
+if (@($ops).Count -eq 0 -or @($ops | Where-Object { -not $_.StartsWith("BULK_CREATE_") }).Count -gt 0) {
+    Write-Host "FAIL metrics by operation - got $($ops -join ', ')"
+    $failed++
+}
+$ops = (Check "metrics by operation limit" Get "/api/metrics/by_operation/BULK_CREATE?limit=1" $null 200).Content | ConvertFrom-Json
+if (@($ops).Count -ne 1) {
+    Write-Host "FAIL metrics by operation limit - got $(@($ops).Count) metrics"
+    $failed++
+}
+#Tail: a long-poll from the current index returns once a new metric arrives, with an advanced cursor
+$cursor = ((Check "metrics tail now" Get "/api/metrics/tail?timeout_ms=0" $null 200).Content | ConvertFrom-Json).next_index
+$job = Start-ThreadJob -ArgumentList $base {
+    param($base)
+    Start-Sleep -Milliseconds 300
+    Invoke-WebRequest -Uri "$base/api/metrics_ingest" -Method Post -ContentType "application/json" -Body '{"operation":"TAIL_PROBE","execution_time_ms":1}' -SkipCertificateCheck | Out-Null
+}
+$tail = (Check "metrics tail wait" Get "/api/metrics/tail?since=$cursor&timeout_ms=5000" $null 200).Content | ConvertFrom-Json
+$job | Wait-Job | Remove-Job
+if (-not ($tail.metrics | Where-Object { $_.operation -eq "TAIL_PROBE" }) -or $tail.next_index -le $cursor) {
+    Write-Host "FAIL metrics tail wait - got $(@($tail.metrics).Count) metrics, next_index $($tail.next_index) after $cursor"
+    $failed++
+}
+$tail = (Check "metrics tail timeout" Get "/api/metrics/tail?since=$($tail.next_index)&timeout_ms=200" $null 200).Content | ConvertFrom-Json
+if (@($tail.metrics).Count -ne 0) {
+    Write-Host "FAIL metrics tail timeout - expected no metrics"
+    $failed++
+}
+$cmp = (Check "metrics compare" Get "/api/metrics/compare?a=CREATE&b=UPDATE" $null 200).Content | ConvertFrom-Json
+foreach ($f in "a", "b") { CheckField "metrics compare" $cmp $f }
+Check "metrics compare missing b" Get "/api/metrics/compare?a=CREATE" $null 400 | Out-Null
+#Rust vs Python: one metric of each language for the same operation must give both stats and a speedup
+Check "ingest rust metric" Post "/api/metrics_ingest" '{"operation":"LANG_CMP","execution_time_ms":1.0,"language":"rust"}' 201 | Out-Null
+Check "ingest python metric" Post "/api/metrics_ingest" '{"operation":"LANG_CMP","execution_time_ms":4.0,"language":"python"}' 201 | Out-Null
+Check "ingest empty language" Post "/api/metrics_ingest" '{"operation":"LANG_CMP","language":""}' 400 | Out-Null
+$row = ((Check "compare languages" Get "/api/compare" $null 200).Content | ConvertFrom-Json) | Where-Object { $_.operation -eq "LANG_CMP" }
+if ($null -eq $row -or $null -eq $row.languages.rust -or $null -eq $row.languages.python -or $row.speedup.python -ne 4) {
+    Write-Host "FAIL compare languages - got $($row | ConvertTo-Json -Compress -Depth 4)"
+    $failed++
+}
+#Cached and uncached statement preparation must return the same items
+$bench = (Check "bench prepare" Get "/api/bench/prepare?iterations=200" $null 200).Content | ConvertFrom-Json
+if ($bench.identical -ne $true) {
+    Write-Host "FAIL bench prepare - cached and uncached results differ"
+    $failed++
+}
+Check "bench prepare zero iterations" Get "/api/bench/prepare?iterations=0" $null 400 | Out-Null
+#Schema derived from the structs: both models present, execution_time_ms a number
+$schema = (Check "schema" Get "/api/schema" $null 200).Content | ConvertFrom-Json
+foreach ($f in "Item", "Metric") { CheckField "schema" $schema $f }
+if ($schema.Metric.properties.execution_time_ms.type -ne "number") {
+    Write-Host "FAIL schema - execution_time_ms type is $($schema.Metric.properties.execution_time_ms.type)"
+    $failed++
+}
+$stats = (Check "stats" Get "/api/stats" $null 200).Content | ConvertFrom-Json
+foreach ($f in "worker_threads", "metrics_capacity", "metrics_in_memory", "metrics_sample_rate", "metrics_write_count", "metrics_write_ms_total") { CheckField "stats" $stats $f }
+#Every recorded metric is timed on its way to the CSV (assumes METRICS_SAMPLE_RATE is 1)
+foreach ($i in 1..3) { Check "count for write stats" Get "/api/items/count" $null 200 | Out-Null }
+$statsAfter = (Check "stats after counts" Get "/api/stats" $null 200).Content | ConvertFrom-Json
+if ($statsAfter.metrics_write_count -lt $stats.metrics_write_count + 3 -or $statsAfter.metrics_write_ms_total -lt $stats.metrics_write_ms_total) {
+    Write-Host "FAIL stats - metrics_write_count went from $($stats.metrics_write_count) to $($statsAfter.metrics_write_count) after 3 counts"
+    $failed++
+}
+Check "export" Get "/api/export?format=sql" $null 200 | Out-Null
+
+#DB download is only routed with ALLOW_DB_DOWNLOAD=true (set it for both the server and this script)
+if ($env:ALLOW_DB_DOWNLOAD -eq "true") {
+    $res = Check "db download" Get "/api/db/download" $null 200
+    if ($res.Headers["Content-Type"] -ne "application/x-sqlite3" -or $res.RawContentLength -eq 0) {
+        Write-Host "FAIL db download - content type '$($res.Headers["Content-Type"])', $($res.RawContentLength) bytes"
+        $failed++
+    }
+} else {
+    Check "db download disabled" Get "/api/db/download" $null 404 | Out-Null
+}
+
+#Delete -> 200 {"affected": 1}, then gone
+$res = (Check "delete" Delete "/api/delete/$id" $null 200).Content | ConvertFrom-Json
+if ($res.affected -ne 1) {
+    Write-Host "FAIL delete - affected $($res.affected)"
+    $failed++
+}
+
+#Error cases
+Check "read missing id" Get "/api/read/$id" $null 404 | Out-Null
+$last = ((Check "metrics after 404" Get "/api/metrics" $null 200).Content | ConvertFrom-Json) | Where-Object { $_.operation -eq "READ (Description)" } | Select-Object -Last 1
+if ($last.status_code -ne 404) {
+    Write-Host "FAIL metrics after 404 - READ (Description) recorded status $($last.status_code)"
+    $failed++
+}
+$summary = (Check "metrics summary" Get "/api/metrics/summary" $null 200).Content | ConvertFrom-Json
+foreach ($f in "operation", "samples", "estimated_count", "successes", "failures") { CheckField "metrics summary" @($summary)[0] $f }
+Check "metrics summary exact" Get "/api/metrics/summary?exact=true" $null 200 | Out-Null
+Check "update missing id" Put "/api/update/$id" '{"name":"x"}' 404 | Out-Null
+Check "no-op patch missing id" Patch "/api/update/$id" '{}' 404 | Out-Null
+Check "delete missing id" Delete "/api/delete/$id" $null 404 | Out-Null
+Check "create bad json" Post "/api/create" '{"name":' 400 | Out-Null
+Check "bulk create bad json" Post "/api/bulk_create" 'not json' 400 | Out-Null
+Check "create numeric name" Post "/api/create" '{"name":5}' 422 | Out-Null
+Check "create missing name" Post "/api/create" '{"description":"no name"}' 422 | Out-Null
+Check "bulk create not an array" Post "/api/bulk_create" '{"name":"ok"}' 422 | Out-Null
+Check "update numeric description" Patch "/api/update/$id" '{"description":3}' 422 | Out-Null
+
+#Descriptions over MAX_DESC_LEN (assumes the default 1000): rejected, or cut with ?truncate=true
+$long = "x" * 1001
+Check "create long description" Post "/api/create" "{`"name`":`"long`",`"description`":`"$long`"}" 400 | Out-Null
+$res = (Check "create long description truncated" Post "/api/create?truncate=true" "{`"name`":`"long`",`"description`":`"$long`"}" 201).Content | ConvertFrom-Json
+if ($res.description.Length -ne 1000) {
+    Write-Host "FAIL create long description truncated - stored $($res.description.Length) characters"
+    $failed++
+}
+Check "update long description" Patch "/api/update/$($res.id)" "{`"description`":`"$long`"}" 400 | Out-Null
+$upd = (Check "update long description truncated" Patch "/api/update/$($res.id)?truncate=true" "{`"description`":`"$long`"}" 200).Content | ConvertFrom-Json
+if ($upd.description.Length -ne 1000) {
+    Write-Host "FAIL update long description truncated - stored $($upd.description.Length) characters"
+    $failed++
+}
+Check "delete truncated item" Delete "/api/delete/$($res.id)" $null 200 | Out-Null
+
+if ($failed -gt 0) {
+    Write-Host "$failed check(s) failed"
+    exit 1
+}
+Write-Host "All checks passed"
@@ -0,0 +1,103 @@
+use parking_lot::Mutex;
+use polars::prelude::*;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+// In-process analytics over the items table with Polars: the rows are pulled out with one plain
+// SELECT and every aggregate is computed on the DataFrame, not in SQL, so the cost of the
+// columnar step can be compared with the equivalent pandas code on the Python side.
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ItemAnalytics {
+    pub count: usize,
+    pub distinct_names: usize,
+    pub null_descriptions: usize,
+    // null_descriptions / count, None for an empty table
+    pub null_description_ratio: Option<f64>,
+}
+
+// Every item as a three-column frame: id, name, description (null where the column is NULL)
+pub fn load_items_frame(conn: &Connection) -> Result<DataFrame, String> {
+    let mut stmt = conn
+        .prepare("SELECT id, name, description FROM items")
+        .map_err(|e| e.to_string())?;
+    let mut ids: Vec<String> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    let mut descriptions: Vec<Option<String>> = Vec::new();
+    let mut rows = stmt.query([]).map_err(|e| e.to_string())?;
+    while let Some(row) = rows.next().map_err(|e| e.to_string())? {
+        ids.push(row.get(0).map_err(|e| e.to_string())?);
+        names.push(row.get(1).map_err(|e| e.to_string())?);
+        descriptions.push(row.get(2).map_err(|e| e.to_string())?);
+    }
+    df!("id" => ids, "name" => names, "description" => descriptions).map_err(|e| e.to_string())
+}
+
+pub fn compute(df: &DataFrame) -> PolarsResult<ItemAnalytics> {
+    let count = df.height();
+    let distinct_names = df.column("name")?.n_unique()?;
+    let null_descriptions = df.column("description")?.null_count();
+    Ok(ItemAnalytics {
+        count,
+        distinct_names,
+        null_descriptions,
+        null_description_ratio: (count > 0).then(|| null_descriptions as f64 / count as f64),
+    })
+}
+
+// Last computed analytics, reused by /api/items/analytics until ANALYTICS_CACHE_TTL_MS passes or the items change.
+// Every item write bumps GENERATION after committing. An entry computed from a read that started before a write
+// carries the older generation and is never served, even if it was stored after the write
+struct CachedAnalytics {
+    generation: u64,
+    computed_at: Instant,
+    stats: ItemAnalytics,
+    load_ms: f64,
+    compute_ms: f64,
+}
+
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+static CACHE: Mutex<Option<CachedAnalytics>> = parking_lot::const_mutex(None);
+
+// A cache hit: the stats, the load and compute times of the request that computed them, and their age
+pub struct CacheHit {
+    pub stats: ItemAnalytics,
+    pub load_ms: f64,
+    pub compute_ms: f64,
+    pub age: Duration,
+}
+
+// Called after every committed write to the items table
+pub fn invalidate_cache() {
+    GENERATION.fetch_add(1, Ordering::AcqRel);
+    *CACHE.lock() = None;
+}
+
+// Read before loading the frame, and passed back to `store_cached`
+pub fn cache_generation() -> u64 {
+    GENERATION.load(Ordering::Acquire)
+}
+
+pub fn cached(ttl: Duration) -> Option<CacheHit> {
+    let cache = CACHE.lock();
+    let entry = cache.as_ref()?;
+    let age = entry.computed_at.elapsed();
+    (entry.generation == cache_generation() && age < ttl).then(|| CacheHit {
+        stats: entry.stats.clone(),
+        load_ms: entry.load_ms,
+        compute_ms: entry.compute_ms,
+        age,
+    })
+}
+
+pub fn store_cached(generation: u64, stats: &ItemAnalytics, load_ms: f64, compute_ms: f64) {
+    *CACHE.lock() = Some(CachedAnalytics {
+        generation,
+        computed_at: Instant::now(),
+        stats: stats.clone(),
+        load_ms,
+        compute_ms,
+    });
+}
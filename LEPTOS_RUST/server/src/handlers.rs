@@ -1,152 +1,764 @@
 
 // Handler function imports
-use axum::{extract::{Path, Json}, http::{StatusCode, HeaderMap}};
+use axum::{extract::{Path, Json, Query}, http::{header, StatusCode, HeaderMap}, Extension};
 use axum::{routing::{get, post, put, delete}, Router};
+use axum::response::{IntoResponse, Response};
+use axum::body::StreamBody;
+use axum::middleware;
 use std::sync::Arc;
-use uuid::Uuid;
 use chrono::Local;
 use serde_json::Value;
+use serde::Deserialize;
+use tokio_stream::wrappers::ReceiverStream;
 
-use rusqlite::{params, Connection, OptionalExtension};
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
 
-use crate::item::Item;
-use crate::metric::Metric;
-use crate::utils::{append_metric_to_csv, sample_proc_memory_mb};
+use crate::analytics;
+use crate::idempotency;
+use crate::migrations;
+use crate::prepare_bench::{self, PrepareBench};
+use crate::tags::{self, select_items_sql};
+use crate::timing::timing_envelope;
+use crate::item::{limit_description, parse_fields, project_row, projection_sql, read_seed_items, CreateItemRequest, Item, PatchItemRequest};
+use crate::metric::{default_language, round_for_output, LanguageComparison, Metric, MetricStore, OperationSummary};
+use crate::rate_limit::{rate_limit, TokenBucket};
+use crate::request_id::{self, request_id};
+use crate::sampling::{is_sampled, record_metric, sample_request, with_sampled};
+use crate::utils::{allow_db_download, analytics_cache_ttl, db_path, generate_id, items_to_csv, max_desc_len, memory_delta_mb, metrics_capacity, metrics_sample_rate, rate_limit_rps, MetricsCsvWriter, sample_proc_memory_mb, seed_items_path, static_dir};
 
 use parking_lot::Mutex;
-type Metrics = Arc<Mutex<Vec<Metric>>>;
+
+// What the handlers share: the in-memory metric store and the CSV its metrics are appended to.
+// create_app_with_state builds one per app, so two apps (or two tests) never share a buffer
+pub struct AppState {
+	pub store: Mutex<MetricStore>,
+	pub metrics_csv: MetricsCsvWriter,
+}
+
+type Metrics = Arc<AppState>;
+
+const UNKNOWN_FIELD: &str = "unknown name in ?fields (allowed: id, name, description, tags)";
+const DESCRIPTION_TOO_LONG: &str = "description is longer than MAX_DESC_LEN characters (pass ?truncate=true to cut it)";
+
+#[derive(Deserialize, Default)]
+struct CreateParams {
+	id: Option<String>,
+	truncate: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct UpdateParams {
+	truncate: Option<bool>,
+}
+
+#[derive(Deserialize)]
+struct CompareParams {
+	a: String,
+	b: String,
+}
+
+#[derive(Deserialize)]
+struct SearchIdParams {
+	id: String,
+	algo: Option<String>,
+}
+
+#[derive(Deserialize, Default)]
+struct RandomParams {
+	n: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+struct PrepareBenchParams {
+	iterations: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+struct BulkParams {
+	partial: Option<bool>,
+	truncate: Option<bool>,
+	batch: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+struct ReadAllParams {
+	stream: Option<bool>,
+	fields: Option<String>,
+}
 
 // Handler for /api/database
 async fn get_database() -> Result<Json<serde_json::Value>, (StatusCode, &'static str)> {
-	let conn = Connection::open("db.sqlite").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
-	let mut stmt = conn.prepare("SELECT id, name, description FROM items").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
-	let items_iter = stmt.query_map([], |row| {
-		Ok(Item {
-			id: row.get(0)?,
-			name: row.get(1)?,
-			description: row.get(2).ok(),
-		})
-	}).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let mut stmt = conn.prepare(&select_items_sql("")).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let items_iter = stmt.query_map([], Item::from_row).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 	let mut items_vec = Vec::new();
-	for it in items_iter {
-		if let Ok(i) = it { items_vec.push(i); }
+	for i in items_iter.flatten() {
+		items_vec.push(i);
 	}
 	let total = items_vec.len();
+	// Per-column null counts, a quick data-quality view for the dashboard
+	let (id_nulls, name_nulls, desc_nulls) = conn.query_row(
+		"SELECT COUNT(*) FILTER (WHERE id IS NULL),
+				COUNT(*) FILTER (WHERE name IS NULL),
+				COUNT(*) FILTER (WHERE description IS NULL)
+		 FROM items",
+		[],
+		|row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?)),
+	).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 	let db_info = serde_json::json!({
 		"total_items": total,
 		"items": items_vec,
-		"database_uri": "sqlite://db.sqlite"
+		"column_stats": {
+			"id": { "null_count": id_nulls },
+			"name": { "null_count": name_nulls },
+			"description": { "null_count": desc_nulls },
+		},
+		"database_uri": format!("sqlite://{}", db_path())
 	});
 	Ok(Json(db_info))
 }
 
+// Handler for /api/items/count
+// COUNT(*) only, so the dashboard badge doesn't pull every row the way /api/database does
+async fn count_items(metrics: Metrics, headers: HeaderMap) -> Result<(Extension<Metric>, Json<Value>), (StatusCode, &'static str)> {
+	let mem_before = sample_proc_memory_mb();
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let start = std::time::Instant::now();
+	let count: i64 = conn.query_row("SELECT COUNT(*) FROM items", [], |row| row.get(0))
+		.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let exec = start.elapsed().as_secs_f64() * 1000.0;
+	let client_latency = headers.get("x-client-latency-ms")
+									 .and_then(|v| v.to_str().ok())
+									 .and_then(|s| s.parse::<f64>().ok())
+									 .unwrap_or(0.0);
+	let mem_after = sample_proc_memory_mb();
+	let metric = Metric {
+		timestamp: Local::now().to_rfc3339(),
+		operation: "COUNT".to_string(),
+		execution_time_ms: exec,
+		memory_mb: memory_delta_mb(mem_before, mem_after),
+		network_latency_ms: client_latency,
+		status_code: StatusCode::OK.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
+	};
+	record_metric(&metrics, &metric);
+	Ok((Extension(metric), Json(serde_json::json!({ "count": count }))))
+}
+
+// Handler for /api/items/random?n=
+// `n` items (default 1) picked with ORDER BY RANDOM(), the server-side counterpart of pre_proc's sample_df.
+// Asking for more than the table holds returns every item. They come back in insertion order
+async fn random_items(metrics: Metrics, headers: HeaderMap, Query(params): Query<RandomParams>) -> Result<(Extension<Metric>, Json<Vec<Item>>), (StatusCode, &'static str)> {
+	let n = params.n.unwrap_or(1);
+	if n == 0 {
+		return Err((StatusCode::BAD_REQUEST, "n must be at least 1"));
+	}
+	let mem_before = sample_proc_memory_mb();
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let start = std::time::Instant::now();
+	let mut stmt = conn.prepare(&select_items_sql(" WHERE i.id IN (SELECT id FROM items ORDER BY RANDOM() LIMIT ?1)"))
+		.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let items: Vec<Item> = stmt.query_map(params![n as i64], Item::from_row)
+		.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?
+		.flatten()
+		.collect();
+	let exec = start.elapsed().as_secs_f64() * 1000.0;
+	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+	let mem_after = sample_proc_memory_mb();
+	let metric = Metric {
+		timestamp: Local::now().to_rfc3339(),
+		operation: "RANDOM".to_string(),
+		execution_time_ms: exec,
+		memory_mb: memory_delta_mb(mem_before, mem_after),
+		network_latency_ms: client_latency,
+		status_code: StatusCode::OK.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
+	};
+	record_metric(&metrics, &metric);
+	Ok((Extension(metric), Json(items)))
+}
+
+// Handler for /api/items/analytics
+// Loads the items into a Polars DataFrame and aggregates there (see analytics.rs), timing the two parts apart.
+// The result is reused for ANALYTICS_CACHE_TTL_MS or until the next item write; `cache` says which happened.
+// The ANALYTICS metric records the DataFrame computation alone, ANALYTICS_CACHED the cache lookup
+async fn item_analytics(metrics: Metrics, headers: HeaderMap) -> Result<(Extension<Metric>, Json<Value>), (StatusCode, &'static str)> {
+	let mem_before = sample_proc_memory_mb();
+	let start = std::time::Instant::now();
+	let (stats, load_ms, compute_ms, cache_age_ms, operation, exec) = match analytics::cached(analytics_cache_ttl()) {
+		Some(hit) => {
+			let exec = start.elapsed().as_secs_f64() * 1000.0;
+			(hit.stats, hit.load_ms, hit.compute_ms, Some(hit.age.as_secs_f64() * 1000.0), "ANALYTICS_CACHED", exec)
+		}
+		None => {
+			let generation = analytics::cache_generation();
+			let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+			let load_start = std::time::Instant::now();
+			let df = analytics::load_items_frame(&conn).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+			let load_ms = load_start.elapsed().as_secs_f64() * 1000.0;
+
+			let start = std::time::Instant::now();
+			let stats = analytics::compute(&df).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Analytics error"))?;
+			let exec = start.elapsed().as_secs_f64() * 1000.0;
+			analytics::store_cached(generation, &stats, load_ms, exec);
+			(stats, load_ms, exec, None, "ANALYTICS", exec)
+		}
+	};
+	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+	let mem_after = sample_proc_memory_mb();
+	let metric = Metric {
+		timestamp: Local::now().to_rfc3339(),
+		operation: operation.to_string(),
+		execution_time_ms: exec,
+		memory_mb: memory_delta_mb(mem_before, mem_after),
+		network_latency_ms: client_latency,
+		status_code: StatusCode::OK.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
+	};
+	record_metric(&metrics, &metric);
+	Ok((Extension(metric), Json(serde_json::json!({
+		"count": stats.count,
+		"distinct_names": stats.distinct_names,
+		"null_descriptions": stats.null_descriptions,
+		"null_description_ratio": stats.null_description_ratio,
+		"load_time_ms": load_ms,
+		"compute_time_ms": compute_ms,
+		"cache": if cache_age_ms.is_some() { "hit" } else { "miss" },
+		"cache_age_ms": cache_age_ms,
+	}))))
+}
+
+// A search_core search over the sorted ids
+type IdSearch = fn(&[String], &String) -> Option<usize>;
+
+// Handler for /api/items/search_id?id=...&algo=binary|linear|by_key
+// Loads every id sorted and locates `id` with the search_core algorithm, timing the search alone.
+// by_key loads whole items instead, sorted by id, and searches the records with binary_search_by_key
+async fn search_item_id(metrics: Metrics, headers: HeaderMap, Query(params): Query<SearchIdParams>) -> Result<(Extension<Metric>, Json<Value>), (StatusCode, &'static str)> {
+	let algo = params.algo.unwrap_or_else(|| "binary".to_string());
+	let search: Option<IdSearch> = match algo.as_str() {
+		"binary" => Some(search_core::algorithms::binary_search),
+		"linear" => Some(search_core::algorithms::linear_search),
+		"by_key" => None,
+		_ => return Err((StatusCode::BAD_REQUEST, "algo must be binary, linear or by_key")),
+	};
+	let mem_before = sample_proc_memory_mb();
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let load_start = std::time::Instant::now();
+	let (position, total, load_ms, exec) = match search {
+		Some(search) => {
+			let mut stmt = conn.prepare("SELECT id FROM items").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+			let mut ids: Vec<String> = stmt.query_map([], |row| row.get(0))
+				.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?
+				.flatten()
+				.collect();
+			// Sorted in Rust so the order is exactly the one binary_search compares with
+			ids.sort_unstable();
+			let load_ms = load_start.elapsed().as_secs_f64() * 1000.0;
+
+			let start = std::time::Instant::now();
+			let position = search(&ids, &params.id);
+			(position, ids.len(), load_ms, start.elapsed().as_secs_f64() * 1000.0)
+		}
+		None => {
+			let mut stmt = conn.prepare(&select_items_sql("")).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+			let mut items: Vec<Item> = stmt.query_map([], Item::from_row)
+				.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?
+				.flatten()
+				.collect();
+			items.sort_unstable_by(|a, b| a.id.cmp(&b.id));
+			let load_ms = load_start.elapsed().as_secs_f64() * 1000.0;
+
+			let start = std::time::Instant::now();
+			let position = search_core::algorithms::binary_search_by_key(&items, &params.id.as_str(), |item| item.id.as_str());
+			(position, items.len(), load_ms, start.elapsed().as_secs_f64() * 1000.0)
+		}
+	};
+	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+	let mem_after = sample_proc_memory_mb();
+	let metric = Metric {
+		timestamp: Local::now().to_rfc3339(),
+		operation: format!("SEARCH_ID_{}", algo.to_uppercase()),
+		execution_time_ms: exec,
+		memory_mb: memory_delta_mb(mem_before, mem_after),
+		network_latency_ms: client_latency,
+		status_code: if position.is_some() { StatusCode::OK } else { StatusCode::NOT_FOUND }.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
+	};
+	record_metric(&metrics, &metric);
+	match position {
+		Some(position) => Ok((Extension(metric), Json(serde_json::json!({
+			"id": params.id,
+			"algo": algo,
+			"position": position,
+			"total_ids": total,
+			"load_time_ms": load_ms,
+			"search_time_ms": exec,
+		})))),
+		None => Err((StatusCode::NOT_FOUND, "Not Found")),
+	}
+}
+
+// Handler for /api/bench/prepare?iterations=N
+// Times the read-one query with and without rusqlite's statement cache on one connection,
+// recording the per-call mean of each as a PREPARE_UNCACHED / PREPARE_CACHED metric
+async fn bench_prepare(metrics: Metrics, Query(params): Query<PrepareBenchParams>) -> Result<Json<PrepareBench>, (StatusCode, &'static str)> {
+	let iterations = params.iterations.unwrap_or(prepare_bench::DEFAULT_ITERATIONS);
+	if iterations == 0 || iterations > prepare_bench::MAX_ITERATIONS {
+		return Err((StatusCode::BAD_REQUEST, "iterations must be between 1 and 100000"));
+	}
+	let mem_before = sample_proc_memory_mb();
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let result = prepare_bench::run(&conn, iterations).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let mem_mb = memory_delta_mb(mem_before, sample_proc_memory_mb());
+	let request_id = request_id::current();
+	for (operation, exec) in [("PREPARE_UNCACHED", result.uncached_ms_per_call), ("PREPARE_CACHED", result.cached_ms_per_call)] {
+		let metric = Metric {
+			timestamp: Local::now().to_rfc3339(),
+			operation: operation.to_string(),
+			execution_time_ms: exec,
+			memory_mb: mem_mb,
+			network_latency_ms: 0.0,
+			status_code: StatusCode::OK.as_u16(),
+			language: default_language(),
+			request_id: request_id.clone(),
+		};
+		record_metric(&metrics, &metric);
+	}
+	Ok(Json(result))
+}
+
 // Handler for /api/metrics
 async fn get_metrics(metrics: Metrics) -> Result<Json<Vec<Metric>>, (StatusCode, &'static str)> {
-	let m = metrics.lock().clone();
+	let m = metrics.store.lock().to_vec();
 	Ok(Json(m))
 }
 
+// Handler for /api/metrics/by_operation/:op?limit=N
+// Prefix match on the operation, newest first
+async fn metrics_by_operation(metrics: Metrics, Path(op): Path<String>, Query(params): Query<ByOperationParams>) -> Json<Vec<Metric>> {
+	Json(metrics.store.lock().by_operation(&op, params.limit))
+}
+
+// Handler for /api/metrics/tail?since=<index>&timeout_ms=
+// Long-poll: returns at once when metrics from `since` on are held, otherwise waits for the next one
+// or the timeout. `next_index` is the `since` of the following call; leave `since` out to start from now.
+// `missed` counts metrics that were evicted from the store before this call could return them.
+// A `since` beyond the store (e.g. from before a restart) returns at once with the current index
+async fn tail_metrics(metrics: Metrics, Query(params): Query<TailParams>) -> Json<Value> {
+	let timeout = std::time::Duration::from_millis(params.timeout_ms.unwrap_or(TAIL_DEFAULT_TIMEOUT_MS).min(TAIL_MAX_TIMEOUT_MS));
+	let deadline = tokio::time::Instant::now() + timeout;
+	let notify = metrics.store.lock().notifier();
+	let since = params.since.unwrap_or_else(|| metrics.store.lock().next_index());
+	loop {
+		let notified = notify.notified();
+		tokio::pin!(notified);
+		notified.as_mut().enable();
+		let (batch, missed, next_index) = {
+			let store = metrics.store.lock();
+			let (batch, missed) = store.since(since);
+			(batch, missed, store.next_index())
+		};
+		let done = !batch.is_empty() || missed > 0 || since > next_index;
+		if done || tokio::time::timeout_at(deadline, notified).await.is_err() {
+			return Json(serde_json::json!({ "metrics": batch, "next_index": next_index, "missed": missed }));
+		}
+	}
+}
+
+// Handler for /api/metrics/compare?a=CREATE&b=BULK_CREATE_100
+// Mean/p95 execution time of each operation plus b relative to a; anything that needs a missing side is null
+async fn compare_metrics(metrics: Metrics, Query(params): Query<CompareParams>) -> Json<Value> {
+	let (a, b) = {
+		let store = metrics.store.lock();
+		(store.operation_stats(&params.a), store.operation_stats(&params.b))
+	};
+	let (mean_ratio, mean_delta_ms, p95_ratio) = match (&a, &b) {
+		(Some(a), Some(b)) => (
+			(a.mean_ms > 0.0).then(|| b.mean_ms / a.mean_ms),
+			Some(b.mean_ms - a.mean_ms),
+			(a.p95_ms > 0.0).then(|| b.p95_ms / a.p95_ms),
+		),
+		_ => (None, None, None),
+	};
+	Json(serde_json::json!({
+		"a": { "operation": params.a, "stats": a },
+		"b": { "operation": params.b, "stats": b },
+		"mean_ratio": mean_ratio,
+		"mean_delta_ms": mean_delta_ms,
+		"p95_ratio": p95_ratio,
+	}))
+}
+
+// Handler for /api/compare
+// Rust vs Python (or any ingested language) per operation, from the in-memory metrics
+async fn compare_languages(metrics: Metrics) -> Json<Vec<LanguageComparison>> {
+	Json(metrics.store.lock().compare_languages())
+}
+
+#[derive(Deserialize, Default)]
+struct TailParams {
+	since: Option<usize>,
+	timeout_ms: Option<u64>,
+}
+
+// Default and longest wait of /api/metrics/tail, kept under the usual 60s proxy idle timeout
+const TAIL_DEFAULT_TIMEOUT_MS: u64 = 25_000;
+const TAIL_MAX_TIMEOUT_MS: u64 = 55_000;
+
+#[derive(Deserialize, Default)]
+struct ByOperationParams {
+	limit: Option<usize>,
+}
+
+#[derive(Deserialize, Default)]
+struct SummaryParams {
+	exact: Option<bool>,
+}
+
+// Handler for /api/metrics/summary
+// Running aggregates with an approximate p95 by default; `?exact=true` recomputes from the in-memory metrics
+async fn metrics_summary(metrics: Metrics, Query(params): Query<SummaryParams>) -> Json<Vec<OperationSummary>> {
+	let store = metrics.store.lock();
+	if params.exact.unwrap_or(false) {
+		Json(store.summary_exact())
+	} else {
+		Json(store.summary())
+	}
+}
+
+// Handler for /api/schema
+// JSON Schema of the Item and Metric models, derived from the structs (schemars follows their serde
+// attributes) so clients generating types can't drift from what the API sends
+async fn get_schema() -> Json<Value> {
+	Json(serde_json::json!({
+		"Item": schemars::schema_for!(Item),
+		"Metric": schemars::schema_for!(Metric),
+	}))
+}
+
+// Handler for /api/stats
+// metrics_write_* is the time spent appending metrics to the CSV, the overhead of measuring itself.
+// worker_threads is read from the running runtime, so it is the pool main actually built rather than
+// WORKER_THREADS re-read (which may be unset, invalid, or changed since startup)
+async fn get_stats(metrics: Metrics) -> Json<serde_json::Value> {
+	let (write_count, write_ms) = metrics.metrics_csv.write_stats();
+	let store = metrics.store.lock();
+	Json(serde_json::json!({
+		"worker_threads": tokio::runtime::Handle::current().metrics().num_workers(),
+		"metrics_capacity": store.capacity(),
+		"metrics_in_memory": store.len(),
+		"metrics_sample_rate": metrics_sample_rate(),
+		"metrics_write_count": write_count,
+		"metrics_write_ms_total": round_for_output(write_ms),
+	}))
+}
+
 // Handler for /api/metrics_ingest
-async fn ingest_metrics(metrics: Metrics, Json(payload): Json<Value>) -> Result<StatusCode, (StatusCode, &'static str)> {
-	let op = payload.get("operation").and_then(|v| v.as_str()).unwrap_or("UNKNOWN").to_string();
-	let net = payload.get("network_latency_ms").and_then(|v| v.as_f64()).unwrap_or(0.0);
-	let exec = payload.get("execution_time_ms").and_then(|v| v.as_f64()).unwrap_or(0.0);
-	let mem = payload.get("memory_mb").and_then(|v| v.as_f64()).unwrap_or(0.0);
+// Metrics from other clients (the fallback UI, the Python side) are checked before they join the stream:
+// `operation` must be a non-empty string, and each numeric field that is present must be a finite number.
+// NaN/Infinity can't be JSON numbers, so they arrive as null (JavaScript's JSON.stringify) or as strings;
+// both are rejected with 400 listing every problem. Absent numeric fields still default to 0
+async fn ingest_metrics(metrics: Metrics, Json(payload): Json<Value>) -> Result<StatusCode, (StatusCode, String)> {
+	let mut problems = Vec::new();
+	let op = match payload.get("operation").and_then(|v| v.as_str()).map(str::trim) {
+		Some(op) if !op.is_empty() => op.to_string(),
+		_ => {
+			problems.push(String::from("operation must be a non-empty string"));
+			String::new()
+		}
+	};
+	let mut number = |field: &str| match payload.get(field) {
+		None => 0.0,
+		Some(v) => match v.as_f64().filter(|n| n.is_finite()) {
+			Some(n) => n,
+			None => {
+				problems.push(format!("{} must be a finite number, got {}", field, v));
+				0.0
+			}
+		},
+	};
+	let net = number("network_latency_ms");
+	let exec = number("execution_time_ms");
+	let mem = number("memory_mb");
+	// 0 = the client didn't report a status
+	let status_code = match payload.get("status_code") {
+		None => 0,
+		Some(v) => match v.as_u64().and_then(|v| u16::try_from(v).ok()).filter(|s| (100..600).contains(s)) {
+			Some(s) => s,
+			None => {
+				problems.push(format!("status_code must be an HTTP status (100-599), got {}", v));
+				0
+			}
+		},
+	};
+	// which implementation measured it, for /api/compare; lowercased so "Python" and "python" group together
+	let language = match payload.get("language") {
+		None => default_language(),
+		Some(v) => match v.as_str().map(str::trim).filter(|l| !l.is_empty()) {
+			Some(l) => l.to_lowercase(),
+			None => {
+				problems.push(format!("language must be a non-empty string, got {}", v));
+				String::new()
+			}
+		},
+	};
+	// the X-Request-Id the client sent with the request it measured, to line up with the server's metric
+	let request_id = match payload.get("request_id") {
+		None | Some(Value::Null) => None,
+		Some(v) => match v.as_str().filter(|id| request_id::valid(id)) {
+			Some(id) => Some(id.to_string()),
+			None => {
+				problems.push(format!("request_id must be a non-empty string of at most 128 visible ASCII characters, got {}", v));
+				None
+			}
+		},
+	};
+	if !problems.is_empty() {
+		return Err((StatusCode::BAD_REQUEST, problems.join("; ")));
+	}
 	let metric = Metric {
 		timestamp: Local::now().to_rfc3339(),
 		operation: op,
 		execution_time_ms: exec,
 		memory_mb: mem,
 		network_latency_ms: net,
+		status_code,
+		language,
+		request_id,
 	};
-	metrics.lock().push(metric.clone());
-	let _ = append_metric_to_csv(&metric);
+	// Measured by the client, so always kept (weight 1) whatever METRICS_SAMPLE_RATE is
+	metrics.store.lock().push(metric.clone());
+	let _ = metrics.metrics_csv.append(&metric);
+	#[cfg(feature = "otel")]
+	crate::otel::record(&metric);
 	Ok(StatusCode::CREATED)
 }
 
+// The insert hit an existing primary key (or another UNIQUE column)
+fn is_unique_violation(e: &rusqlite::Error) -> bool {
+	matches!(e, rusqlite::Error::SqliteFailure(f, _)
+		if f.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_PRIMARYKEY || f.extended_code == rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE)
+}
+
 // Handler for /api/create
-async fn create_item(metrics: Metrics, headers: HeaderMap, Json(payload): Json<Value>) -> Result<StatusCode, (StatusCode, &'static str)> {
-	let name = payload.get("name").and_then(|v| v.as_str()).unwrap_or("").to_string();
-	let description = payload.get("description").and_then(|v| v.as_str()).map(|s| s.to_string());
-	let id = Uuid::new_v4().to_string();
+// `?id=` overrides the generated id, for scripted benchmarks that need known ids
+// A repeated `Idempotency-Key` returns the original response without inserting again
+// A description over MAX_DESC_LEN is 400, or cut to the limit with `?truncate=true`; the stored value is returned
+async fn create_item(metrics: Metrics, headers: HeaderMap, Query(params): Query<CreateParams>, Json(payload): Json<CreateItemRequest>) -> Result<Response, (StatusCode, &'static str)> {
+	let CreateItemRequest { name, description } = payload;
+	let description = limit_description(description, max_desc_len(), params.truncate.unwrap_or(false)).map_err(|_| (StatusCode::BAD_REQUEST, DESCRIPTION_TOO_LONG))?;
+	let id = params.id.unwrap_or_else(generate_id);
+	let idem_key = idempotency::key_from_headers(&headers);
 	let mem_before = sample_proc_memory_mb();
-	let conn = Connection::open("db.sqlite").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let mut conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	// Key lookup, insert and key store commit together. IMMEDIATE takes the write lock up front, so a
+	// concurrent retry with the same key waits here and then finds the stored response instead of inserting
+	let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	if let Some(key) = &idem_key {
+		if let Some(original) = idempotency::lookup(&tx, "create", key).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))? {
+			return Ok((StatusCode::CREATED, Json(original)).into_response());
+		}
+	}
 	let start = std::time::Instant::now();
-	let _ = conn.execute(
+	let body = serde_json::json!({ "id": id, "description": description });
+	// A client `?id=`, or an ID_MODE=v5 sequence restarted since the row was written, can name an existing row.
+	// A failed insert drops the transaction, so the key isn't stored and a retry tries again
+	let status = match tx.execute(
 		"INSERT INTO items (id, name, description) VALUES (?1, ?2, ?3)",
 		params![id.clone(), name.clone(), description.clone()],
-	);
+	) {
+		Ok(_) => {
+			let stored = match &idem_key {
+				Some(key) => idempotency::store(&tx, "create", key, &body),
+				None => Ok(()),
+			};
+			match stored.and_then(|_| tx.commit()) {
+				Ok(()) => StatusCode::CREATED,
+				Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+			}
+		}
+		Err(e) if is_unique_violation(&e) => StatusCode::CONFLICT,
+		Err(_) => StatusCode::INTERNAL_SERVER_ERROR,
+	};
+	if status == StatusCode::CREATED {
+		analytics::invalidate_cache();
+	}
 	let exec = start.elapsed().as_secs_f64() * 1000.0;
 	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
 	let mem_after = sample_proc_memory_mb();
-	let mem_mb = mem_after - mem_before;
+	let mem_mb = memory_delta_mb(mem_before, mem_after);
 	let metric = Metric {
 		timestamp: Local::now().to_rfc3339(),
 		operation: "CREATE".to_string(),
 		execution_time_ms: exec,
 		memory_mb: mem_mb,
 		network_latency_ms: client_latency,
+		status_code: status.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
 	};
-	metrics.lock().push(metric.clone());
-	let _ = append_metric_to_csv(&metric);
-	Ok(StatusCode::CREATED)
+	record_metric(&metrics, &metric);
+	match status {
+		StatusCode::CREATED => {}
+		StatusCode::CONFLICT => return Err((status, "An item with this id already exists")),
+		_ => return Err((status, "DB error")),
+	}
+	Ok((StatusCode::CREATED, Extension(metric), Json(body)).into_response())
 }
 
 // Handler for /api/bulk_create
-async fn bulk_create(metrics: Metrics, headers: HeaderMap, Json(payload): Json<Value>) -> Result<StatusCode, (StatusCode, &'static str)> {
+// Elements are checked one by one, so a bad element is reported by index instead of failing the whole body.
+// By default any failure rolls the transaction back (422, nothing inserted); `?partial=true` skips the
+// failed elements and commits the rest. Descriptions over MAX_DESC_LEN fail their element unless `?truncate=true`.
+// `?batch=N` commits every N valid elements in their own transaction and reports each one's size and time.
+// Every element is checked before the first insert, so a bad element still leaves nothing inserted; only a
+// database error part-way keeps the batches committed before it
+async fn bulk_create(metrics: Metrics, headers: HeaderMap, Query(params): Query<BulkParams>, Json(items): Json<Vec<Value>>) -> Result<Response, (StatusCode, &'static str)> {
+	let partial = params.partial.unwrap_or(false);
+	let (max_len, truncate) = (max_desc_len(), params.truncate.unwrap_or(false));
+	if params.batch == Some(0) {
+		return Err((StatusCode::BAD_REQUEST, "batch must be at least 1"));
+	}
+	let idem_key = idempotency::key_from_headers(&headers);
+	// Separate batch transactions can't hold the key for the whole request, so a retry could insert twice
+	if idem_key.is_some() && params.batch.is_some() {
+		return Err((StatusCode::BAD_REQUEST, "Idempotency-Key can't be combined with ?batch"));
+	}
 	let mem_before = sample_proc_memory_mb();
-	let mut conn = Connection::open("db.sqlite").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let mut conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	if let Some(key) = &idem_key {
+		if let Some(original) = idempotency::lookup(&conn, "bulk_create", key).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))? {
+			return Ok((StatusCode::CREATED, Json(original)).into_response());
+		}
+	}
 	let start = std::time::Instant::now();
-	let items = payload.as_array().ok_or((StatusCode::BAD_REQUEST, "Expected an array of items"))?;
-	let tx = conn.transaction().map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
-	for item in items {
-		let name = item.get("name").and_then(|v| v.as_str()).unwrap_or("");
-		let description = item.get("description").and_then(|v| v.as_str());
-		let id = Uuid::new_v4().to_string();
-		let _ = tx.execute(
-			"INSERT INTO items (id, name, description) VALUES (?1, ?2, ?3)",
-			params![id, name, description],
-		);
+	let mut valid = Vec::with_capacity(items.len());
+	let mut failed = Vec::new();
+	for (index, value) in items.iter().enumerate() {
+		let item = match CreateItemRequest::deserialize(value) {
+			Ok(item) => item,
+			Err(e) => {
+				failed.push(serde_json::json!({ "index": index, "error": e.to_string() }));
+				continue;
+			}
+		};
+		match limit_description(item.description, max_len, truncate) {
+			Ok(description) => valid.push((index, item.name, description)),
+			Err(len) => failed.push(serde_json::json!({ "index": index, "error": format!("description is {} characters, the limit is {}", len, max_len) })),
+		}
 	}
-	tx.commit().map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+
+	let mut inserted = Vec::with_capacity(valid.len());
+	let mut batches = Vec::new();
+	let mut status = StatusCode::CREATED;
+	if !failed.is_empty() && !partial {
+		status = StatusCode::UNPROCESSABLE_ENTITY;
+	} else {
+		// One transaction even for an empty body, which still records the idempotency key
+		let batch_size = params.batch.unwrap_or(valid.len()).max(1);
+		let chunks: Vec<_> = if valid.is_empty() { vec![&valid[..]] } else { valid.chunks(batch_size).collect() };
+		let last = chunks.len() - 1;
+		for (number, chunk) in chunks.into_iter().enumerate() {
+			let batch_start = std::time::Instant::now();
+			// With an Idempotency-Key there is a single batch: look the key up again under the write lock,
+			// in case a concurrent retry stored it after the check above
+			let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+			if let Some(key) = &idem_key {
+				if let Some(original) = idempotency::lookup(&tx, "bulk_create", key).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))? {
+					return Ok((StatusCode::CREATED, Json(original)).into_response());
+				}
+			}
+			let mut chunk_ids = Vec::with_capacity(chunk.len());
+			let failed_before = failed.len();
+			for (index, name, description) in chunk {
+				let id = generate_id();
+				match tx.execute(
+					"INSERT INTO items (id, name, description) VALUES (?1, ?2, ?3)",
+					params![id, name, description],
+				) {
+					Ok(_) => chunk_ids.push(id),
+					Err(e) => failed.push(serde_json::json!({ "index": index, "error": e.to_string() })),
+				}
+			}
+			if failed.len() > failed_before && !partial {
+				// Dropping the transaction rolls back this batch; earlier batches stay committed
+				drop(tx);
+				status = StatusCode::UNPROCESSABLE_ENTITY;
+				break;
+			}
+			inserted.extend(chunk_ids);
+			if number == last {
+				if let Some(key) = &idem_key {
+					let body = serde_json::json!({ "inserted": inserted, "failed": failed });
+					idempotency::store(&tx, "bulk_create", key, &body).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+				}
+			}
+			tx.commit().map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+			batches.push(serde_json::json!({ "size": chunk.len(), "time_ms": batch_start.elapsed().as_secs_f64() * 1000.0 }));
+		}
+		if !inserted.is_empty() {
+			analytics::invalidate_cache();
+		}
+	}
+	let body = match params.batch {
+		Some(_) => serde_json::json!({ "inserted": inserted, "failed": failed, "batches": batches }),
+		None => serde_json::json!({ "inserted": inserted, "failed": failed }),
+	};
 	let exec = start.elapsed().as_secs_f64() * 1000.0;
 	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
 	let mem_after = sample_proc_memory_mb();
-	let mem_mb = mem_after - mem_before;
+	let mem_mb = memory_delta_mb(mem_before, mem_after);
 	let metric = Metric {
 		timestamp: Local::now().to_rfc3339(),
 		operation: format!("BULK_CREATE_{}", items.len()),
 		execution_time_ms: exec,
 		memory_mb: mem_mb,
 		network_latency_ms: client_latency,
+		status_code: status.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
 	};
-	metrics.lock().push(metric.clone());
-	let _ = append_metric_to_csv(&metric);
-	Ok(StatusCode::CREATED)
+	record_metric(&metrics, &metric);
+	Ok((status, Extension(metric), Json(body)).into_response())
 }
 
 // Handler for /api/read
-async fn read_all(metrics: Metrics, headers: HeaderMap) -> Result<Json<Vec<Item>>, (StatusCode, &'static str)> {
+// Responds with CSV when the client sends `Accept: text/csv`, JSON otherwise
+async fn read_all(metrics: Metrics, headers: HeaderMap, Query(params): Query<ReadAllParams>) -> Result<Response, (StatusCode, &'static str)> {
+	let wants_csv = headers.get(header::ACCEPT)
+							   .and_then(|v| v.to_str().ok())
+							   .map(|s| s.contains("text/csv"))
+							   .unwrap_or(false);
+	if let Some(list) = &params.fields {
+		if wants_csv || params.stream.unwrap_or(false) {
+			return Err((StatusCode::BAD_REQUEST, "?fields only applies to the buffered JSON response"));
+		}
+		let fields = parse_fields(list).ok_or((StatusCode::BAD_REQUEST, UNKNOWN_FIELD))?;
+		return read_projected(metrics, &headers, &fields);
+	}
+	if params.stream.unwrap_or(false) && !wants_csv {
+		return Ok(stream_items(metrics, &headers, StreamFormat::Json, "READ_ALL_STREAM"));
+	}
+
 	let mem_before = sample_proc_memory_mb();
 
-	let conn = Connection::open("db.sqlite").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 	
 	let start = std::time::Instant::now();
-	let mut stmt = conn.prepare("SELECT id, name, description FROM items")
+	let mut stmt = conn.prepare(&select_items_sql(""))
 								      .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 
-	let items_iter = stmt.query_map([], |row| {
-																								Ok(Item {
-																									id: row.get(0)?,
-																									name: row.get(1)?,
-																									description: row.get(2).ok(),
-																								})
-																							}).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let items_iter = stmt.query_map([], Item::from_row).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 
 	let exec = start.elapsed().as_secs_f64() * 1000.0;
 	let mut items_vec = Vec::new();
-	for it in items_iter {
-		if let Ok(i) = it { items_vec.push(i); }
+	for i in items_iter.flatten() {
+		items_vec.push(i);
 	}
 	
 	let client_latency = headers.get("x-client-latency-ms")
@@ -155,28 +767,191 @@ async fn read_all(metrics: Metrics, headers: HeaderMap) -> Result<Json<Vec<Item>
 									 .unwrap_or(0.0);
 
 	let mem_after = sample_proc_memory_mb();
-	let mem_mb = mem_after - mem_before;
+	let mem_mb = memory_delta_mb(mem_before, mem_after);
 	let metric = Metric {
 		timestamp: Local::now().to_rfc3339(),
 		operation: "READ_ALL".to_string(),
 		execution_time_ms: exec,
 		memory_mb: mem_mb,
 		network_latency_ms: client_latency,
+		status_code: StatusCode::OK.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
 	};
-	metrics.lock().push(metric.clone());
-	let _ = append_metric_to_csv(&metric);
-	Ok(Json(items_vec))
+	record_metric(&metrics, &metric);
+
+	if wants_csv {
+		let body = items_to_csv(&items_vec).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "CSV error"))?;
+		return Ok(([(header::CONTENT_TYPE, "text/csv")], Extension(metric), body).into_response());
+	}
+	Ok((Extension(metric), Json(items_vec)).into_response())
+}
+
+// /api/read?fields=...: only the requested columns are selected, and each item is an object with just those keys.
+// Recorded as READ_ALL_PROJECTED so it can be compared with READ_ALL
+fn read_projected(metrics: Metrics, headers: &HeaderMap, fields: &[&str]) -> Result<Response, (StatusCode, &'static str)> {
+	let mem_before = sample_proc_memory_mb();
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+
+	let start = std::time::Instant::now();
+	let mut stmt = conn.prepare(&projection_sql(fields)).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let rows = stmt.query_map([], |row| project_row(row, fields)).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let exec = start.elapsed().as_secs_f64() * 1000.0;
+	let items: Vec<Value> = rows.flatten().collect();
+
+	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+	let mem_after = sample_proc_memory_mb();
+	let metric = Metric {
+		timestamp: Local::now().to_rfc3339(),
+		operation: "READ_ALL_PROJECTED".to_string(),
+		execution_time_ms: exec,
+		memory_mb: memory_delta_mb(mem_before, mem_after),
+		network_latency_ms: client_latency,
+		status_code: StatusCode::OK.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
+	};
+	record_metric(&metrics, &metric);
+	Ok((Extension(metric), Json(items)).into_response())
+}
+
+// Output format for streamed item bodies
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum StreamFormat {
+	Json,
+	Sql,
+}
+
+impl StreamFormat {
+	fn content_type(self) -> &'static str {
+		match self {
+			StreamFormat::Json => "application/json",
+			StreamFormat::Sql => "application/sql",
+		}
+	}
+
+	fn header(self) -> &'static [u8] {
+		match self {
+			StreamFormat::Json => b"[",
+			StreamFormat::Sql => b"CREATE TABLE IF NOT EXISTS items (id TEXT PRIMARY KEY, name TEXT NOT NULL, description TEXT);
+CREATE TABLE IF NOT EXISTS tags (id INTEGER PRIMARY KEY, name TEXT NOT NULL UNIQUE);
+CREATE TABLE IF NOT EXISTS item_tags (item_id TEXT NOT NULL, tag_id INTEGER NOT NULL, PRIMARY KEY (item_id, tag_id));\n",
+		}
+	}
+
+	fn footer(self) -> &'static [u8] {
+		match self {
+			StreamFormat::Json => b"]",
+			StreamFormat::Sql => b"",
+		}
+	}
+
+	fn render(self, item: &Item, first: bool) -> Vec<u8> {
+		match self {
+			StreamFormat::Json => {
+				let mut chunk = if first { Vec::new() } else { b",".to_vec() };
+				let _ = serde_json::to_writer(&mut chunk, item);
+				chunk
+			}
+			StreamFormat::Sql => {
+				let quote = |v: &str| format!("'{}'", v.replace('\'', "''"));
+				let mut sql = format!(
+					"INSERT INTO items (id, name, description) VALUES ({}, {}, {});\n",
+					quote(&item.id),
+					quote(&item.name),
+					item.description.as_deref().map(quote).unwrap_or_else(|| "NULL".to_string()),
+				);
+				for tag in &item.tags {
+					sql.push_str(&format!(
+						"INSERT OR IGNORE INTO tags (name) VALUES ({1});\nINSERT OR IGNORE INTO item_tags (item_id, tag_id) SELECT {0}, id FROM tags WHERE name = {1};\n",
+						quote(&item.id),
+						quote(tag),
+					));
+				}
+				sql.into_bytes()
+			}
+		}
+	}
+}
+
+// Streams every item into the response body one row at a time instead of collecting
+// them into a Vec first, so memory stays flat regardless of table size.
+// Used by `/api/read?stream=true` and `/api/export`
+fn stream_items(metrics: Metrics, headers: &HeaderMap, format: StreamFormat, operation: &'static str) -> Response {
+	let client_latency = headers.get("x-client-latency-ms")
+									 .and_then(|v| v.to_str().ok())
+									 .and_then(|s| s.parse::<f64>().ok())
+									 .unwrap_or(0.0);
+	let (tx, rx) = tokio::sync::mpsc::channel::<Result<Vec<u8>, std::io::Error>>(64);
+
+	// The blocking task runs outside the request, so it is handed the sampling decision and request id
+	let sampled = is_sampled();
+	let request_id = request_id::current();
+	tokio::task::spawn_blocking(move || with_sampled(sampled, || {
+		let mem_before = sample_proc_memory_mb();
+		let start = std::time::Instant::now();
+		let streamed = (|| -> rusqlite::Result<()> {
+			let conn = Connection::open(db_path())?;
+			let mut stmt = conn.prepare(&select_items_sql(""))?;
+			let mut rows = stmt.query([])?;
+			if tx.blocking_send(Ok(format.header().to_vec())).is_err() { return Ok(()); }
+			let mut first = true;
+			while let Some(row) = rows.next()? {
+				let item = Item::from_row(row)?;
+				let chunk = format.render(&item, first);
+				first = false;
+				// Receiver dropped means the client went away, stop reading rows
+				if tx.blocking_send(Ok(chunk)).is_err() { return Ok(()); }
+			}
+			let _ = tx.blocking_send(Ok(format.footer().to_vec()));
+			Ok(())
+		})();
+		if let Err(e) = streamed {
+			// Abort the body so the client sees a truncated response rather than invalid output
+			let _ = tx.blocking_send(Err(std::io::Error::other(e.to_string())));
+			return;
+		}
+
+		let exec = start.elapsed().as_secs_f64() * 1000.0;
+		let mem_after = sample_proc_memory_mb();
+		let mem_mb = memory_delta_mb(mem_before, mem_after);
+		let metric = Metric {
+			timestamp: Local::now().to_rfc3339(),
+			operation: operation.to_string(),
+			execution_time_ms: exec,
+			memory_mb: mem_mb,
+			network_latency_ms: client_latency,
+			status_code: StatusCode::OK.as_u16(),
+			language: default_language(),
+			request_id: request_id.clone(),
+		};
+		record_metric(&metrics, &metric);
+	}));
+
+	([(header::CONTENT_TYPE, format.content_type())], StreamBody::new(ReceiverStream::new(rx))).into_response()
+}
+
+#[derive(Deserialize)]
+struct ExportParams {
+	format: Option<StreamFormat>,
+}
+
+// Handler for /api/export
+// Dumps the whole table as a JSON array (default) or as SQL statements reproducing it
+async fn export_db(metrics: Metrics, headers: HeaderMap, Query(params): Query<ExportParams>) -> Response {
+	stream_items(metrics, &headers, params.format.unwrap_or(StreamFormat::Json), "EXPORT")
 }
 
 // Handler for /api/read/:id
-async fn read_one(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>) -> Result<Json<Item>, (StatusCode, &'static str)> {
+async fn read_one(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>) -> Result<(Extension<Metric>, Json<Item>), (StatusCode, &'static str)> {
 	let mem_before = sample_proc_memory_mb();
-	let conn = Connection::open("db.sqlite").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 	let start = std::time::Instant::now();
 	let maybe = conn.query_row(
-												"SELECT id, name, description FROM items WHERE id = ?1",
+												&select_items_sql(" WHERE i.id = ?1"),
 												params![id.clone()],
-												|row| Ok(Item { id: row.get(0)?, name: row.get(1)?, description: row.get(2).ok() }),
+												Item::from_row,
 											)
 											.optional()
 											.map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
@@ -188,134 +963,364 @@ async fn read_one(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>)
 									 .unwrap_or(0.0);
 									
 	let mem_after = sample_proc_memory_mb();
-	let mem_mb = mem_after - mem_before;
+	let mem_mb = memory_delta_mb(mem_before, mem_after);
 	let metric = Metric {
 		timestamp: Local::now().to_rfc3339(),
 		operation: "READ (Description)".to_string(),
 		execution_time_ms: exec,
 		memory_mb: mem_mb,
 		network_latency_ms: client_latency,
+		status_code: if maybe.is_some() { StatusCode::OK } else { StatusCode::NOT_FOUND }.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
 	};
-	metrics.lock().push(metric.clone());
-	let _ = append_metric_to_csv(&metric);
+	record_metric(&metrics, &metric);
 	match maybe {
-		Some(item) => Ok(Json(item)),
+		Some(item) => Ok((Extension(metric), Json(item))),
 		None => Err((StatusCode::NOT_FOUND, "Not Found"))
 	}
 }
 
-// Handler for /api/update/:id
-async fn update_item(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>, Json(payload): Json<Value>) -> Result<StatusCode, (StatusCode, &'static str)> {
-	let conn = Connection::open("db.sqlite").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+// Handler for PUT /api/update/:id: replaces the item, so a missing description is stored as NULL
+async fn replace_item(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>, Query(params): Query<UpdateParams>, Json(payload): Json<CreateItemRequest>) -> Result<(StatusCode, Extension<Metric>, Json<Value>), (StatusCode, &'static str)> {
+	write_item(metrics, headers, id, params, Some(payload.name), Some(payload.description), "UPDATE").await
+}
+
+// Handler for PATCH /api/update/:id: merges, so only the fields in the body change and `"description": null`
+// clears the description. The name can't be cleared, `"name": null` is 422 like a non-string name
+async fn patch_item(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>, Query(params): Query<UpdateParams>, Json(payload): Json<PatchItemRequest>) -> Result<(StatusCode, Extension<Metric>, Json<Value>), (StatusCode, &'static str)> {
+	let name = match payload.name {
+		Some(None) => return Err((StatusCode::UNPROCESSABLE_ENTITY, "name: cannot be null")),
+		name => name.flatten(),
+	};
+	write_item(metrics, headers, id, params, name, payload.description, "PATCH").await
+}
+
+// Shared by PUT and PATCH. `name` None keeps the stored name; `description` None keeps the stored one and
+// Some(None) sets it to NULL. A new description goes through the same MAX_DESC_LEN check as /api/create and
+// is echoed back as stored
+async fn write_item(metrics: Metrics, headers: HeaderMap, id: String, params: UpdateParams, name: Option<String>, description: Option<Option<String>>, operation: &str) -> Result<(StatusCode, Extension<Metric>, Json<Value>), (StatusCode, &'static str)> {
+	let description = match description {
+		Some(d) => Some(limit_description(d, max_desc_len(), params.truncate.unwrap_or(false)).map_err(|_| (StatusCode::BAD_REQUEST, DESCRIPTION_TOO_LONG))?),
+		None => None,
+	};
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 	let mem_before = sample_proc_memory_mb();
-	let mut changed = false;
 	let start = std::time::Instant::now();
-	if let Some(n) = payload.get("name").and_then(|v| v.as_str()) {
-		let _ = conn.execute("UPDATE items SET name = ?1 WHERE id = ?2", params![n, id.clone()]);
-		changed = true;
-	}
-	if let Some(d) = payload.get("description").and_then(|v| v.as_str()) {
-		let _ = conn.execute("UPDATE items SET description = ?1 WHERE id = ?2", params![d, id.clone()]);
-		changed = true;
-	}
+	// Absent fields keep their value; SQLite counts every matched row, so an existing id is affected
+	// even when nothing changes and a missing id is the only way to get 0
+	let affected = conn.execute(
+		"UPDATE items SET name = COALESCE(?1, name), description = CASE WHEN ?2 THEN ?3 ELSE description END WHERE id = ?4",
+		params![name, description.is_some(), description.clone().flatten(), id],
+	).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	analytics::invalidate_cache();
 	let exec = start.elapsed().as_secs_f64() * 1000.0;
-	if changed {
-		let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
-		let mem_after = sample_proc_memory_mb();
-		let mem_mb = mem_after - mem_before;
-		let metric = Metric {
-			timestamp: Local::now().to_rfc3339(),
-			operation: "UPDATE".to_string(),
-			execution_time_ms: exec,
-			memory_mb: mem_mb,
-			network_latency_ms: client_latency,
-		};
-		metrics.lock().push(metric.clone());
-		let _ = append_metric_to_csv(&metric);
-		Ok(StatusCode::OK)
+	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+	let mem_after = sample_proc_memory_mb();
+	let mem_mb = memory_delta_mb(mem_before, mem_after);
+	let metric = Metric {
+		timestamp: Local::now().to_rfc3339(),
+		operation: operation.to_string(),
+		execution_time_ms: exec,
+		memory_mb: mem_mb,
+		network_latency_ms: client_latency,
+		status_code: if affected > 0 { StatusCode::OK } else { StatusCode::NOT_FOUND }.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
+	};
+	record_metric(&metrics, &metric);
+	if affected > 0 {
+		let mut body = serde_json::json!({ "affected": affected });
+		if let Some(description) = description {
+			body["description"] = description.map_or(Value::Null, Value::String);
+		}
+		Ok((StatusCode::OK, Extension(metric), Json(body)))
 	} else {
 		Err((StatusCode::NOT_FOUND, "Not Found"))
 	}
 }
 
 // Handler for /api/delete/:id
-async fn delete_item(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>) -> Result<StatusCode, (StatusCode, &'static str)> {
+async fn delete_item(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>) -> Result<(StatusCode, Extension<Metric>, Json<Value>), (StatusCode, &'static str)> {
 	let mem_before = sample_proc_memory_mb();
-	let conn = Connection::open("db.sqlite").map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 	let start = std::time::Instant::now();
-	let removed = conn.execute("DELETE FROM items WHERE id = ?1", params![id.clone()]).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let affected = conn.execute("DELETE FROM items WHERE id = ?1", params![id.clone()]).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	analytics::invalidate_cache();
+	conn.execute("DELETE FROM item_tags WHERE item_id = ?1", params![id.clone()]).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
 	let exec = start.elapsed().as_secs_f64() * 1000.0;
 	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
 	let mem_after = sample_proc_memory_mb();
-	let mem_mb = mem_after - mem_before;
+	let mem_mb = memory_delta_mb(mem_before, mem_after);
 	let metric = Metric {
 		timestamp: Local::now().to_rfc3339(),
 		operation: "DELETE".to_string(),
 		execution_time_ms: exec,
 		memory_mb: mem_mb,
 		network_latency_ms: client_latency,
+		status_code: if affected > 0 { StatusCode::OK } else { StatusCode::NOT_FOUND }.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
 	};
-	metrics.lock().push(metric.clone());
-	let _ = append_metric_to_csv(&metric);
-	if removed > 0 {
-		Ok(StatusCode::OK)
+	record_metric(&metrics, &metric);
+	if affected > 0 {
+		Ok((StatusCode::OK, Extension(metric), Json(serde_json::json!({ "affected": affected }))))
 	} else {
 		Err((StatusCode::NOT_FOUND, "Not Found"))
 	}
 }
 
 
+// Handler for POST /api/items/:id/tags with body {"tags": ["a", "b"]}
+// Returns the item's full tag list after adding
+async fn add_item_tags(metrics: Metrics, headers: HeaderMap, Path(id): Path<String>, Json(payload): Json<Value>) -> Result<(Extension<Metric>, Json<Value>), (StatusCode, &'static str)> {
+	let new_tags: Vec<String> = payload.get("tags")
+									   .and_then(|v| v.as_array())
+									   .map(|a| a.iter().filter_map(|t| t.as_str()).map(|t| t.trim().to_string()).filter(|t| !t.is_empty()).collect())
+									   .unwrap_or_default();
+	if new_tags.is_empty() {
+		return Err((StatusCode::BAD_REQUEST, "Expected a non-empty \"tags\" array of strings"));
+	}
+	let mem_before = sample_proc_memory_mb();
+	let mut conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	if !tags::item_exists(&conn, &id).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))? {
+		return Err((StatusCode::NOT_FOUND, "Not Found"));
+	}
+	let start = std::time::Instant::now();
+	tags::add_tags(&mut conn, &id, &new_tags).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let exec = start.elapsed().as_secs_f64() * 1000.0;
+	let all_tags = tags::tags_for(&conn, &id).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+	let mem_after = sample_proc_memory_mb();
+	let mem_mb = memory_delta_mb(mem_before, mem_after);
+	let metric = Metric {
+		timestamp: Local::now().to_rfc3339(),
+		operation: "ADD_TAGS".to_string(),
+		execution_time_ms: exec,
+		memory_mb: mem_mb,
+		network_latency_ms: client_latency,
+		status_code: StatusCode::OK.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
+	};
+	record_metric(&metrics, &metric);
+	Ok((Extension(metric), Json(serde_json::json!({ "id": id, "tags": all_tags }))))
+}
 
-pub fn create_app() -> Router {
-	let metrics: Metrics = Arc::new(Mutex::new(Vec::new()));
+// Handler for DELETE /api/items/:id/tags/:tag
+async fn remove_item_tag(metrics: Metrics, headers: HeaderMap, Path((id, tag)): Path<(String, String)>) -> Result<(StatusCode, Extension<Metric>), (StatusCode, &'static str)> {
+	let mem_before = sample_proc_memory_mb();
+	let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let start = std::time::Instant::now();
+	let removed = tags::remove_tag(&conn, &id, &tag).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let exec = start.elapsed().as_secs_f64() * 1000.0;
+	let client_latency = headers.get("x-client-latency-ms").and_then(|v| v.to_str().ok()).and_then(|s| s.parse::<f64>().ok()).unwrap_or(0.0);
+	let mem_after = sample_proc_memory_mb();
+	let mem_mb = memory_delta_mb(mem_before, mem_after);
+	let metric = Metric {
+		timestamp: Local::now().to_rfc3339(),
+		operation: "REMOVE_TAG".to_string(),
+		execution_time_ms: exec,
+		memory_mb: mem_mb,
+		network_latency_ms: client_latency,
+		status_code: if removed { StatusCode::OK } else { StatusCode::NOT_FOUND }.as_u16(),
+		language: default_language(),
+		request_id: request_id::current(),
+	};
+	record_metric(&metrics, &metric);
+	if removed {
+		Ok((StatusCode::OK, Extension(metric)))
+	} else {
+		Err((StatusCode::NOT_FOUND, "Not Found"))
+	}
+}
 
-	// Ensure database file and table exist
-	let db_path = "db.sqlite";
-	let mut created = false;
-	if !std::path::Path::new(db_path).exists() {
-		created = true;
+// Handler for /api/db/download, only routed when ALLOW_DB_DOWNLOAD=true
+// Checkpoints the WAL into the main file first, then streams the file in chunks
+async fn download_db() -> Result<Response, (StatusCode, &'static str)> {
+	let path = db_path();
+	let conn = Connection::open(&path).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	conn.query_row("PRAGMA wal_checkpoint(TRUNCATE)", [], |_| Ok(())).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	drop(conn);
+	let mut file = std::fs::File::open(&path).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+	let filename = std::path::Path::new(&path).file_name().and_then(|n| n.to_str()).unwrap_or("db.sqlite").to_string();
+
+	let (tx, rx) = tokio::sync::mpsc::channel::<Result<Vec<u8>, std::io::Error>>(16);
+	tokio::task::spawn_blocking(move || {
+		use std::io::Read;
+		let mut buf = vec![0u8; 64 * 1024];
+		loop {
+			match file.read(&mut buf) {
+				Ok(0) => break,
+				Ok(n) => if tx.blocking_send(Ok(buf[..n].to_vec())).is_err() { break },
+				Err(e) => { let _ = tx.blocking_send(Err(e)); break }
+			}
+		}
+	});
+
+	Ok((
+		[
+			(header::CONTENT_TYPE, "application/x-sqlite3".to_string()),
+			(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+		],
+		StreamBody::new(ReceiverStream::new(rx)),
+	).into_response())
+}
+
+// Fallback when the static directory is missing: a minimal index page at / so a browser shows
+// something useful, and a 404 naming the directory for anything else
+async fn missing_static(static_dir: String, uri: axum::http::Uri) -> Response {
+	if uri.path() == "/" || uri.path() == "/index.html" {
+		let page = format!(
+			"<!DOCTYPE html><html><head><meta charset=\"utf-8\"/><title>Leptos CRUD</title></head><body>\
+			<h2>Leptos CRUD with Metrics</h2>\
+			<p>The API is running, but the static directory <code>{}</code> was not found, so the UI isn't available. \
+			Start the server from <code>LEPTOS_RUST/server</code> or set <code>STATIC_DIR</code>.</p>\
+			<p>Try <a href=\"/api/read\">/api/read</a>, <a href=\"/api/metrics\">/api/metrics</a> or <a href=\"/health\">/health</a>.</p>\
+			</body></html>",
+			static_dir
+		);
+		return axum::response::Html(page).into_response();
 	}
-	let conn = Connection::open(db_path).expect("failed to open sqlite db");
-	conn.execute(
-		"CREATE TABLE IF NOT EXISTS items (
-			id TEXT PRIMARY KEY,
-			name TEXT NOT NULL,
-			description TEXT
-		)",
-		[],
-	).expect("failed to create items table");
+	(StatusCode::NOT_FOUND, format!("Not Found: {} (no static directory at {})", uri.path(), static_dir)).into_response()
+}
+
+// Handler for /ready
+// create_app migrates (and fails startup if it can't) before the server listens, so there is no window in
+// which the schema is behind: whenever this answers, it is 200 with the version the database is at
+async fn ready(schema_version: u32) -> Json<Value> {
+	Json(serde_json::json!({ "ready": true, "schema_version": schema_version, "latest_version": migrations::latest_version() }))
+}
 
-	// add a sample item only if DB was just created
+// Applies the migrations and, for a database that didn't exist yet, adds the SEED_ITEMS items (or a sample
+// item). Runs before the router is built, so no request ever sees an old or missing schema. Returns the
+// schema version reached
+fn prepare_database(db_path: &str) -> Result<u32, String> {
+	let created = !std::path::Path::new(db_path).exists();
+	// Read before the file is created, so a bad seed file doesn't leave an unseeded database behind
+	let seed = match seed_items_path() {
+		Some(path) if created => Some((read_seed_items(&path).map_err(|e| format!("invalid SEED_ITEMS file: {}", e))?, path)),
+		_ => None,
+	};
+	let mut conn = Connection::open(db_path).map_err(|e| format!("failed to open {}: {}", db_path, e))?;
+	let version = migrations::run(&mut conn).map_err(|e| format!("database migration failed: {}", e))?;
 	if created {
-		let id = Uuid::new_v4().to_string();
-		let _ = conn.execute(
-			"INSERT INTO items (id, name, description) VALUES (?1, ?2, ?3)",
-			params![id.clone(), "Example Item", Some("This is an example description")],
-		);
+		let seeded = (|| -> rusqlite::Result<usize> {
+			let tx = conn.transaction()?;
+			let count = match &seed {
+				Some((items, _)) => {
+					for item in items {
+						tx.execute(
+							"INSERT INTO items (id, name, description) VALUES (?1, ?2, ?3)",
+							params![generate_id(), item.name, item.description],
+						)?;
+					}
+					items.len()
+				}
+				None => {
+					tx.execute(
+						"INSERT INTO items (id, name, description) VALUES (?1, ?2, ?3)",
+						params![generate_id(), "Example Item", Some("This is an example description")],
+					)?;
+					1
+				}
+			};
+			tx.commit()?;
+			Ok(count)
+		})().map_err(|e| format!("failed to seed items: {}", e))?;
+		if let Some((_, path)) = &seed {
+			println!("Seeded {} item(s) from {}", seeded, path);
+		}
 	}
-	drop(conn);
+	analytics::invalidate_cache();
+	Ok(version)
+}
+
+// Fails when the database can't be migrated or seeded; main reports it and exits before binding the port
+pub fn create_app() -> Result<Router, String> {
+	create_app_with_state().map(|(app, _)| app)
+}
+
+// create_app plus the state its handlers share, for main to flush the metrics CSV on an interval and at shutdown
+pub fn create_app_with_state() -> Result<(Router, Arc<AppState>), String> {
+	let metrics: Metrics = Arc::new(AppState {
+		store: Mutex::new(MetricStore::new(metrics_capacity())),
+		metrics_csv: MetricsCsvWriter::from_env(),
+	});
+	let schema_version = prepare_database(&db_path())?;
 
-	Router::new()
+	let app = Router::new()
+		.route("/health", get(|| async { StatusCode::OK }))
+		.route("/ready", get(move || ready(schema_version)))
 		.route("/api/database", get(get_database))
+		.route("/api/items/count", get({
+			let metrics = metrics.clone();
+			move |headers| count_items(metrics.clone(), headers)
+		}))
+		.route("/api/items/random", get({
+			let metrics = metrics.clone();
+			move |headers, query| random_items(metrics.clone(), headers, query)
+		}))
+		.route("/api/items/analytics", get({
+			let metrics = metrics.clone();
+			move |headers| item_analytics(metrics.clone(), headers)
+		}))
+		.route("/api/items/search_id", get({
+			let metrics = metrics.clone();
+			move |headers, query| search_item_id(metrics.clone(), headers, query)
+		}))
 		.route("/api/bulk_create", post({
 			let metrics = metrics.clone();
-			move |headers, payload| bulk_create(metrics.clone(), headers, payload)
+			move |headers, query, payload| bulk_create(metrics.clone(), headers, query, payload)
+		}))
+		.route("/api/bench/prepare", get({
+			let metrics = metrics.clone();
+			move |query| bench_prepare(metrics.clone(), query)
 		}))
 		.route("/api/metrics", get({
 			let metrics = metrics.clone();
 			move || get_metrics(metrics.clone())
 		}))
+		.route("/api/metrics/by_operation/:op", get({
+			let metrics = metrics.clone();
+			move |path, query| metrics_by_operation(metrics.clone(), path, query)
+		}))
+		.route("/api/metrics/tail", get({
+			let metrics = metrics.clone();
+			move |query| tail_metrics(metrics.clone(), query)
+		}))
+		.route("/api/metrics/compare", get({
+			let metrics = metrics.clone();
+			move |query| compare_metrics(metrics.clone(), query)
+		}))
+		.route("/api/compare", get({
+			let metrics = metrics.clone();
+			move || compare_languages(metrics.clone())
+		}))
+		.route("/api/metrics/summary", get({
+			let metrics = metrics.clone();
+			move |query| metrics_summary(metrics.clone(), query)
+		}))
+		.route("/api/schema", get(get_schema))
+		.route("/api/stats", get({
+			let metrics = metrics.clone();
+			move || get_stats(metrics.clone())
+		}))
 		.route("/api/metrics_ingest", post({
 			let metrics = metrics.clone();
 			move |payload| ingest_metrics(metrics.clone(), payload)
 		}))
 		.route("/api/create", post({
 			let metrics = metrics.clone();
-			move |headers, payload| create_item(metrics.clone(), headers, payload)
+			move |headers, query, payload| create_item(metrics.clone(), headers, query, payload)
 		}))
 		.route("/api/read", get({
 			let metrics = metrics.clone();
-			move |headers| read_all(metrics.clone(), headers)
+			move |headers, query| read_all(metrics.clone(), headers, query)
+		}))
+		.route("/api/export", get({
+			let metrics = metrics.clone();
+			move |headers, query| export_db(metrics.clone(), headers, query)
 		}))
 		.route("/api/read/:id", get({
 			let metrics = metrics.clone();
@@ -323,14 +1328,50 @@ pub fn create_app() -> Router {
 		}))
 		.route("/api/update/:id", put({
 			let metrics = metrics.clone();
-			move |headers, path, payload| update_item(metrics.clone(), headers, path, payload)
+			move |headers, path, query, payload| replace_item(metrics.clone(), headers, path, query, payload)
+		}).patch({
+			let metrics = metrics.clone();
+			move |headers, path, query, payload| patch_item(metrics.clone(), headers, path, query, payload)
+		}))
+		.route("/api/items/:id/tags", post({
+			let metrics = metrics.clone();
+			move |headers, path, body| add_item_tags(metrics.clone(), headers, path, body)
+		}))
+		.route("/api/items/:id/tags/:tag", delete({
+			let metrics = metrics.clone();
+			move |headers, path| remove_item_tag(metrics.clone(), headers, path)
 		}))
 		.route("/api/delete/:id", delete({
 			let metrics = metrics.clone();
 			move |headers, path| delete_item(metrics.clone(), headers, path)
-		}))
-		// serve static files (including fallback index) from workspace root
-		.fallback_service(axum::routing::get_service(tower_http::services::ServeDir::new("../static")).handle_error(|err| async move {
+		}));
+	// Not routed at all unless enabled, so it 404s like any unknown path
+	let app = if allow_db_download() {
+		app.route("/api/db/download", get(download_db))
+	} else {
+		app
+	};
+	// serve static files (including fallback index) from workspace root
+	let static_dir = static_dir();
+	let app = if std::path::Path::new(&static_dir).is_dir() {
+		app.fallback_service(axum::routing::get_service(tower_http::services::ServeDir::new(&static_dir)).handle_error(|err| async move {
 			(StatusCode::INTERNAL_SERVER_ERROR, format!("Unhandled internal error: {}", err))
 		}))
+	} else {
+		eprintln!("Warning: static directory {} not found, serving a built-in index page (set STATIC_DIR to point at LEPTOS_RUST/static)", static_dir);
+		app.fallback(move |uri: axum::http::Uri| missing_static(static_dir.clone(), uri))
+	};
+	let app = app.layer(middleware::from_fn(timing_envelope));
+	let app = app.layer(middleware::from_fn(sample_request));
+
+	// Optional token-bucket limit (RATE_LIMIT_RPS) so load tests run at a controlled, steady rate
+	let app = match rate_limit_rps() {
+		Some(rps) => {
+			let limiter = Arc::new(Mutex::new(TokenBucket::new(rps)));
+			app.layer(middleware::from_fn(move |req, next| rate_limit(limiter.clone(), req, next)))
+		}
+		None => app,
+	};
+	// Outermost, so even a rate-limited 429 carries an X-Request-Id
+	Ok((app.layer(middleware::from_fn(request_id)), metrics))
 }
@@ -0,0 +1,41 @@
+use axum::http::HeaderMap;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::Value;
+
+// Responses remembered per `Idempotency-Key` header, so a client retrying a create after a
+// network blip gets the original result back instead of inserting a duplicate row.
+// Keys are scoped per endpoint and stored next to the items so they survive restarts.
+
+pub const CREATE_TABLE_SQL: &str = "CREATE TABLE IF NOT EXISTS idempotency_keys (
+	endpoint TEXT NOT NULL,
+	key TEXT NOT NULL,
+	response TEXT NOT NULL,
+	PRIMARY KEY (endpoint, key)
+)";
+
+pub fn key_from_headers(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("idempotency-key")
+        .and_then(|v| v.to_str().ok())
+        .filter(|s| !s.is_empty())
+        .map(|s| s.to_string())
+}
+
+pub fn lookup(conn: &Connection, endpoint: &str, key: &str) -> rusqlite::Result<Option<Value>> {
+    let stored: Option<String> = conn
+        .query_row(
+            "SELECT response FROM idempotency_keys WHERE endpoint = ?1 AND key = ?2",
+            params![endpoint, key],
+            |row| row.get(0),
+        )
+        .optional()?;
+    Ok(stored.and_then(|s| serde_json::from_str(&s).ok()))
+}
+
+pub fn store(conn: &Connection, endpoint: &str, key: &str, response: &Value) -> rusqlite::Result<()> {
+    conn.execute(
+        "INSERT OR IGNORE INTO idempotency_keys (endpoint, key, response) VALUES (?1, ?2, ?3)",
+        params![endpoint, key, response.to_string()],
+    )?;
+    Ok(())
+}
@@ -1,8 +1,142 @@
-use serde::{Serialize, Deserialize};
+use rusqlite::Row;
+use schemars::JsonSchema;
+use serde::{Deserialize, Deserializer, Serialize};
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+use crate::tags;
+use crate::utils::max_desc_len;
+
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug, PartialEq)]
 pub struct Item {
     pub id: String,
     pub name: String,
     pub description: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Item {
+    // Columns `from_row` reads, in order, over `items i` joined with its tags (see tags::select_items_sql).
+    // A new column goes here and in from_row, nowhere else
+    pub const SELECT_COLUMNS: &'static str = "i.id, i.name, i.description, GROUP_CONCAT(t.name, char(31))";
+
+    pub fn from_row(row: &Row) -> rusqlite::Result<Item> {
+        Ok(Item {
+            id: row.get(0)?,
+            name: row.get(1)?,
+            description: row.get(2)?,
+            tags: tags::split_tags(row.get(3)?),
+        })
+    }
+}
+
+// Fields `/api/read?fields=` may project, with the column expression each one selects
+const PROJECTABLE: [(&str, &str); 4] = [
+    ("id", "i.id"),
+    ("name", "i.name"),
+    ("description", "i.description"),
+    ("tags", "GROUP_CONCAT(t.name, char(31))"),
+];
+
+// Comma-separated field names from `?fields=`, checked against PROJECTABLE. Duplicates are dropped,
+// the first occurrence keeps its place. None if any name is unknown or empty
+pub fn parse_fields(list: &str) -> Option<Vec<&'static str>> {
+    let mut fields = Vec::new();
+    for name in list.split(',').map(str::trim) {
+        let field = PROJECTABLE.iter().map(|(f, _)| *f).find(|f| *f == name)?;
+        if !fields.contains(&field) {
+            fields.push(field);
+        }
+    }
+    Some(fields)
+}
+
+// SELECT for just `fields`, in insertion order like tags::select_items_sql. The tag joins are only
+// made when `tags` is requested, so `?fields=id,name` reads the items table alone
+pub fn projection_sql(fields: &[&str]) -> String {
+    let columns: Vec<&str> = fields
+        .iter()
+        .filter_map(|f| PROJECTABLE.iter().find(|(name, _)| name == f).map(|(_, column)| *column))
+        .collect();
+    if fields.contains(&"tags") {
+        format!(
+            "SELECT {}
+FROM items i
+LEFT JOIN item_tags it ON it.item_id = i.id
+LEFT JOIN tags t ON t.id = it.tag_id GROUP BY i.id ORDER BY i.rowid",
+            columns.join(", ")
+        )
+    } else {
+        format!("SELECT {} FROM items i ORDER BY i.rowid", columns.join(", "))
+    }
+}
+
+// One row of projection_sql as a JSON object holding only `fields`
+pub fn project_row(row: &Row, fields: &[&str]) -> rusqlite::Result<serde_json::Value> {
+    let mut object = serde_json::Map::new();
+    for (index, field) in fields.iter().enumerate() {
+        let value = match *field {
+            "tags" => serde_json::json!(tags::split_tags(row.get(index)?)),
+            "description" => serde_json::json!(row.get::<_, Option<String>>(index)?),
+            _ => serde_json::json!(row.get::<_, String>(index)?),
+        };
+        object.insert(field.to_string(), value);
+    }
+    Ok(serde_json::Value::Object(object))
+}
+
+// Body of POST /api/create, of each element of POST /api/bulk_create, and of PUT /api/update/:id (a full replace).
+// A missing or non-string `name` is rejected by the Json extractor with 422 before the handler runs.
+// Unknown fields are ignored so /api/export output (which carries id and tags) can be posted back
+#[derive(Deserialize, Debug)]
+pub struct CreateItemRequest {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+// Body of PATCH /api/update/:id; only the fields present are changed. The outer Option says whether the field
+// was sent at all and the inner one whether it was null, so `{"description": null}` clears the description
+// while leaving it out keeps it. PUT replaces the whole item and takes a CreateItemRequest instead
+#[derive(Deserialize, Debug)]
+pub struct PatchItemRequest {
+    #[serde(default, deserialize_with = "present")]
+    pub name: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub description: Option<Option<String>>,
+}
+
+// Only called for a field that is in the body, so wrapping in Some marks it present; with `default` an absent
+// field stays None. Plain Option<Option<T>> would read null as absent
+fn present<'de, D: Deserializer<'de>, T: Deserialize<'de>>(deserializer: D) -> Result<Option<T>, D::Error> {
+    T::deserialize(deserializer).map(Some)
+}
+
+// Applies the description length limit (counted in characters). Over the limit is an error carrying the
+// description's length, unless `truncate`, which keeps the first `max_len` characters instead
+pub fn limit_description(description: Option<String>, max_len: usize, truncate: bool) -> Result<Option<String>, usize> {
+    match description {
+        Some(d) => match d.char_indices().nth(max_len) {
+            None => Ok(Some(d)),
+            Some((cut, _)) if truncate => Ok(Some(d[..cut].to_string())),
+            Some(_) => Err(d.chars().count()),
+        },
+        None => Ok(None),
+    }
+}
+
+// Items to start a fresh database with, from the SEED_ITEMS JSON file: an array of /api/create bodies.
+// Every element must have a string name and a description within MAX_DESC_LEN; the first bad one is reported by index
+pub fn read_seed_items(path: &str) -> Result<Vec<CreateItemRequest>, String> {
+    let text = std::fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+    let values: Vec<serde_json::Value> = serde_json::from_str(&text).map_err(|e| format!("{} is not a JSON array: {}", path, e))?;
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(index, value)| {
+            let item = CreateItemRequest::deserialize(value).map_err(|e| format!("{} element {}: {}", path, index, e))?;
+            limit_description(item.description.clone(), max_desc_len(), false)
+                .map_err(|len| format!("{} element {}: description is {} characters, the limit is {}", path, index, len, max_desc_len()))?;
+            Ok(item)
+        })
+        .collect()
 }
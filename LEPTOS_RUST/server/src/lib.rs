@@ -1,4 +1,16 @@
 pub mod utils;
 pub mod item;
 pub mod metric;
+pub mod quantile;
 pub mod handlers;
+pub mod rate_limit;
+pub mod idempotency;
+pub mod migrations;
+pub mod tags;
+pub mod timing;
+pub mod prepare_bench;
+pub mod analytics;
+pub mod sampling;
+pub mod request_id;
+#[cfg(feature = "otel")]
+pub mod otel;
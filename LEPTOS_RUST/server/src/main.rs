@@ -1,12 +1,98 @@
-use server::handlers;
+use server::{handlers, utils};
 use std::net::SocketAddr;
+use std::time::Duration;
 
 
-#[tokio::main]
-async fn main() {
-    let app = handlers::create_app();
+fn main() {
+    // Worker thread count comes from WORKER_THREADS so it can be reported alongside benchmark numbers
+    let threads = utils::worker_threads();
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(threads)
+        .enable_all()
+        .build()
+        .expect("failed to build tokio runtime")
+        .block_on(serve(threads));
+}
+
+// Both listeners speak HTTP/1.1 and HTTP/2: negotiated through ALPN over TLS,
+// and by prior knowledge (h2c) over plain HTTP
+async fn serve(threads: usize) {
+    // Migrations and seeding finish here, before the port is bound
+    let (app, state) = handlers::create_app_with_state().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    #[cfg(feature = "otel")]
+    server::otel::init();
     let addr = SocketAddr::from(([127, 0, 0, 1], 3000));
-    
-    println!("Listening on http://{}", addr);
-    axum::Server::bind(&addr).serve(app.into_make_service()).await.unwrap();
-}
\ No newline at end of file
+
+    // Buffered metric rows reach the CSV at least this often
+    tokio::spawn({
+        let state = state.clone();
+        async move {
+            let mut interval = tokio::time::interval(utils::metrics_flush_interval());
+            loop {
+                interval.tick().await;
+                let _ = state.metrics_csv.flush();
+            }
+        }
+    });
+
+    match utils::tls_paths() {
+        Some((cert, key)) => {
+            // Reported like a create_app failure rather than a panic, before the port is bound
+            let config = utils::load_tls_config(&cert, &key).await.unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            });
+            let handle = axum_server::Handle::new();
+            tokio::spawn({
+                let handle = handle.clone();
+                async move {
+                    shutdown_signal().await;
+                    handle.graceful_shutdown(Some(Duration::from_secs(5)));
+                }
+            });
+            println!("Listening on https://{} ({} worker threads)", addr, threads);
+            axum_server::bind_rustls(addr, config).handle(handle).serve(app.into_make_service()).await.unwrap();
+        }
+        None => {
+            println!("Listening on http://{} ({} worker threads)", addr, threads);
+            axum::Server::bind(&addr)
+                .serve(app.into_make_service())
+                .with_graceful_shutdown(shutdown_signal())
+                .await
+                .unwrap();
+        }
+    }
+
+    // Rows still buffered since the last flush
+    if let Err(e) = state.metrics_csv.flush() {
+        eprintln!("Failed to flush the metrics CSV on shutdown: {}", e);
+    }
+    #[cfg(feature = "otel")]
+    server::otel::shutdown();
+}
+
+// Ctrl+C, or SIGTERM on Unix (e.g. `kill`, a container stop)
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        let _ = tokio::signal::ctrl_c().await;
+    };
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
+    println!("Shutting down");
+}
@@ -1,10 +1,329 @@
-use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use serde::{Serialize, Serializer, Deserialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::sync::Arc;
+use tokio::sync::Notify;
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+use bench_utils::stats::{mean_std, Welford};
+
+use crate::quantile::P2Quantile;
+use crate::utils::metric_decimals;
+
+// The float fields keep full precision in memory (percentiles use them as-is) and are rounded
+// to METRIC_DECIMALS only when serialized to JSON or the CSV
+#[derive(Clone, Serialize, Deserialize, JsonSchema, Debug)]
 pub struct Metric {
     pub timestamp: String,
     pub operation: String,
+    #[serde(serialize_with = "serialize_rounded")]
     pub execution_time_ms: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     pub memory_mb: f64,
+    #[serde(serialize_with = "serialize_rounded")]
     pub network_latency_ms: f64,
+    // HTTP status the handler responded with; 0 when an ingested metric didn't report one
+    #[serde(default)]
+    pub status_code: u16,
+    // Implementation that measured it: "rust" for this server, whatever an ingested metric says otherwise
+    #[serde(default = "default_language")]
+    pub language: String,
+    // X-Request-Id of the request it measured (see request_id.rs); for an ingested metric, the id the
+    // client sent with its original request, if it reports one
+    #[serde(default)]
+    pub request_id: Option<String>,
+}
+
+// Language of the server's own metrics, and the baseline of /api/compare's speedups
+pub const DEFAULT_LANGUAGE: &str = "rust";
+
+pub fn default_language() -> String {
+    DEFAULT_LANGUAGE.to_string()
+}
+
+// `v` rounded to METRIC_DECIMALS places, for output only
+pub fn round_for_output(v: f64) -> f64 {
+    let scale = 10f64.powi(metric_decimals() as i32);
+    (v * scale).round() / scale
+}
+
+fn serialize_rounded<S: Serializer>(v: &f64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(round_for_output(*v))
+}
+
+impl Metric {
+    pub fn succeeded(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+}
+
+// Running totals for one operation, updated on every push and never evicted
+struct OperationAggregate {
+    operation: String,
+    samples: usize,
+    // requests the samples stand for: each sampled metric weighs 1 / METRICS_SAMPLE_RATE
+    estimated: f64,
+    // execution time mean and spread
+    times: Welford,
+    reported: usize,
+    successes: usize,
+    p95: P2Quantile,
+}
+
+impl OperationAggregate {
+    fn new(operation: &str) -> Self {
+        OperationAggregate {
+            operation: operation.to_string(),
+            samples: 0,
+            estimated: 0.0,
+            times: Welford::new(),
+            reported: 0,
+            successes: 0,
+            p95: P2Quantile::new(0.95),
+        }
+    }
+
+    fn add(&mut self, metric: &Metric, weight: f64) {
+        self.samples += 1;
+        self.estimated += weight;
+        self.times.push(metric.execution_time_ms);
+        self.p95.add(metric.execution_time_ms);
+        if metric.status_code != 0 {
+            self.reported += 1;
+        }
+        if metric.succeeded() {
+            self.successes += 1;
+        }
+    }
+}
+
+// Bounded in-memory metric log. Once full, each push drops the oldest entry so a long
+// benchmark run doesn't grow the very memory it is measuring; the CSV sink keeps everything.
+// Per-operation aggregates (approximate p95) cover every metric pushed since startup
+pub struct MetricStore {
+    // each metric with its weight, see `push_weighted`
+    entries: VecDeque<(Metric, f64)>,
+    capacity: usize,
+    // in order of first appearance, indexed by operation
+    aggregates: Vec<OperationAggregate>,
+    aggregate_index: HashMap<String, usize>,
+    // metrics pushed since startup, evicted ones included: the cursor of /api/metrics/tail
+    pushed: usize,
+    // woken on every push, for long-polling tails
+    notify: Arc<Notify>,
+}
+
+impl MetricStore {
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        MetricStore {
+            entries: VecDeque::with_capacity(capacity),
+            capacity,
+            aggregates: Vec::new(),
+            aggregate_index: HashMap::new(),
+            pushed: 0,
+            notify: Arc::new(Notify::new()),
+        }
+    }
+
+    pub fn push(&mut self, metric: Metric) {
+        self.push_weighted(metric, 1.0);
+    }
+
+    // `weight` is the number of requests the metric stands for: 1 / METRICS_SAMPLE_RATE for
+    // a sampled server metric (see sampling.rs), 1 for an ingested one
+    pub fn push_weighted(&mut self, metric: Metric, weight: f64) {
+        let idx = match self.aggregate_index.get(&metric.operation) {
+            Some(&idx) => idx,
+            None => {
+                self.aggregates.push(OperationAggregate::new(&metric.operation));
+                self.aggregate_index.insert(metric.operation.clone(), self.aggregates.len() - 1);
+                self.aggregates.len() - 1
+            }
+        };
+        self.aggregates[idx].add(&metric, weight);
+        if self.entries.len() == self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((metric, weight));
+        self.pushed += 1;
+        self.notify.notify_waiters();
+    }
+
+    // Index the next pushed metric will get
+    pub fn next_index(&self) -> usize {
+        self.pushed
+    }
+
+    // Metrics pushed at index `since` or later that are still held, oldest first, and how many of
+    // those were already evicted to make room
+    pub fn since(&self, since: usize) -> (Vec<Metric>, usize) {
+        let oldest = self.pushed - self.entries.len();
+        let skip = since.saturating_sub(oldest);
+        let missed = oldest.saturating_sub(since);
+        (self.iter().skip(skip).cloned().collect(), missed)
+    }
+
+    // Notified on every push. Waiters must call `Notified::enable` before reading `next_index`,
+    // or a push in between is missed
+    pub fn notifier(&self) -> Arc<Notify> {
+        self.notify.clone()
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &Metric> {
+        self.entries.iter().map(|(m, _)| m)
+    }
+
+    pub fn to_vec(&self) -> Vec<Metric> {
+        self.iter().cloned().collect()
+    }
+
+    // Metrics whose operation starts with `prefix` (so BULK_CREATE covers BULK_CREATE_100), newest first,
+    // at most `limit` of them
+    pub fn by_operation(&self, prefix: &str, limit: Option<usize>) -> Vec<Metric> {
+        self.iter().rev()
+            .filter(|m| m.operation.starts_with(prefix))
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+
+    // Execution time summary for one operation label, None if it has no samples in memory
+    pub fn operation_stats(&self, operation: &str) -> Option<OperationStats> {
+        let times: Vec<f64> = self.iter()
+            .filter(|m| m.operation == operation)
+            .map(|m| m.execution_time_ms)
+            .collect();
+        OperationStats::from_times(times)
+    }
+
+    // Per-operation timing and success rate since startup, in order of each operation's first appearance.
+    // O(operations): reads the running aggregates, p95 is a P² estimate
+    pub fn summary(&self) -> Vec<OperationSummary> {
+        self.aggregates.iter().map(|agg| {
+            let successes = agg.successes;
+            OperationSummary {
+                operation: agg.operation.clone(),
+                stats: OperationStats {
+                    samples: agg.samples,
+                    mean_ms: agg.times.mean(),
+                    std_ms: agg.times.std(),
+                    p95_ms: agg.p95.estimate().unwrap_or(0.0),
+                },
+                estimated_count: agg.estimated.round() as u64,
+                successes,
+                failures: agg.reported - successes,
+                success_rate: (agg.reported > 0).then(|| successes as f64 / agg.reported as f64),
+            }
+        }).collect()
+    }
+
+    // Per operation (in order of first appearance), the stats of each language that reported it, computed
+    // exactly over the metrics still in memory like `summary_exact`
+    pub fn compare_languages(&self) -> Vec<LanguageComparison> {
+        let mut operations: Vec<&str> = Vec::new();
+        let mut times: HashMap<&str, BTreeMap<&str, Vec<f64>>> = HashMap::new();
+        for m in self.iter() {
+            let by_language = times.entry(&m.operation).or_insert_with(|| {
+                operations.push(&m.operation);
+                BTreeMap::new()
+            });
+            by_language.entry(&m.language).or_default().push(m.execution_time_ms);
+        }
+        operations.into_iter().map(|op| {
+            let languages: BTreeMap<String, OperationStats> = times.remove(op).unwrap_or_default().into_iter()
+                .filter_map(|(lang, t)| Some((lang.to_string(), OperationStats::from_times(t)?)))
+                .collect();
+            let speedup = match languages.get(DEFAULT_LANGUAGE) {
+                Some(base) if base.mean_ms > 0.0 => languages.iter()
+                    .filter(|(lang, _)| lang.as_str() != DEFAULT_LANGUAGE)
+                    .map(|(lang, stats)| (lang.clone(), stats.mean_ms / base.mean_ms))
+                    .collect(),
+                _ => BTreeMap::new(),
+            };
+            LanguageComparison { operation: op.to_string(), languages, speedup }
+        }).collect()
+    }
+
+    // Same shape as `summary`, computed exactly (sorted p95) over the metrics still in memory.
+    // O(n log n) per call; for validating the approximate figures
+    pub fn summary_exact(&self) -> Vec<OperationSummary> {
+        let mut operations: Vec<&str> = Vec::new();
+        for m in self.iter() {
+            if !operations.contains(&m.operation.as_str()) {
+                operations.push(&m.operation);
+            }
+        }
+        operations.into_iter().filter_map(|op| {
+            let of_op = || self.iter().filter(move |m| m.operation == op);
+            let estimated: f64 = self.entries.iter().filter(|(m, _)| m.operation == op).map(|(_, w)| w).sum();
+            let stats = OperationStats::from_times(of_op().map(|m| m.execution_time_ms).collect())?;
+            let reported = of_op().filter(|m| m.status_code != 0).count();
+            let successes = of_op().filter(|m| m.succeeded()).count();
+            Some(OperationSummary {
+                operation: op.to_string(),
+                stats,
+                estimated_count: estimated.round() as u64,
+                successes,
+                failures: reported - successes,
+                success_rate: (reported > 0).then(|| successes as f64 / reported as f64),
+            })
+        }).collect()
+    }
+}
+
+// One row of /api/metrics/summary. success_rate counts 2xx among the samples that reported a status.
+// estimated_count extrapolates the samples to the requests made when METRICS_SAMPLE_RATE is below 1
+#[derive(Clone, Serialize, Debug)]
+pub struct OperationSummary {
+    pub operation: String,
+    #[serde(flatten)]
+    pub stats: OperationStats,
+    pub estimated_count: u64,
+    pub successes: usize,
+    pub failures: usize,
+    pub success_rate: Option<f64>,
+}
+
+// One row of /api/compare. speedup maps every other language to its mean over rust's mean, so 3.0 means
+// rust took a third of the time; empty when rust has no samples for the operation
+#[derive(Clone, Serialize, Debug)]
+pub struct LanguageComparison {
+    pub operation: String,
+    pub languages: BTreeMap<String, OperationStats>,
+    pub speedup: BTreeMap<String, f64>,
+}
+
+#[derive(Clone, Serialize, Debug)]
+pub struct OperationStats {
+    pub samples: usize,
+    pub mean_ms: f64,
+    // population standard deviation
+    pub std_ms: f64,
+    pub p95_ms: f64,
+}
+
+impl OperationStats {
+    pub fn from_times(mut times: Vec<f64>) -> Option<Self> {
+        if times.is_empty() {
+            return None;
+        }
+        times.sort_by(|a, b| a.total_cmp(b));
+        let samples = times.len();
+        let (mean_ms, std_ms) = mean_std(&times, 0);
+        // nearest-rank percentile
+        let rank = ((0.95 * samples as f64).ceil() as usize).clamp(1, samples);
+        Some(OperationStats { samples, mean_ms, std_ms, p95_ms: times[rank - 1] })
+    }
 }
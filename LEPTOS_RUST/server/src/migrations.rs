@@ -0,0 +1,61 @@
+use rusqlite::{params, Connection};
+
+use crate::{idempotency, tags};
+
+const CREATE_ITEMS_SQL: &str = "CREATE TABLE IF NOT EXISTS items (
+	id TEXT PRIMARY KEY,
+	name TEXT NOT NULL,
+	description TEXT
+)";
+
+// Schema steps in order: applying MIGRATIONS[i] takes the database to version i + 1.
+// Only ever append; a step that already ran somewhere is never run there again, so editing it changes nothing.
+// The first three are the tables create_app used to create on every start. They keep IF NOT EXISTS so a
+// database from before schema_version existed passes through them unchanged and ends up at the same version
+pub const MIGRATIONS: &[&str] = &[
+    CREATE_ITEMS_SQL,
+    idempotency::CREATE_TABLE_SQL,
+    tags::CREATE_TABLES_SQL,
+];
+
+pub fn latest_version() -> u32 {
+    MIGRATIONS.len() as u32
+}
+
+// Highest applied step, 0 for a database that has none (or no schema_version table yet)
+pub fn current_version(conn: &Connection) -> rusqlite::Result<u32> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS (SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version')",
+        [],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Ok(0);
+    }
+    conn.query_row("SELECT COALESCE(MAX(version), 0) FROM schema_version", [], |row| row.get(0))
+}
+
+// Applies every step above the recorded version and returns the version reached. Each step commits
+// together with its schema_version row, so a failing step leaves the database at the previous version.
+// Running it again on an up-to-date database applies nothing
+pub fn run(conn: &mut Connection) -> rusqlite::Result<u32> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version INTEGER PRIMARY KEY,
+            applied_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+    let mut version = current_version(conn)?;
+    for (index, sql) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let tx = conn.transaction()?;
+        tx.execute_batch(sql)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, applied_at) VALUES (?1, datetime('now'))",
+            params![index + 1],
+        )?;
+        tx.commit()?;
+        version = index as u32 + 1;
+    }
+    Ok(version)
+}
@@ -0,0 +1,76 @@
+use std::sync::OnceLock;
+
+use opentelemetry::metrics::{Histogram, MeterProvider};
+use opentelemetry::KeyValue;
+use opentelemetry_otlp::MetricExporter;
+use opentelemetry_sdk::metrics::{SdkMeterProvider, Temporality};
+
+use crate::metric::Metric;
+use crate::utils::MEMORY_UNAVAILABLE;
+
+// OTLP export of the recorded metrics (`otel` feature), alongside the CSV. Each Metric becomes one
+// histogram measurement per field, labelled with its operation and status code. The SDK's periodic
+// reader batches them and pushes over HTTP/protobuf to OTEL_EXPORTER_OTLP_ENDPOINT + /v1/metrics,
+// every OTEL_METRIC_EXPORT_INTERVAL ms (default 60000) and at shutdown. Delta temporality, so an export
+// carries only the metrics recorded since the previous one
+struct Exporter {
+    provider: SdkMeterProvider,
+    execution_time: Histogram<f64>,
+    memory: Histogram<f64>,
+    network_latency: Histogram<f64>,
+}
+
+// None when OTEL_EXPORTER_OTLP_ENDPOINT is unset or the exporter can't be built
+static EXPORTER: OnceLock<Option<Exporter>> = OnceLock::new();
+
+fn build() -> Option<Exporter> {
+    let endpoint = std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT").ok().filter(|e| !e.is_empty())?;
+    let exporter = match MetricExporter::builder().with_http().with_temporality(Temporality::Delta).build() {
+        Ok(exporter) => exporter,
+        Err(e) => {
+            eprintln!("Warning: OTLP export to {} disabled: {}", endpoint, e);
+            return None;
+        }
+    };
+    let provider = SdkMeterProvider::builder().with_periodic_exporter(exporter).build();
+    let meter = provider.meter("server");
+    println!("Exporting metrics over OTLP to {}", endpoint);
+    Some(Exporter {
+        execution_time: meter.f64_histogram("execution_time_ms").with_unit("ms").build(),
+        memory: meter.f64_histogram("memory_mb").with_unit("MiBy").build(),
+        network_latency: meter.f64_histogram("network_latency_ms").with_unit("ms").build(),
+        provider,
+    })
+}
+
+// Builds the exporter from the environment, call once at startup
+pub fn init() {
+    EXPORTER.get_or_init(build);
+}
+
+// Queues `metric` for the next export; a no-op when export is off.
+// An unavailable memory reading is left out rather than exported as -1
+pub fn record(metric: &Metric) {
+    let Some(exporter) = EXPORTER.get_or_init(build) else {
+        return;
+    };
+    let attributes = [
+        KeyValue::new("operation", metric.operation.clone()),
+        KeyValue::new("status_code", metric.status_code as i64),
+        KeyValue::new("language", metric.language.clone()),
+    ];
+    exporter.execution_time.record(metric.execution_time_ms, &attributes);
+    if metric.memory_mb != MEMORY_UNAVAILABLE {
+        exporter.memory.record(metric.memory_mb, &attributes);
+    }
+    exporter.network_latency.record(metric.network_latency_ms, &attributes);
+}
+
+// Pushes what is still queued, call once on shutdown
+pub fn shutdown() {
+    if let Some(Some(exporter)) = EXPORTER.get() {
+        if let Err(e) = exporter.provider.shutdown() {
+            eprintln!("Failed to flush the OTLP metrics on shutdown: {}", e);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+use std::time::Instant;
+
+use rusqlite::{params, Connection, OptionalExtension, Statement};
+use serde::Serialize;
+
+use crate::item::Item;
+use crate::tags::select_items_sql;
+
+// Statement preparation cost, isolated: the /api/read/:id query run repeatedly on one connection,
+// once re-preparing the SQL on every call (what each handler does today, since it opens a fresh
+// connection per request) and once through rusqlite's per-connection statement cache.
+
+// Ids cycled through by the benchmark; one id that matches nothing is added so the 404 path is covered too
+const SAMPLE_IDS: usize = 100;
+pub const DEFAULT_ITERATIONS: usize = 1000;
+pub const MAX_ITERATIONS: usize = 100_000;
+
+#[derive(Serialize, Debug)]
+pub struct PrepareBench {
+    pub iterations: usize,
+    pub distinct_ids: usize,
+    pub uncached_ms_per_call: f64,
+    pub cached_ms_per_call: f64,
+    // uncached - cached: what preparing the statement costs on each call
+    pub saved_ms_per_call: f64,
+    // Whether both paths returned the same item (or None) for every call
+    pub identical: bool,
+}
+
+fn read_one_sql() -> String {
+    select_items_sql(" WHERE i.id = ?1")
+}
+
+fn query_item(stmt: &mut Statement, id: &str) -> rusqlite::Result<Option<Item>> {
+    stmt.query_row(params![id], Item::from_row).optional()
+}
+
+// One lookup, preparing the SQL from scratch or taking it from the connection's statement cache
+pub fn read_one(conn: &Connection, id: &str, cached: bool) -> rusqlite::Result<Option<Item>> {
+    let sql = read_one_sql();
+    if cached {
+        let mut stmt = conn.prepare_cached(&sql)?;
+        query_item(&mut stmt, id)
+    } else {
+        query_item(&mut conn.prepare(&sql)?, id)
+    }
+}
+
+fn timed_pass(conn: &Connection, ids: &[String], iterations: usize, cached: bool) -> rusqlite::Result<(f64, Vec<Option<Item>>)> {
+    let mut results = Vec::with_capacity(iterations);
+    let start = Instant::now();
+    for id in ids.iter().cycle().take(iterations) {
+        results.push(read_one(conn, id, cached)?);
+    }
+    Ok((start.elapsed().as_secs_f64() * 1000.0, results))
+}
+
+pub fn run(conn: &Connection, iterations: usize) -> rusqlite::Result<PrepareBench> {
+    let mut stmt = conn.prepare("SELECT id FROM items ORDER BY rowid LIMIT ?1")?;
+    let mut ids = stmt
+        .query_map(params![SAMPLE_IDS], |row| row.get::<_, String>(0))?
+        .collect::<rusqlite::Result<Vec<String>>>()?;
+    ids.push(String::from("prepare-bench-missing-id"));
+
+    // Untimed pass over every id so both timed passes start with the pages and the cache warm
+    for id in &ids {
+        read_one(conn, id, false)?;
+        read_one(conn, id, true)?;
+    }
+    let (uncached_ms, uncached) = timed_pass(conn, &ids, iterations, false)?;
+    let (cached_ms, cached) = timed_pass(conn, &ids, iterations, true)?;
+
+    let per_call = |ms: f64| ms / iterations.max(1) as f64;
+    Ok(PrepareBench {
+        iterations,
+        distinct_ids: ids.len(),
+        uncached_ms_per_call: per_call(uncached_ms),
+        cached_ms_per_call: per_call(cached_ms),
+        saved_ms_per_call: per_call(uncached_ms - cached_ms),
+        identical: uncached == cached,
+    })
+}
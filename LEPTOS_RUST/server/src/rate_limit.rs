@@ -0,0 +1,54 @@
+use axum::{http::{header, Request, StatusCode}, middleware::Next, response::{IntoResponse, Response}};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Token bucket refilled continuously at `rate_per_sec`, holding at most one second of burst
+pub struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    rate_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    pub fn new(rate_per_sec: f64) -> Self {
+        TokenBucket {
+            capacity: rate_per_sec,
+            tokens: rate_per_sec,
+            rate_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    pub fn try_acquire(&mut self) -> bool {
+        let now = Instant::now();
+        let refill = now.duration_since(self.last_refill).as_secs_f64() * self.rate_per_sec;
+        self.tokens = (self.tokens + refill).min(self.capacity);
+        self.last_refill = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    // Time until the bucket next holds a whole token, as of the last try_acquire
+    pub fn retry_after(&self) -> Duration {
+        Duration::from_secs_f64((1.0 - self.tokens).max(0.0) / self.rate_per_sec)
+    }
+}
+
+// Middleware rejecting requests with 429 once the bucket is empty, with Retry-After in whole seconds
+// (at least 1) until a token is back. /health is never limited
+pub async fn rate_limit<B>(limiter: Arc<Mutex<TokenBucket>>, req: Request<B>, next: Next<B>) -> Response {
+    if req.uri().path() != "/health" {
+        let mut bucket = limiter.lock();
+        if !bucket.try_acquire() {
+            let retry_after = (bucket.retry_after().as_secs_f64().ceil() as u64).max(1);
+            return (StatusCode::TOO_MANY_REQUESTS, [(header::RETRY_AFTER, retry_after.to_string())], "Too Many Requests").into_response();
+        }
+    }
+    next.run(req).await
+}
@@ -0,0 +1,38 @@
+use axum::{http::{HeaderValue, Request}, middleware::Next, response::Response};
+use uuid::Uuid;
+
+pub const HEADER: &str = "x-request-id";
+
+// Longest id taken from a client; anything longer, empty, or with spaces or control characters is replaced
+const MAX_LEN: usize = 128;
+
+tokio::task_local! {
+    // Id of the request being handled, set once per request by `request_id`
+    static REQUEST_ID: String;
+}
+
+// Middleware that gives every request an id: the client's X-Request-Id when it sends a usable one, otherwise
+// a fresh UUID. Handlers put it in their Metric (see `current`) and it is echoed in the response's
+// X-Request-Id, so a client can match its own timing to the server's metric for the same request
+pub async fn request_id<B>(req: Request<B>, next: Next<B>) -> Response {
+    let id = req.headers()
+        .get(HEADER)
+        .and_then(|v| v.to_str().ok())
+        .filter(|id| valid(id))
+        .map(str::to_string)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let mut res = REQUEST_ID.scope(id.clone(), next.run(req)).await;
+    if let Ok(value) = HeaderValue::from_str(&id) {
+        res.headers_mut().insert(HEADER, value);
+    }
+    res
+}
+
+pub fn valid(id: &str) -> bool {
+    !id.is_empty() && id.len() <= MAX_LEN && id.bytes().all(|b| b.is_ascii_graphic())
+}
+
+// The current request's id; None outside a request (e.g. seeding at startup)
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(|id| id.clone()).ok()
+}
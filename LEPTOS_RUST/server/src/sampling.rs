@@ -0,0 +1,46 @@
+use axum::{http::Request, middleware::Next, response::Response};
+use rand::Rng;
+
+use crate::handlers::AppState;
+use crate::metric::Metric;
+use crate::utils::metrics_sample_rate;
+
+tokio::task_local! {
+    // Whether the request being handled is measured, decided once per request by `sample_request`
+    static SAMPLED: bool;
+}
+
+// True with probability `rate`: always at 1.0, never at 0.0
+pub fn decide(rate: f64) -> bool {
+    rate >= 1.0 || (rate > 0.0 && rand::thread_rng().gen_bool(rate))
+}
+
+// Middleware that decides, per request, whether its metric is measured (memory sampled) and
+// recorded, keeping METRICS_SAMPLE_RATE of them. Unsampled requests still run normally
+pub async fn sample_request<B>(req: Request<B>, next: Next<B>) -> Response {
+    SAMPLED.scope(decide(metrics_sample_rate()), next.run(req)).await
+}
+
+// The current request's decision; true outside a request (e.g. at startup)
+pub fn is_sampled() -> bool {
+    SAMPLED.try_with(|s| *s).unwrap_or(true)
+}
+
+// Carries the request's decision into a spawn_blocking closure, which runs outside the request task
+pub fn with_sampled<R>(sampled: bool, f: impl FnOnce() -> R) -> R {
+    SAMPLED.sync_scope(sampled, f)
+}
+
+// Stores and appends a server-measured metric if the current request is sampled, weighted so
+// the summary's estimated_count extrapolates to every request
+pub fn record_metric(state: &AppState, metric: &Metric) {
+    if !is_sampled() {
+        return;
+    }
+    let rate = metrics_sample_rate();
+    let weight = if rate > 0.0 { 1.0 / rate } else { 1.0 };
+    state.store.lock().push_weighted(metric.clone(), weight);
+    let _ = state.metrics_csv.append(metric);
+    #[cfg(feature = "otel")]
+    crate::otel::record(metric);
+}
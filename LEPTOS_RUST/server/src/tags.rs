@@ -0,0 +1,73 @@
+use rusqlite::{params, Connection};
+
+use crate::item::Item;
+
+// Tag names live once in `tags`; `item_tags` is the many-to-many junction with items
+pub const CREATE_TABLES_SQL: &str = "
+CREATE TABLE IF NOT EXISTS tags (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE IF NOT EXISTS item_tags (
+    item_id TEXT NOT NULL,
+    tag_id INTEGER NOT NULL,
+    PRIMARY KEY (item_id, tag_id)
+);";
+
+// Separator GROUP_CONCAT joins tag names with (ASCII unit separator, so any printable tag name is safe)
+const TAG_SEP: char = '\u{1f}';
+
+// Items joined with their tags, one row per item in insertion order, with Item::SELECT_COLUMNS so every
+// row maps through Item::from_row. `filter` is an optional WHERE clause, e.g. " WHERE i.id = ?1"
+pub fn select_items_sql(filter: &str) -> String {
+    format!(
+        "SELECT {}
+FROM items i
+LEFT JOIN item_tags it ON it.item_id = i.id
+LEFT JOIN tags t ON t.id = it.tag_id{} GROUP BY i.id ORDER BY i.rowid",
+        Item::SELECT_COLUMNS, filter
+    )
+}
+
+// Turns the GROUP_CONCAT column back into a sorted tag list
+pub fn split_tags(joined: Option<String>) -> Vec<String> {
+    let mut tags: Vec<String> = joined
+        .map(|s| s.split(TAG_SEP).map(|t| t.to_string()).collect())
+        .unwrap_or_default();
+    tags.sort();
+    tags
+}
+
+pub fn item_exists(conn: &Connection, item_id: &str) -> rusqlite::Result<bool> {
+    conn.query_row("SELECT EXISTS(SELECT 1 FROM items WHERE id = ?1)", params![item_id], |row| row.get(0))
+}
+
+// Attaches each tag to the item, creating tag rows as needed. Already-attached tags are left alone
+pub fn add_tags(conn: &mut Connection, item_id: &str, tags: &[String]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    for tag in tags {
+        tx.execute("INSERT OR IGNORE INTO tags (name) VALUES (?1)", params![tag])?;
+        tx.execute(
+            "INSERT OR IGNORE INTO item_tags (item_id, tag_id) SELECT ?1, id FROM tags WHERE name = ?2",
+            params![item_id, tag],
+        )?;
+    }
+    tx.commit()
+}
+
+// Returns whether the tag was attached to the item
+pub fn remove_tag(conn: &Connection, item_id: &str, tag: &str) -> rusqlite::Result<bool> {
+    let removed = conn.execute(
+        "DELETE FROM item_tags WHERE item_id = ?1 AND tag_id = (SELECT id FROM tags WHERE name = ?2)",
+        params![item_id, tag],
+    )?;
+    Ok(removed > 0)
+}
+
+pub fn tags_for(conn: &Connection, item_id: &str) -> rusqlite::Result<Vec<String>> {
+    let mut stmt = conn.prepare(
+        "SELECT t.name FROM item_tags it JOIN tags t ON t.id = it.tag_id WHERE it.item_id = ?1 ORDER BY t.name",
+    )?;
+    let tags = stmt.query_map(params![item_id], |row| row.get(0))?.collect();
+    tags
+}
@@ -0,0 +1,56 @@
+use axum::body::{boxed, Full};
+use axum::extract::Query;
+use axum::http::{header, HeaderValue, Request};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::metric::{round_for_output, Metric};
+
+#[derive(Deserialize, Default)]
+struct TimingParams {
+    with_timing: Option<bool>,
+}
+
+// `?with_timing=true` wraps the response of any timed handler (one that attaches its Metric as a
+// response extension) in {"data": ..., "timing": {"execution_time_ms", "memory_mb"}}, so the client
+// gets the server-side cost without a separate /api/metrics call. Other responses pass through untouched
+pub async fn timing_envelope<B>(req: Request<B>, next: Next<B>) -> Response {
+    let wants_timing = Query::<TimingParams>::try_from_uri(req.uri())
+        .map(|Query(p)| p.with_timing.unwrap_or(false))
+        .unwrap_or(false);
+    let res = next.run(req).await;
+    if !wants_timing || !res.status().is_success() {
+        return res;
+    }
+    let (mut parts, body) = res.into_parts();
+    let metric = match parts.extensions.remove::<Metric>() {
+        Some(m) => m,
+        None => return Response::from_parts(parts, body),
+    };
+    let bytes = match hyper::body::to_bytes(body).await {
+        Ok(b) => b,
+        Err(_) => return (axum::http::StatusCode::INTERNAL_SERVER_ERROR, "Body error").into_response(),
+    };
+    // Status-only responses (update/delete) have an empty body
+    let data = if bytes.is_empty() {
+        Value::Null
+    } else {
+        match serde_json::from_slice::<Value>(&bytes) {
+            Ok(v) => v,
+            // Not JSON (e.g. CSV), hand it back unchanged
+            Err(_) => return Response::from_parts(parts, boxed(Full::from(bytes))),
+        }
+    };
+    let envelope = serde_json::json!({
+        "data": data,
+        "timing": {
+            "execution_time_ms": round_for_output(metric.execution_time_ms),
+            "memory_mb": round_for_output(metric.memory_mb),
+        },
+    });
+    parts.headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+    parts.headers.remove(header::CONTENT_LENGTH);
+    Response::from_parts(parts, boxed(Full::from(serde_json::to_vec(&envelope).unwrap_or_default())))
+}
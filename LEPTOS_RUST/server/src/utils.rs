@@ -1,26 +1,389 @@
+use std::collections::HashMap;
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Once, OnceLock};
+use parking_lot::Mutex;
+use uuid::Uuid;
 use csv::WriterBuilder;
+use crate::item::Item;
 use crate::metric::Metric;
 use sysinfo::{System, SystemExt, ProcessExt};
+use bench_utils::MemoryUnit;
+use axum_server::tls_rustls::RustlsConfig;
+use crate::sampling::is_sampled;
 
 const CSV_FILE: &str = "read.csv";
 
-pub fn append_metric_to_csv(metric: &Metric) -> Result<(), std::io::Error> {
-    let file_exists = std::path::Path::new(CSV_FILE).exists();
-    let file = fs::OpenOptions::new().create(true).append(true).open(CSV_FILE)?;
-    let mut wtr = WriterBuilder::new().has_headers(!file_exists).from_writer(file);
-    wtr.serialize(metric)?;
-    wtr.flush()?;
-    Ok(())
+// A metrics CSV, opened on its first append and kept open, with the rows buffered since its last flush
+struct MetricsCsv {
+    writer: csv::Writer<fs::File>,
+    unflushed: usize,
 }
 
-pub fn sample_proc_memory_mb() -> f64 {
+// The metrics CSVs of one app, held in its AppState so separate apps (and tests) never share a buffer.
+// Rows reach a file every `flush_rows` rows, on the METRICS_FLUSH_MS interval and at shutdown (flush)
+pub struct MetricsCsvWriter {
+    dir: PathBuf,
+    flush_rows: usize,
+    // Open files by name: just read.csv, or one per operation with METRICS_CSV_SHARD
+    files: Mutex<HashMap<String, MetricsCsv>>,
+    // Time spent in append and flush since startup, so /api/stats can show the cost of the measurement
+    // itself. Every append counts, including failed writes
+    write_nanos: AtomicU64,
+    write_count: AtomicU64,
+}
+
+impl MetricsCsvWriter {
+    // Files go in `dir`; `flush_rows` of 0 is taken as 1 (write every row through)
+    pub fn new(dir: impl Into<PathBuf>, flush_rows: usize) -> MetricsCsvWriter {
+        MetricsCsvWriter {
+            dir: dir.into(),
+            flush_rows: flush_rows.max(1),
+            files: Mutex::new(HashMap::new()),
+            write_nanos: AtomicU64::new(0),
+            write_count: AtomicU64::new(0),
+        }
+    }
+
+    // The working directory, flushed every METRICS_FLUSH_ROWS rows
+    pub fn from_env() -> MetricsCsvWriter {
+        MetricsCsvWriter::new(".", metrics_flush_rows())
+    }
+
+    pub fn append(&self, metric: &Metric) -> Result<(), std::io::Error> {
+        let start = std::time::Instant::now();
+        let result = self.write_row(metric);
+        self.write_nanos.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        self.write_count.fetch_add(1, Ordering::Relaxed);
+        result
+    }
+
+    fn write_row(&self, metric: &Metric) -> Result<(), std::io::Error> {
+        let file_name = metrics_csv_file(metric);
+        let mut files = self.files.lock();
+        let csv = match files.get_mut(&file_name) {
+            Some(csv) => csv,
+            None => {
+                let path = self.dir.join(&file_name);
+                let file_exists = prepare_metrics_csv(&path)?;
+                let file = fs::OpenOptions::new().create(true).append(true).open(&path)?;
+                let writer = WriterBuilder::new().has_headers(!file_exists).from_writer(file);
+                files.entry(file_name).or_insert(MetricsCsv { writer, unflushed: 0 })
+            }
+        };
+        csv.writer.serialize(metric)?;
+        csv.unflushed += 1;
+        if csv.unflushed >= self.flush_rows {
+            csv.writer.flush()?;
+            csv.unflushed = 0;
+        }
+        Ok(())
+    }
+
+    // Writes any buffered rows to their files. Called on the flush interval and at shutdown
+    pub fn flush(&self) -> Result<(), std::io::Error> {
+        let start = std::time::Instant::now();
+        let mut files = self.files.lock();
+        let mut pending = files.values_mut().filter(|csv| csv.unflushed > 0).peekable();
+        if pending.peek().is_none() {
+            return Ok(());
+        }
+        // every file gets flushed even if an earlier one fails; the first error is reported
+        let mut result = Ok(());
+        for csv in pending {
+            csv.unflushed = 0;
+            let flushed = csv.writer.flush();
+            if result.is_ok() {
+                result = flushed;
+            }
+        }
+        self.write_nanos.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        result
+    }
+
+    // (appends, total milliseconds spent appending and flushing) so far
+    pub fn write_stats(&self) -> (u64, f64) {
+        let count = self.write_count.load(Ordering::Relaxed);
+        let nanos = self.write_nanos.load(Ordering::Relaxed);
+        (count, nanos as f64 / 1_000_000.0)
+    }
+}
+
+// File name `metric` is appended to
+fn metrics_csv_file(metric: &Metric) -> String {
+    if metrics_csv_sharded() {
+        shard_file_name(&metric.operation)
+    } else {
+        CSV_FILE.to_string()
+    }
+}
+
+// metrics_<operation>.csv, with every character other than ASCII letters, digits, '-' and '_' replaced by '_'
+// so an ingested operation can't name a path (READ (Description) -> metrics_READ__Description_.csv)
+pub fn shard_file_name(operation: &str) -> String {
+    let safe: String = operation
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect();
+    format!("metrics_{}.csv", safe)
+}
+
+// Header line the current Metric fields serialize to, e.g. `timestamp,operation,...,request_id`
+fn metric_csv_header() -> &'static str {
+    static HEADER: OnceLock<String> = OnceLock::new();
+    HEADER.get_or_init(|| {
+        let placeholder = Metric {
+            timestamp: String::new(),
+            operation: String::new(),
+            execution_time_ms: 0.0,
+            memory_mb: 0.0,
+            network_latency_ms: 0.0,
+            status_code: 0,
+            language: String::new(),
+            request_id: None,
+        };
+        let mut writer = WriterBuilder::new().from_writer(Vec::new());
+        let _ = writer.serialize(&placeholder);
+        let bytes = writer.into_inner().unwrap_or_default();
+        String::from_utf8_lossy(&bytes).lines().next().unwrap_or_default().to_string()
+    })
+}
+
+// Before the first append to `path`: true when it already holds rows under the current header, so
+// only rows are added. A file with another header (written before a Metric field was added) is renamed
+// to `<path>.<timestamp>.old` rather than getting wider rows under its old columns, and an empty
+// one is started over; both then get a fresh header
+fn prepare_metrics_csv(path: &Path) -> Result<bool, std::io::Error> {
+    let first_line = match fs::File::open(path) {
+        Ok(file) => {
+            let mut line = String::new();
+            std::io::BufRead::read_line(&mut std::io::BufReader::new(file), &mut line)?;
+            line
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e),
+    };
+    let first_line = first_line.trim_end();
+    if first_line == metric_csv_header() {
+        return Ok(true);
+    }
+    if !first_line.is_empty() {
+        let rotated = format!("{}.{}.old", path.display(), chrono::Local::now().format("%Y%m%d%H%M%S"));
+        fs::rename(path, &rotated)?;
+        eprintln!("{} has the header '{}', not the current metric columns; moved it to {}", path.display(), first_line, rotated);
+    } else {
+        fs::remove_file(path)?;
+    }
+    Ok(false)
+}
+
+// One metrics CSV per operation (metrics_<operation>.csv) instead of read.csv: METRICS_CSV_SHARD=true, off by default
+pub fn metrics_csv_sharded() -> bool {
+    static SHARDED: OnceLock<bool> = OnceLock::new();
+    *SHARDED.get_or_init(|| std::env::var("METRICS_CSV_SHARD").map(|v| v == "true").unwrap_or(false))
+}
+
+// Buffered metric rows that trigger a flush (per file when sharded): METRICS_FLUSH_ROWS if set, otherwise 100.
+// 1 writes every metric through as it is recorded
+pub fn metrics_flush_rows() -> usize {
+    static ROWS: OnceLock<usize> = OnceLock::new();
+    *ROWS.get_or_init(|| {
+        std::env::var("METRICS_FLUSH_ROWS")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or(100)
+    })
+}
+
+// How often buffered metric rows are flushed regardless of count: METRICS_FLUSH_MS if set, otherwise 1000
+pub fn metrics_flush_interval() -> std::time::Duration {
+    let ms = std::env::var("METRICS_FLUSH_MS")
+        .ok()
+        .and_then(|s| s.parse::<u64>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(1000);
+    std::time::Duration::from_millis(ms)
+}
+
+// Directory the non-API routes are served from: STATIC_DIR if set, otherwise ../static
+// (the workspace's static/ when started from server/)
+pub fn static_dir() -> String {
+    std::env::var("STATIC_DIR").unwrap_or_else(|_| String::from("../static"))
+}
+
+// SQLite database file: DB_PATH if set, otherwise db.sqlite in the working directory
+pub fn db_path() -> String {
+    std::env::var("DB_PATH").unwrap_or_else(|_| "db.sqlite".to_string())
+}
+
+static ID_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+// Item id generation, selected with the ID_MODE env var:
+//   v7 (default) - time-ordered, so ids sort in creation order and SQLite inserts append to the index
+//   v4           - fully random
+//   v5           - derived from a per-process sequence number, so a fresh run reproduces the same ids
+pub fn generate_id() -> String {
+    match std::env::var("ID_MODE").as_deref() {
+        Ok("v4") => Uuid::new_v4().to_string(),
+        Ok("v5") => {
+            let seq = ID_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+            Uuid::new_v5(&Uuid::NAMESPACE_OID, seq.to_string().as_bytes()).to_string()
+        }
+        _ => Uuid::now_v7().to_string(),
+    }
+}
+
+// JSON file of items to seed a new database with: SEED_ITEMS if set, otherwise the single example item
+pub fn seed_items_path() -> Option<String> {
+    std::env::var("SEED_ITEMS").ok().filter(|p| !p.is_empty())
+}
+
+// In-memory metric capacity: METRICS_CAPACITY if set, otherwise 10_000
+pub fn metrics_capacity() -> usize {
+    std::env::var("METRICS_CAPACITY")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or(10_000)
+}
+
+// Longest item description accepted, in characters: MAX_DESC_LEN if set, otherwise 1000
+pub fn max_desc_len() -> usize {
+    static LEN: OnceLock<usize> = OnceLock::new();
+    *LEN.get_or_init(|| {
+        std::env::var("MAX_DESC_LEN")
+            .ok()
+            .and_then(|s| s.parse::<usize>().ok())
+            .filter(|&n| n > 0)
+            .unwrap_or(1000)
+    })
+}
+
+// How long /api/items/analytics reuses its last result: ANALYTICS_CACHE_TTL_MS if set, otherwise 5000.
+// 0 turns the cache off
+pub fn analytics_cache_ttl() -> std::time::Duration {
+    static TTL: OnceLock<std::time::Duration> = OnceLock::new();
+    *TTL.get_or_init(|| {
+        std::time::Duration::from_millis(
+            std::env::var("ANALYTICS_CACHE_TTL_MS")
+                .ok()
+                .and_then(|s| s.parse::<u64>().ok())
+                .unwrap_or(5000),
+        )
+    })
+}
+
+// Fraction of requests whose metric is measured and recorded: METRICS_SAMPLE_RATE (0.0-1.0) if set,
+// otherwise 1.0 (every request). Out-of-range values are clamped. Read once, it is checked on every request
+pub fn metrics_sample_rate() -> f64 {
+    static RATE: OnceLock<f64> = OnceLock::new();
+    *RATE.get_or_init(|| {
+        std::env::var("METRICS_SAMPLE_RATE")
+            .ok()
+            .and_then(|s| s.parse::<f64>().ok())
+            .filter(|r| r.is_finite())
+            .map(|r| r.clamp(0.0, 1.0))
+            .unwrap_or(1.0)
+    })
+}
+
+// Decimal places metric floats are rounded to when serialized: METRIC_DECIMALS if set, otherwise 4.
+// Read once, since it is consulted for every serialized metric
+pub fn metric_decimals() -> u32 {
+    static DECIMALS: OnceLock<u32> = OnceLock::new();
+    *DECIMALS.get_or_init(|| {
+        std::env::var("METRIC_DECIMALS")
+            .ok()
+            .and_then(|s| s.parse::<u32>().ok())
+            .map(|n| n.min(15))
+            .unwrap_or(4)
+    })
+}
+
+// Requests per second allowed by the rate limiter: RATE_LIMIT_RPS if set, otherwise unlimited
+pub fn rate_limit_rps() -> Option<f64> {
+    std::env::var("RATE_LIMIT_RPS")
+        .ok()
+        .and_then(|s| s.parse::<f64>().ok())
+        .filter(|&n| n > 0.0)
+}
+
+// Whether GET /api/db/download is routed at all: ALLOW_DB_DOWNLOAD=true, off by default
+pub fn allow_db_download() -> bool {
+    std::env::var("ALLOW_DB_DOWNLOAD").map(|v| v == "true").unwrap_or(false)
+}
+
+// PEM certificate chain and private key for HTTPS: TLS_CERT and TLS_KEY, both required.
+// Unset (or only one of them set) means plain HTTP
+pub fn tls_paths() -> Option<(String, String)> {
+    match (std::env::var("TLS_CERT"), std::env::var("TLS_KEY")) {
+        (Ok(cert), Ok(key)) if !cert.is_empty() && !key.is_empty() => Some((cert, key)),
+        _ => None,
+    }
+}
+
+// Loads the TLS_CERT / TLS_KEY pair, naming both files when either can't be read or parsed
+pub async fn load_tls_config(cert: &str, key: &str) -> Result<RustlsConfig, String> {
+    RustlsConfig::from_pem_file(cert, key)
+        .await
+        .map_err(|e| format!("failed to load TLS_CERT {} / TLS_KEY {}: {}", cert, key, e))
+}
+
+// Tokio worker thread count: WORKER_THREADS if set, otherwise the available parallelism
+pub fn worker_threads() -> usize {
+    std::env::var("WORKER_THREADS")
+        .ok()
+        .and_then(|s| s.parse::<usize>().ok())
+        .filter(|&n| n > 0)
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+}
+
+// Tags go in one column joined with ';' since csv can't serialize a Vec field
+pub fn items_to_csv(items: &[Item]) -> Result<Vec<u8>, csv::Error> {
+    let mut wtr = WriterBuilder::new().from_writer(Vec::new());
+    wtr.write_record(["id", "name", "description", "tags"])?;
+    for item in items {
+        wtr.write_record([
+            item.id.as_str(),
+            item.name.as_str(),
+            item.description.as_deref().unwrap_or(""),
+            item.tags.join(";").as_str(),
+        ])?;
+    }
+    wtr.into_inner().map_err(|e| e.into_error().into())
+}
+
+// memory_mb recorded when sysinfo couldn't read this process (sandboxes hiding the process list),
+// so the metric doesn't pass off a missing reading as a real 0 MB change
+pub const MEMORY_UNAVAILABLE: f64 = -1.0;
+
+// sysinfo reports process memory in bytes. Skipped (Some(0.0)) for requests METRICS_SAMPLE_RATE leaves out,
+// since refreshing the process list is the most expensive part of recording a metric.
+// None when the process can't be found, logged on stderr the first time
+pub fn sample_proc_memory_mb() -> Option<f64> {
+    if !is_sampled() {
+        return Some(0.0);
+    }
     let mut sys = System::new_all();
     sys.refresh_processes();
     let current_pid_str = std::process::id().to_string();
-    sys.processes()
+    let memory = sys.processes()
         .values()
         .find(|p| p.pid().to_string() == current_pid_str)
-        .map(|p| p.memory() as f64 / 1024.0)
-        .unwrap_or(0.0)
+        .map(|p| MemoryUnit::MB.from_bytes(p.memory()));
+    if memory.is_none() {
+        static WARNED: Once = Once::new();
+        WARNED.call_once(|| eprintln!("Warning: sysinfo can't read this process, memory_mb is recorded as {} (unavailable)", MEMORY_UNAVAILABLE));
+    }
+    memory
+}
+
+// memory_mb of a metric from the readings before and after the operation, MEMORY_UNAVAILABLE if either is missing
+pub fn memory_delta_mb(before: Option<f64>, after: Option<f64>) -> f64 {
+    match (before, after) {
+        (Some(before), Some(after)) => after - before,
+        _ => MEMORY_UNAVAILABLE,
+    }
 }
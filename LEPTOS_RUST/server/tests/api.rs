@@ -0,0 +1,517 @@
+// Contract tests for the /api/* routes, run in-process against create_app with tower's oneshot, so no
+// port is bound. api-tests.ps1 covers the same ground against a running server
+
+use std::sync::{Mutex, OnceLock};
+
+use axum::body::Body;
+use axum::http::{header, HeaderMap, Method, Request, StatusCode};
+use axum::Router;
+use rusqlite::Connection;
+use serde_json::Value;
+use server::handlers::create_app;
+use tower::ServiceExt;
+
+// Shared-cache in-memory database, so every connection a handler opens sees the same tables
+const DB_URI: &str = "file:api-tests?mode=memory&cache=shared";
+
+// The app is built once: DB_PATH and the working directory are process-wide. The metrics CSV lands in a
+// scratch directory instead of the crate, and one connection held open for the whole run keeps the
+// in-memory database alive between requests. Router isn't Sync, hence the Mutex around the shared copy
+fn app() -> Router {
+    static APP: OnceLock<Mutex<Router>> = OnceLock::new();
+    APP.get_or_init(|| {
+        let scratch = std::env::temp_dir().join(format!("server-api-tests-{}", std::process::id()));
+        std::fs::create_dir_all(&scratch).unwrap();
+        std::env::set_current_dir(&scratch).unwrap();
+        std::env::set_var("DB_PATH", DB_URI);
+        std::mem::forget(Connection::open(DB_URI).unwrap());
+        Mutex::new(create_app().unwrap())
+    })
+    .lock()
+    .unwrap()
+    .clone()
+}
+
+// Shared-cache connections fail with SQLITE_LOCKED rather than waiting, so tests take turns
+static SERIAL: tokio::sync::Mutex<()> = tokio::sync::Mutex::const_new(());
+
+// Sends one request and returns the status with the body as JSON (a string for non-JSON, Null when empty)
+async fn call(method: Method, uri: &str, body: Option<&str>) -> (StatusCode, Value) {
+    let mut req = Request::builder().method(method).uri(uri);
+    if body.is_some() {
+        req = req.header("content-type", "application/json");
+    }
+    let req = req.body(Body::from(body.unwrap_or_default().to_string())).unwrap();
+    let res = app().oneshot(req).await.unwrap();
+    let status = res.status();
+    let bytes = hyper::body::to_bytes(res.into_body()).await.unwrap();
+    let body = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into_owned()))
+    };
+    (status, body)
+}
+
+// Sends a prepared request and returns the status, headers and raw body, for responses that aren't JSON
+async fn send(req: Request<Body>) -> (StatusCode, HeaderMap, Vec<u8>) {
+    let res = app().oneshot(req).await.unwrap();
+    let (status, headers) = (res.status(), res.headers().clone());
+    (status, headers, hyper::body::to_bytes(res.into_body()).await.unwrap().to_vec())
+}
+
+async fn create(body: &str) -> String {
+    let (status, res) = call(Method::POST, "/api/create", Some(body)).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", res);
+    res["id"].as_str().unwrap().to_string()
+}
+
+async fn count() -> u64 {
+    let (status, res) = call(Method::GET, "/api/items/count", None).await;
+    assert_eq!(status, StatusCode::OK);
+    res["count"].as_u64().unwrap()
+}
+
+#[tokio::test]
+async fn health_ready_and_index() {
+    let _turn = SERIAL.lock().await;
+    assert_eq!(call(Method::GET, "/health", None).await.0, StatusCode::OK);
+    let (status, ready) = call(Method::GET, "/ready", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(ready["schema_version"], ready["latest_version"]);
+    // No static directory next to the scratch working directory: the built-in page, never a 500
+    assert_eq!(call(Method::GET, "/", None).await.0, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn create_read_update_delete() {
+    let _turn = SERIAL.lock().await;
+    let id = create(r#"{"name":"contract","description":"first"}"#).await;
+
+    let (status, all) = call(Method::GET, "/api/read", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(all.as_array().unwrap().iter().any(|item| item["id"] == id.as_str()));
+    let (status, item) = call(Method::GET, &format!("/api/read/{}", id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!((item["name"].as_str(), item["description"].as_str()), (Some("contract"), Some("first")));
+
+    // PATCH merges: only the fields sent change, an explicit null clears the description
+    let (status, res) = call(Method::PATCH, &format!("/api/update/{}", id), Some(r#"{"description":"second"}"#)).await;
+    assert_eq!((status, res["affected"].as_u64()), (StatusCode::OK, Some(1)));
+    call(Method::PATCH, &format!("/api/update/{}", id), Some(r#"{"name":"patched"}"#)).await;
+    let (_, item) = call(Method::GET, &format!("/api/read/{}", id), None).await;
+    assert_eq!((item["name"].as_str(), item["description"].as_str()), (Some("patched"), Some("second")));
+    call(Method::PATCH, &format!("/api/update/{}", id), Some(r#"{"description":null}"#)).await;
+    let (_, item) = call(Method::GET, &format!("/api/read/{}", id), None).await;
+    assert_eq!((item["name"].as_str(), &item["description"]), (Some("patched"), &Value::Null));
+
+    // PUT replaces: a missing description is stored as null
+    call(Method::PUT, &format!("/api/update/{}", id), Some(r#"{"name":"replaced","description":"d"}"#)).await;
+    let (status, _) = call(Method::PUT, &format!("/api/update/{}", id), Some(r#"{"name":"replaced"}"#)).await;
+    assert_eq!(status, StatusCode::OK);
+    let (_, item) = call(Method::GET, &format!("/api/read/{}", id), None).await;
+    assert_eq!((item["name"].as_str(), &item["description"]), (Some("replaced"), &Value::Null));
+
+    let (status, res) = call(Method::POST, &format!("/api/items/{}/tags", id), Some(r#"{"tags":["red","blue"]}"#)).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(res["tags"].as_array().unwrap().len(), 2);
+    assert_eq!(call(Method::DELETE, &format!("/api/items/{}/tags/red", id), None).await.0, StatusCode::OK);
+    assert_eq!(call(Method::DELETE, &format!("/api/items/{}/tags/red", id), None).await.0, StatusCode::NOT_FOUND);
+    let (_, item) = call(Method::GET, &format!("/api/read/{}", id), None).await;
+    assert_eq!(item["tags"], serde_json::json!(["blue"]));
+
+    let (status, res) = call(Method::DELETE, &format!("/api/delete/{}", id), None).await;
+    assert_eq!((status, res["affected"].as_u64()), (StatusCode::OK, Some(1)));
+    assert_eq!(call(Method::GET, &format!("/api/read/{}", id), None).await.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn missing_id_is_not_found() {
+    let _turn = SERIAL.lock().await;
+    let missing = "no-such-id";
+    assert_eq!(call(Method::GET, &format!("/api/read/{}", missing), None).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(call(Method::PUT, &format!("/api/update/{}", missing), Some(r#"{"name":"x"}"#)).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(call(Method::PATCH, &format!("/api/update/{}", missing), Some("{}")).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(call(Method::DELETE, &format!("/api/delete/{}", missing), None).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(call(Method::POST, &format!("/api/items/{}/tags", missing), Some(r#"{"tags":["a"]}"#)).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(call(Method::DELETE, &format!("/api/items/{}/tags/a", missing), None).await.0, StatusCode::NOT_FOUND);
+    assert_eq!(call(Method::GET, &format!("/api/items/search_id?id={}", missing), None).await.0, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn bad_bodies_and_parameters_are_rejected() {
+    let _turn = SERIAL.lock().await;
+    let id = create(r#"{"name":"target"}"#).await;
+    let long = "x".repeat(1001);
+    let long_body = format!(r#"{{"name":"long","description":"{}"}}"#, long);
+    let long_patch = format!(r#"{{"description":"{}"}}"#, long);
+    let update = format!("/api/update/{}", id);
+    let tags = format!("/api/items/{}/tags", id);
+    // Malformed JSON and failed checks are 400; well-formed JSON of the wrong shape is 422
+    for (method, uri, body, expected) in [
+        (Method::POST, "/api/create", Some(r#"{"name":"#), StatusCode::BAD_REQUEST),
+        (Method::POST, "/api/bulk_create", Some("not json"), StatusCode::BAD_REQUEST),
+        (Method::POST, "/api/create", Some(long_body.as_str()), StatusCode::BAD_REQUEST),
+        (Method::PATCH, update.as_str(), Some(long_patch.as_str()), StatusCode::BAD_REQUEST),
+        (Method::POST, tags.as_str(), Some(r#"{"tags":[]}"#), StatusCode::BAD_REQUEST),
+        (Method::POST, "/api/bulk_create?batch=0", Some(r#"[{"name":"ok"}]"#), StatusCode::BAD_REQUEST),
+        (Method::GET, "/api/read?fields=id,secret", None, StatusCode::BAD_REQUEST),
+        (Method::GET, "/api/items/random?n=0", None, StatusCode::BAD_REQUEST),
+        (Method::GET, "/api/items/search_id?id=x&algo=quantum", None, StatusCode::BAD_REQUEST),
+        (Method::GET, "/api/bench/prepare?iterations=0", None, StatusCode::BAD_REQUEST),
+        (Method::GET, "/api/metrics/compare?a=CREATE", None, StatusCode::BAD_REQUEST),
+        (Method::POST, "/api/metrics_ingest", Some(r#"{"operation":"PY_READ","execution_time_ms":null}"#), StatusCode::BAD_REQUEST),
+        (Method::POST, "/api/metrics_ingest", Some(r#"{"operation":"","execution_time_ms":1}"#), StatusCode::BAD_REQUEST),
+        (Method::POST, "/api/create", Some(r#"{"name":5}"#), StatusCode::UNPROCESSABLE_ENTITY),
+        (Method::POST, "/api/create", Some(r#"{"description":"no name"}"#), StatusCode::UNPROCESSABLE_ENTITY),
+        (Method::POST, "/api/bulk_create", Some(r#"{"name":"ok"}"#), StatusCode::UNPROCESSABLE_ENTITY),
+        (Method::PUT, update.as_str(), Some(r#"{"description":"d"}"#), StatusCode::UNPROCESSABLE_ENTITY),
+        (Method::PATCH, update.as_str(), Some(r#"{"name":null}"#), StatusCode::UNPROCESSABLE_ENTITY),
+        (Method::PATCH, update.as_str(), Some(r#"{"description":3}"#), StatusCode::UNPROCESSABLE_ENTITY),
+    ] {
+        let (status, res) = call(method.clone(), uri, body).await;
+        assert_eq!(status, expected, "{} {} -> {}", method, uri, res);
+    }
+    // ?truncate=true stores the first MAX_DESC_LEN (default 1000) characters instead
+    let (status, res) = call(Method::POST, "/api/create?truncate=true", Some(&long_body)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(res["description"].as_str().unwrap().len(), 1000);
+}
+
+#[tokio::test]
+async fn create_with_existing_id_conflicts() {
+    let _turn = SERIAL.lock().await;
+    let uri = "/api/create?id=api-tests-duplicate";
+    assert_eq!(call(Method::POST, uri, Some(r#"{"name":"first"}"#)).await.0, StatusCode::CREATED);
+    assert_eq!(call(Method::POST, uri, Some(r#"{"name":"second"}"#)).await.0, StatusCode::CONFLICT);
+    let (_, item) = call(Method::GET, "/api/read/api-tests-duplicate", None).await;
+    assert_eq!(item["name"], "first");
+}
+
+#[tokio::test]
+async fn idempotency_key_replays_the_first_create() {
+    let _turn = SERIAL.lock().await;
+    let before = count().await;
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let req = Request::builder()
+            .method(Method::POST)
+            .uri("/api/create")
+            .header("content-type", "application/json")
+            .header("idempotency-key", "api-tests-key")
+            .body(Body::from(r#"{"name":"once"}"#))
+            .unwrap();
+        let res = app().oneshot(req).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+        let body: Value = serde_json::from_slice(&hyper::body::to_bytes(res.into_body()).await.unwrap()).unwrap();
+        ids.push(body["id"].clone());
+    }
+    assert_eq!(ids[0], ids[1]);
+    assert_eq!(count().await, before + 1);
+}
+
+#[tokio::test]
+async fn bulk_create_rolls_back_unless_partial() {
+    let _turn = SERIAL.lock().await;
+    let (status, res) = call(Method::POST, "/api/bulk_create", Some(r#"[{"name":"b1"},{"name":"b2","description":"d"}]"#)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(res["inserted"].as_array().unwrap().len(), 2);
+
+    let mixed = r#"[{"name":"ok"},{"description":"no name"}]"#;
+    let before = count().await;
+    let (status, res) = call(Method::POST, "/api/bulk_create", Some(mixed)).await;
+    assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    assert_eq!((res["inserted"].as_array().unwrap().len(), res["failed"][0]["index"].as_u64()), (0, Some(1)));
+    assert_eq!(count().await, before);
+
+    let (status, res) = call(Method::POST, "/api/bulk_create?partial=true", Some(mixed)).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(res["inserted"].as_array().unwrap().len(), 1);
+    assert_eq!(count().await, before + 1);
+
+    let batch: Vec<Value> = (0..10).map(|i| serde_json::json!({ "name": format!("batch-{}", i) })).collect();
+    let (status, res) = call(Method::POST, "/api/bulk_create?batch=4", Some(&Value::from(batch).to_string())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!((res["inserted"].as_array().unwrap().len(), res["batches"].as_array().unwrap().len()), (10, 3));
+}
+
+#[tokio::test]
+async fn item_queries() {
+    let _turn = SERIAL.lock().await;
+    let id = create(r#"{"name":"query target"}"#).await;
+    for i in 0..5 {
+        create(&format!(r#"{{"name":"query {}"}}"#, i)).await;
+    }
+    let total = count().await;
+    let (_, all) = call(Method::GET, "/api/read", None).await;
+    assert_eq!(all.as_array().unwrap().len() as u64, total);
+
+    let (_, projected) = call(Method::GET, "/api/read?fields=id", None).await;
+    assert!(projected.as_array().unwrap().iter().all(|item| item.as_object().unwrap().keys().eq(["id"])));
+
+    let (status, sample) = call(Method::GET, "/api/items/random?n=3", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let mut ids: Vec<&str> = sample.as_array().unwrap().iter().map(|item| item["id"].as_str().unwrap()).collect();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 3);
+    let (_, sample) = call(Method::GET, &format!("/api/items/random?n={}", total + 5), None).await;
+    assert_eq!(sample.as_array().unwrap().len() as u64, total);
+
+    // The three searches over the sorted ids agree on the position
+    let mut positions = Vec::new();
+    for algo in ["binary", "linear", "by_key"] {
+        let (status, res) = call(Method::GET, &format!("/api/items/search_id?id={}&algo={}", id, algo), None).await;
+        assert_eq!(status, StatusCode::OK);
+        positions.push(res["position"].clone());
+    }
+    assert!(positions[0].is_u64() && positions.iter().all(|p| *p == positions[0]));
+
+    let (status, analytics) = call(Method::GET, "/api/items/analytics", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(analytics["count"].as_u64(), Some(total));
+
+    let (status, db) = call(Method::GET, "/api/database", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(db["total_items"].as_u64(), Some(total));
+
+    assert_eq!(call(Method::GET, "/api/export?format=sql", None).await.0, StatusCode::OK);
+    let (status, bench) = call(Method::GET, "/api/bench/prepare?iterations=20", None).await;
+    assert_eq!((status, &bench["identical"]), (StatusCode::OK, &Value::Bool(true)));
+}
+
+#[tokio::test]
+async fn metric_routes() {
+    let _turn = SERIAL.lock().await;
+    create(r#"{"name":"metric source"}"#).await;
+    let (status, metrics) = call(Method::GET, "/api/metrics", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(metrics.as_array().unwrap().iter().any(|m| m["operation"] == "CREATE"));
+
+    let (status, ops) = call(Method::GET, "/api/metrics/by_operation/CREATE?limit=1", None).await;
+    assert_eq!((status, ops.as_array().unwrap().len()), (StatusCode::OK, 1));
+
+    let body = r#"{"operation":"LANG_CMP","execution_time_ms":1.0,"language":"rust"}"#;
+    assert_eq!(call(Method::POST, "/api/metrics_ingest", Some(body)).await.0, StatusCode::CREATED);
+    let body = r#"{"operation":"LANG_CMP","execution_time_ms":4.0,"language":"python"}"#;
+    assert_eq!(call(Method::POST, "/api/metrics_ingest", Some(body)).await.0, StatusCode::CREATED);
+    let (status, rows) = call(Method::GET, "/api/compare", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let row = rows.as_array().unwrap().iter().find(|r| r["operation"] == "LANG_CMP").unwrap();
+    assert_eq!(row["speedup"]["python"].as_f64(), Some(4.0));
+
+    let (status, tail) = call(Method::GET, "/api/metrics/tail?timeout_ms=0", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(tail["next_index"].is_u64());
+    let (status, cmp) = call(Method::GET, "/api/metrics/compare?a=CREATE&b=LANG_CMP", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(cmp.get("a").is_some() && cmp.get("b").is_some());
+    let (status, summary) = call(Method::GET, "/api/metrics/summary", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(!summary.as_array().unwrap().is_empty());
+
+    let (status, schema) = call(Method::GET, "/api/schema", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(schema["Metric"]["properties"]["execution_time_ms"]["type"], "number");
+    let (status, stats) = call(Method::GET, "/api/stats", None).await;
+    assert_eq!(status, StatusCode::OK);
+    for field in ["worker_threads", "metrics_capacity", "metrics_in_memory"] {
+        assert!(stats.get(field).is_some(), "stats missing {}", field);
+    }
+}
+
+#[tokio::test]
+async fn bulk_create_reports_each_batch() {
+    let _turn = SERIAL.lock().await;
+    let before = count().await;
+    let items: Vec<Value> = (0..1000).map(|i| serde_json::json!({ "name": format!("batched-{}", i) })).collect();
+    let (status, res) = call(Method::POST, "/api/bulk_create?batch=250", Some(&Value::from(items).to_string())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(res["inserted"].as_array().unwrap().len(), 1000);
+    let batches = res["batches"].as_array().unwrap();
+    assert_eq!(batches.len(), 4);
+    for batch in batches {
+        assert_eq!(batch["size"].as_u64(), Some(250));
+        assert!(batch["time_ms"].as_f64().is_some_and(|ms| ms >= 0.0), "{}", batch);
+    }
+    assert_eq!(count().await, before + 1000);
+}
+
+#[tokio::test]
+async fn read_all_follows_the_accept_header() {
+    let _turn = SERIAL.lock().await;
+    let id = create(r#"{"name":"accept, \"quoted\"","description":"csv row"}"#).await;
+    let total = count().await as usize;
+    let read = |accept: Option<&str>, uri: &str| {
+        let mut req = Request::builder().uri(uri);
+        if let Some(accept) = accept {
+            req = req.header(header::ACCEPT, accept);
+        }
+        req.body(Body::empty()).unwrap()
+    };
+
+    for accept in [None, Some("application/json"), Some("*/*")] {
+        let (status, headers, body) = send(read(accept, "/api/read")).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(headers[header::CONTENT_TYPE], "application/json", "{:?}", accept);
+        let items: Vec<Value> = serde_json::from_slice(&body).unwrap();
+        assert_eq!(items.len(), total);
+    }
+
+    let (status, headers, body) = send(read(Some("text/csv"), "/api/read")).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::CONTENT_TYPE], "text/csv");
+    let mut rows = csv::Reader::from_reader(body.as_slice());
+    assert_eq!(rows.headers().unwrap(), vec!["id", "name", "description", "tags"]);
+    let rows: Vec<csv::StringRecord> = rows.records().map(Result::unwrap).collect();
+    assert_eq!(rows.len(), total);
+    let row = rows.iter().find(|r| &r[0] == id.as_str()).unwrap();
+    assert_eq!((&row[1], &row[2]), ("accept, \"quoted\"", "csv row"));
+}
+
+#[tokio::test]
+async fn streamed_read_matches_the_buffered_one() {
+    let _turn = SERIAL.lock().await;
+    create(r#"{"name":"streamed","description":"with a \"quote\""}"#).await;
+    create(r#"{"name":"streamed without description"}"#).await;
+    let (status, buffered) = call(Method::GET, "/api/read", None).await;
+    assert_eq!(status, StatusCode::OK);
+    let (status, headers, body) = send(Request::get("/api/read?stream=true").body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(headers[header::CONTENT_TYPE], "application/json");
+    let streamed: Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(streamed, buffered);
+}
+
+#[tokio::test]
+async fn metrics_tail_waits_for_the_next_metric() {
+    let _turn = SERIAL.lock().await;
+    let (_, tail) = call(Method::GET, "/api/metrics/tail?timeout_ms=0", None).await;
+    let since = tail["next_index"].as_u64().unwrap();
+
+    let uri = format!("/api/metrics/tail?since={}&timeout_ms=10000", since);
+    let waiting = tokio::spawn(async move { call(Method::GET, &uri, None).await });
+    tokio::time::sleep(std::time::Duration::from_millis(200)).await;
+    assert!(!waiting.is_finished(), "tail returned before any metric was pushed");
+
+    let body = r#"{"operation":"TAIL_WAKE","execution_time_ms":1.5}"#;
+    assert_eq!(call(Method::POST, "/api/metrics_ingest", Some(body)).await.0, StatusCode::CREATED);
+    let (status, tail) = tokio::time::timeout(std::time::Duration::from_secs(5), waiting).await.unwrap().unwrap();
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(tail["metrics"][0]["operation"], "TAIL_WAKE");
+    assert_eq!(tail["next_index"].as_u64(), Some(since + 1));
+    assert_eq!(tail["missed"].as_u64(), Some(0));
+}
+
+#[tokio::test]
+async fn metrics_tail_times_out_empty() {
+    let _turn = SERIAL.lock().await;
+    let (_, tail) = call(Method::GET, "/api/metrics/tail?timeout_ms=0", None).await;
+    let since = tail["next_index"].as_u64().unwrap();
+    let started = std::time::Instant::now();
+    let (status, tail) = call(Method::GET, &format!("/api/metrics/tail?since={}&timeout_ms=150", since), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(started.elapsed() >= std::time::Duration::from_millis(150));
+    assert_eq!(tail["metrics"], serde_json::json!([]));
+    assert_eq!(tail["next_index"].as_u64(), Some(since));
+}
+
+#[tokio::test]
+async fn analytics_cache_hits_until_a_write() {
+    let _turn = SERIAL.lock().await;
+    let id = create(r#"{"name":"analytics cache"}"#).await;
+    let cache = |res: Value| res["cache"].as_str().unwrap().to_string();
+    let analytics = || call(Method::GET, "/api/items/analytics", None);
+
+    assert_eq!(cache(analytics().await.1), "miss");
+    let (status, res) = analytics().await;
+    assert_eq!((status, cache(res)), (StatusCode::OK, "hit".to_string()));
+
+    call(Method::PUT, &format!("/api/update/{}", id), Some(r#"{"name":"analytics cache, renamed"}"#)).await;
+    assert_eq!(cache(analytics().await.1), "miss");
+    assert_eq!(cache(analytics().await.1), "hit");
+
+    let before = analytics().await.1["count"].as_u64().unwrap();
+    call(Method::DELETE, &format!("/api/delete/{}", id), None).await;
+    let (_, res) = analytics().await;
+    assert_eq!((cache(res.clone()), res["count"].as_u64()), ("miss".to_string(), Some(before - 1)));
+}
+
+#[tokio::test]
+async fn request_id_is_echoed_and_recorded() {
+    let _turn = SERIAL.lock().await;
+    // The most recent metric carrying `id`, if any
+    let recorded = |id: String| async move {
+        let (_, metrics) = call(Method::GET, "/api/metrics", None).await;
+        metrics.as_array().unwrap().iter().rev().find(|m| m["request_id"] == id.as_str()).cloned()
+    };
+
+    let req = Request::post("/api/create")
+        .header("content-type", "application/json")
+        .header("x-request-id", "api-tests-request-1")
+        .body(Body::from(r#"{"name":"traced"}"#))
+        .unwrap();
+    let (status, headers, _) = send(req).await;
+    assert_eq!(status, StatusCode::CREATED);
+    assert_eq!(headers["x-request-id"], "api-tests-request-1");
+    let metric = recorded("api-tests-request-1".to_string()).await.unwrap();
+    assert_eq!(metric["operation"], "CREATE");
+
+    // Without one (or with an unusable one) the server makes up an id, and it still matches
+    let req = Request::get("/api/items/count").header("x-request-id", "has spaces").body(Body::empty()).unwrap();
+    let (_, headers, _) = send(req).await;
+    let generated = headers["x-request-id"].to_str().unwrap().to_string();
+    assert_ne!(generated, "has spaces");
+    assert_eq!(recorded(generated).await.unwrap()["operation"], "COUNT");
+}
+
+#[tokio::test]
+async fn timing_envelope_only_when_requested() {
+    let _turn = SERIAL.lock().await;
+    let id = create(r#"{"name":"enveloped"}"#).await;
+    let (status, plain) = call(Method::GET, &format!("/api/read/{}", id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(plain["name"], "enveloped");
+    assert!(plain.get("data").is_none() && plain.get("timing").is_none());
+    let (_, plain) = call(Method::GET, &format!("/api/read/{}?with_timing=false", id), None).await;
+    assert_eq!(plain["name"], "enveloped");
+
+    let (status, wrapped) = call(Method::GET, &format!("/api/read/{}?with_timing=true", id), None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(wrapped["data"], plain);
+    assert!(wrapped["timing"]["execution_time_ms"].is_number());
+    assert!(wrapped["timing"]["memory_mb"].is_number());
+    let (_, count) = call(Method::GET, "/api/items/count?with_timing=true", None).await;
+    assert!(count["data"]["count"].is_u64());
+
+    // Untimed routes and errors keep their shape whatever the query says
+    let (status, metrics) = call(Method::GET, "/api/metrics?with_timing=true", None).await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(metrics.is_array());
+    let (status, missing) = call(Method::GET, "/api/read/no-such-id?with_timing=true", None).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert!(missing.get("data").is_none());
+}
+
+#[tokio::test]
+async fn not_found_read_records_its_status() {
+    let _turn = SERIAL.lock().await;
+    let req = Request::get("/api/read/api-tests-absent").header("x-request-id", "api-tests-404").body(Body::empty()).unwrap();
+    assert_eq!(send(req).await.0, StatusCode::NOT_FOUND);
+    let (_, metrics) = call(Method::GET, "/api/metrics", None).await;
+    let metric = metrics.as_array().unwrap().iter().find(|m| m["request_id"] == "api-tests-404").unwrap();
+    assert_eq!(metric["status_code"], 404);
+    assert_eq!(metric["operation"], "READ (Description)");
+}
+
+#[tokio::test]
+async fn default_sample_rate_records_every_request() {
+    let _turn = SERIAL.lock().await;
+    let (_, before) = call(Method::GET, "/api/metrics/tail?timeout_ms=0", None).await;
+    for i in 0..5 {
+        create(&format!(r#"{{"name":"sampled {}"}}"#, i)).await;
+    }
+    let (_, after) = call(Method::GET, &format!("/api/metrics/tail?since={}&timeout_ms=0", before["next_index"]), None).await;
+    let creates = after["metrics"].as_array().unwrap().iter().filter(|m| m["operation"] == "CREATE").count();
+    assert_eq!(creates, 5);
+}
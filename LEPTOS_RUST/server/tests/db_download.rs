@@ -0,0 +1,34 @@
+// GET /api/db/download is only routed with ALLOW_DB_DOWNLOAD=true. Its own test binary, since the flag is
+// read from the environment when the app is built and tests/api.rs runs against an in-memory database
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use server::handlers::create_app;
+use tower::ServiceExt;
+
+async fn download(app: axum::Router) -> (StatusCode, Option<String>, Vec<u8>) {
+    let res = app.oneshot(Request::get("/api/db/download").body(Body::empty()).unwrap()).await.unwrap();
+    let status = res.status();
+    let content_type = res.headers().get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+    (status, content_type, hyper::body::to_bytes(res.into_body()).await.unwrap().to_vec())
+}
+
+#[tokio::test]
+async fn download_follows_the_flag() {
+    let scratch = std::env::temp_dir().join(format!("server-db-download-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&scratch);
+    std::fs::create_dir_all(&scratch).unwrap();
+    std::env::set_current_dir(&scratch).unwrap();
+    std::env::set_var("DB_PATH", scratch.join("download.sqlite"));
+
+    std::env::remove_var("ALLOW_DB_DOWNLOAD");
+    let (status, _, _) = download(create_app().unwrap()).await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+
+    std::env::set_var("ALLOW_DB_DOWNLOAD", "true");
+    let (status, content_type, body) = download(create_app().unwrap()).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/x-sqlite3"));
+    assert!(body.starts_with(b"SQLite format 3\0"));
+    assert_eq!(body.len() as u64, std::fs::metadata(scratch.join("download.sqlite")).unwrap().len());
+}
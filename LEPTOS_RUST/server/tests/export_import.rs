@@ -0,0 +1,56 @@
+// GET /api/export's JSON fed back through /api/bulk_create into an empty database reproduces the rows.
+// Its own test binary: it points DB_PATH at one database file and then another, which would pull the
+// shared database out from under tests/api.rs
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use rusqlite::Connection;
+use serde_json::Value;
+use server::handlers::create_app;
+use server::migrations;
+use tower::ServiceExt;
+
+async fn call(app: &axum::Router, method: Method, uri: &str, body: Option<String>) -> (StatusCode, Value) {
+    let req = Request::builder().method(method).uri(uri).header("content-type", "application/json");
+    let res = app.clone().oneshot(req.body(Body::from(body.unwrap_or_default())).unwrap()).await.unwrap();
+    let status = res.status();
+    (status, serde_json::from_slice(&hyper::body::to_bytes(res.into_body()).await.unwrap()).unwrap())
+}
+
+// (name, description) of every item, in insertion order; ids are generated afresh on import
+fn rows(items: &Value) -> Vec<(Value, Value)> {
+    items.as_array().unwrap().iter().map(|item| (item["name"].clone(), item["description"].clone())).collect()
+}
+
+#[tokio::test]
+async fn json_export_round_trips_through_bulk_create() {
+    let scratch = std::env::temp_dir().join(format!("server-export-import-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&scratch);
+    std::fs::create_dir_all(&scratch).unwrap();
+    std::env::set_current_dir(&scratch).unwrap();
+    let (source, target) = (scratch.join("source.sqlite"), scratch.join("target.sqlite"));
+    std::env::set_var("DB_PATH", &source);
+    let app = create_app().unwrap();
+
+    let items = serde_json::json!([
+        { "name": "plain" },
+        { "name": "with description", "description": "it's \"quoted\", with a comma" },
+        { "name": "unicode ✓", "description": "line\nbreak" },
+    ]);
+    let (status, _) = call(&app, Method::POST, "/api/bulk_create", Some(items.to_string())).await;
+    assert_eq!(status, StatusCode::CREATED);
+    let (status, exported) = call(&app, Method::GET, "/api/export?format=json", None).await;
+    assert_eq!(status, StatusCode::OK);
+    // the sample item the new database was seeded with, plus the three above
+    assert_eq!(exported.as_array().unwrap().len(), 4);
+
+    migrations::run(&mut Connection::open(&target).unwrap()).unwrap();
+    std::env::set_var("DB_PATH", &target);
+    let (_, empty) = call(&app, Method::GET, "/api/read", None).await;
+    assert_eq!(empty, serde_json::json!([]));
+    let (status, res) = call(&app, Method::POST, "/api/bulk_create", Some(exported.to_string())).await;
+    assert_eq!(status, StatusCode::CREATED, "{}", res);
+
+    let (_, imported) = call(&app, Method::GET, "/api/export?format=json", None).await;
+    assert_eq!(rows(&imported), rows(&exported));
+}
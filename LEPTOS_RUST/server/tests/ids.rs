@@ -0,0 +1,12 @@
+// Item ids from generate_id under the default ID_MODE (v7): time-ordered, so ids generated one after the
+// other sort in the order they were made, even within the same millisecond
+
+use server::utils::generate_id;
+
+#[test]
+fn v7_ids_sort_in_generation_order() {
+    std::env::remove_var("ID_MODE");
+    let ids: Vec<String> = (0..10_000).map(|_| generate_id()).collect();
+    assert!(ids.windows(2).all(|w| w[0] < w[1]), "v7 ids out of order");
+    assert!(ids.iter().all(|id| uuid::Uuid::parse_str(id).unwrap().get_version_num() == 7));
+}
@@ -0,0 +1,36 @@
+// Item::from_row over the rows tags::select_items_sql returns, on an in-memory database
+
+use rusqlite::{params, Connection};
+use server::item::Item;
+use server::{migrations, tags};
+
+fn database() -> Connection {
+    let mut conn = Connection::open_in_memory().unwrap();
+    migrations::run(&mut conn).unwrap();
+    conn
+}
+
+#[test]
+fn from_row_maps_every_column() {
+    let mut conn = database();
+    conn.execute("INSERT INTO items (id, name, description) VALUES (?1, ?2, ?3)", params!["a", "tagged", "has tags"]).unwrap();
+    conn.execute("INSERT INTO items (id, name, description) VALUES (?1, ?2, NULL)", params!["b", "bare"]).unwrap();
+    tags::add_tags(&mut conn, "a", &["red".to_string(), "blue".to_string()]).unwrap();
+
+    let mut stmt = conn.prepare(&tags::select_items_sql("")).unwrap();
+    let items: Vec<Item> = stmt.query_map([], Item::from_row).unwrap().map(Result::unwrap).collect();
+    assert_eq!(items.len(), 2);
+    assert_eq!((items[0].id.as_str(), items[0].name.as_str(), items[0].description.as_deref()), ("a", "tagged", Some("has tags")));
+    let mut item_tags = items[0].tags.clone();
+    item_tags.sort();
+    assert_eq!(item_tags, ["blue", "red"]);
+    assert_eq!(items[1], Item { id: "b".to_string(), name: "bare".to_string(), description: None, tags: vec![] });
+}
+
+#[test]
+fn from_row_reads_a_filtered_query() {
+    let conn = database();
+    conn.execute("INSERT INTO items (id, name, description) VALUES ('x', 'only', 'one')", []).unwrap();
+    let item = conn.query_row(&tags::select_items_sql(" WHERE i.id = ?1"), params!["x"], Item::from_row).unwrap();
+    assert_eq!(item, Item { id: "x".to_string(), name: "only".to_string(), description: Some("one".to_string()), tags: vec![] });
+}
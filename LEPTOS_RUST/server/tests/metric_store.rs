@@ -0,0 +1,71 @@
+// MetricStore, the bounded in-memory metric log behind /api/metrics and /api/metrics/summary
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use server::metric::{Metric, MetricStore};
+use server::quantile::P2Quantile;
+
+fn metric(operation: &str, execution_time_ms: f64) -> Metric {
+    Metric {
+        timestamp: String::new(),
+        operation: operation.to_string(),
+        execution_time_ms,
+        memory_mb: 0.0,
+        network_latency_ms: 0.0,
+        status_code: 200,
+        language: "rust".to_string(),
+        request_id: None,
+    }
+}
+
+#[test]
+fn full_store_drops_the_oldest() {
+    let capacity = 50;
+    let mut store = MetricStore::new(capacity);
+    for i in 0..capacity + 10 {
+        store.push(metric("READ", i as f64));
+    }
+    assert_eq!(store.len(), capacity);
+    assert_eq!(store.next_index(), capacity + 10);
+    let held: Vec<f64> = store.iter().map(|m| m.execution_time_ms).collect();
+    let expected: Vec<f64> = (10..capacity + 10).map(|i| i as f64).collect();
+    assert_eq!(held, expected);
+    // The ten evicted metrics still count towards the since-startup aggregates
+    assert_eq!(store.summary()[0].stats.samples, capacity + 10);
+    let (_, missed) = store.since(0);
+    assert_eq!(missed, 10);
+}
+
+// Nearest-rank percentile of sorted values, as summary_exact computes it
+fn exact_percentile(sorted: &[f64], p: f64) -> f64 {
+    let rank = ((p * sorted.len() as f64).ceil() as usize).clamp(1, sorted.len());
+    sorted[rank - 1]
+}
+
+#[test]
+fn p2_estimates_are_close_to_the_exact_percentiles() {
+    let mut rng = StdRng::seed_from_u64(894);
+    // Skewed like request timings: mostly fast, with a long tail
+    let times: Vec<f64> = (0..20_000).map(|_| {
+        let u: f64 = rng.gen_range(0.0..1.0);
+        5.0 - 20.0 * (1.0 - u).ln()
+    }).collect();
+    let mut sorted = times.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    for p in [0.5, 0.95, 0.99] {
+        let mut estimate = P2Quantile::new(p);
+        for &t in &times {
+            estimate.add(t);
+        }
+        let (approx, exact) = (estimate.estimate().unwrap(), exact_percentile(&sorted, p));
+        assert!((approx - exact).abs() / exact < 0.03, "p{}: P² {} vs exact {}", p * 100.0, approx, exact);
+    }
+
+    let mut store = MetricStore::new(times.len());
+    for &t in &times {
+        store.push(metric("READ", t));
+    }
+    let (approx, exact) = (store.summary()[0].stats.p95_ms, store.summary_exact()[0].stats.p95_ms);
+    assert_eq!(exact, exact_percentile(&sorted, 0.95));
+    assert!((approx - exact).abs() / exact < 0.03, "summary p95 {} vs summary_exact {}", approx, exact);
+}
@@ -0,0 +1,100 @@
+// The buffered metrics CSV each app holds in its AppState: rows reach the file every flush_rows appends,
+// and whatever is still buffered when the server stops is written by the shutdown flush
+
+use std::path::PathBuf;
+
+use server::metric::Metric;
+use server::utils::{shard_file_name, MetricsCsvWriter};
+
+const FLUSH_ROWS: usize = 3;
+
+fn scratch(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("server-metrics-csv-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn metric(i: usize) -> Metric {
+    Metric {
+        timestamp: format!("2026-01-01T00:00:{:02}Z", i),
+        operation: "CREATE".to_string(),
+        execution_time_ms: i as f64,
+        memory_mb: 0.0,
+        network_latency_ms: 0.0,
+        status_code: 201,
+        language: "rust".to_string(),
+        request_id: None,
+    }
+}
+
+// Header included; 0 while nothing has reached the file
+fn lines(dir: &std::path::Path) -> usize {
+    std::fs::read_to_string(dir.join("read.csv")).map(|s| s.lines().count()).unwrap_or(0)
+}
+
+#[test]
+fn rows_reach_the_file_every_flush_rows_appends() {
+    let dir = scratch("rows");
+    let writer = MetricsCsvWriter::new(&dir, FLUSH_ROWS);
+    for i in 0..FLUSH_ROWS - 1 {
+        writer.append(&metric(i)).unwrap();
+    }
+    assert_eq!(lines(&dir), 0);
+    writer.append(&metric(FLUSH_ROWS - 1)).unwrap();
+    assert_eq!(lines(&dir), 1 + FLUSH_ROWS);
+    for i in 0..FLUSH_ROWS {
+        writer.append(&metric(FLUSH_ROWS + i)).unwrap();
+    }
+    assert_eq!(lines(&dir), 1 + 2 * FLUSH_ROWS);
+    assert_eq!(writer.write_stats().0, 2 * FLUSH_ROWS as u64);
+}
+
+#[test]
+fn shutdown_flush_writes_the_remainder() {
+    let dir = scratch("shutdown");
+    let writer = MetricsCsvWriter::new(&dir, FLUSH_ROWS);
+    for i in 0..FLUSH_ROWS + 1 {
+        writer.append(&metric(i)).unwrap();
+    }
+    assert_eq!(lines(&dir), 1 + FLUSH_ROWS);
+    writer.flush().unwrap();
+    assert_eq!(lines(&dir), 2 + FLUSH_ROWS);
+    // nothing left to flush
+    writer.flush().unwrap();
+    assert_eq!(lines(&dir), 2 + FLUSH_ROWS);
+}
+
+#[test]
+fn writers_in_separate_directories_keep_separate_buffers() {
+    let (a, b) = (scratch("a"), scratch("b"));
+    let (first, second) = (MetricsCsvWriter::new(&a, FLUSH_ROWS), MetricsCsvWriter::new(&b, FLUSH_ROWS));
+    first.append(&metric(0)).unwrap();
+    second.flush().unwrap();
+    assert_eq!(lines(&a), 0);
+    assert_eq!(lines(&b), 0);
+    first.flush().unwrap();
+    assert_eq!(lines(&a), 2);
+}
+
+#[test]
+fn shard_file_names_follow_the_operation() {
+    assert_eq!(shard_file_name("CREATE"), "metrics_CREATE.csv");
+    assert_eq!(shard_file_name("BULK_CREATE_100"), "metrics_BULK_CREATE_100.csv");
+    assert_eq!(shard_file_name("py-read"), "metrics_py-read.csv");
+    assert_ne!(shard_file_name("READ_ALL"), shard_file_name("READ_ALL_STREAM"));
+    assert_eq!(shard_file_name("READ (Description)"), "metrics_READ__Description_.csv");
+}
+
+#[test]
+fn shard_file_names_cannot_name_a_path() {
+    for operation in ["../x", "..\\x", "/etc/passwd", "C:\\tmp", "a/b", "dot.dot", "tab\there", "ünïcode"] {
+        let name = shard_file_name(operation);
+        let stem = name.strip_prefix("metrics_").unwrap().strip_suffix(".csv").unwrap();
+        assert!(stem.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'), "{} -> {}", operation, name);
+        assert_eq!(std::path::Path::new(&name).components().count(), 1, "{} -> {}", operation, name);
+    }
+    assert_eq!(shard_file_name("../x"), "metrics____x.csv");
+    // Names that differ only in replaced characters share a file
+    assert_eq!(shard_file_name("a/b"), shard_file_name("a.b"));
+}
@@ -0,0 +1,32 @@
+// migrations::run on a database that is already up to date applies nothing and leaves its rows alone
+
+use rusqlite::Connection;
+use server::migrations;
+
+fn count(conn: &Connection, table: &str) -> i64 {
+    conn.query_row(&format!("SELECT COUNT(*) FROM {}", table), [], |row| row.get(0)).unwrap()
+}
+
+#[test]
+fn running_twice_is_idempotent() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    assert_eq!(migrations::current_version(&conn).unwrap(), 0);
+    let latest = migrations::latest_version();
+    assert_eq!(migrations::run(&mut conn).unwrap(), latest);
+    conn.execute("INSERT INTO items (id, name, description) VALUES ('a', 'first', NULL)", []).unwrap();
+
+    assert_eq!(migrations::run(&mut conn).unwrap(), latest);
+    assert_eq!(migrations::current_version(&conn).unwrap(), latest);
+    assert_eq!(count(&conn, "schema_version"), latest as i64);
+    assert_eq!(count(&conn, "items"), 1);
+}
+
+#[test]
+fn a_database_from_before_schema_version_keeps_its_items() {
+    let mut conn = Connection::open_in_memory().unwrap();
+    conn.execute_batch(migrations::MIGRATIONS[0]).unwrap();
+    conn.execute("INSERT INTO items (id, name, description) VALUES ('a', 'old', 'kept')", []).unwrap();
+    assert_eq!(migrations::run(&mut conn).unwrap(), migrations::latest_version());
+    let description: String = conn.query_row("SELECT description FROM items WHERE id = 'a'", [], |row| row.get(0)).unwrap();
+    assert_eq!(description, "kept");
+}
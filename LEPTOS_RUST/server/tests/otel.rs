@@ -0,0 +1,79 @@
+// OTLP export (`otel` feature) against a mock collector: a recorded metric reaches POST /v1/metrics when
+// the exporter shuts down. Run with `cargo test --features otel`
+
+#![cfg(feature = "otel")]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpListener;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use server::metric::Metric;
+
+// Accepts connections on a free local port and sends (path, body) of every request it receives,
+// answering each with an empty 200 as an OTLP/HTTP collector does
+fn mock_collector() -> (String, mpsc::Receiver<(String, Vec<u8>)>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let endpoint = format!("http://{}", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let tx = tx.clone();
+            std::thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut stream = stream;
+                loop {
+                    let mut request_line = String::new();
+                    if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+                        return;
+                    }
+                    let path = request_line.split_whitespace().nth(1).unwrap_or_default().to_string();
+                    let mut length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if line.trim_end().is_empty() {
+                            break;
+                        }
+                        if let Some((name, value)) = line.split_once(':') {
+                            if name.eq_ignore_ascii_case("content-length") {
+                                length = value.trim().parse().unwrap();
+                            }
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    let _ = tx.send((path, body));
+                    let _ = stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: application/x-protobuf\r\nContent-Length: 0\r\n\r\n");
+                }
+            });
+        }
+    });
+    (endpoint, rx)
+}
+
+#[test]
+fn recorded_metric_reaches_the_collector_on_shutdown() {
+    let (endpoint, requests) = mock_collector();
+    std::env::set_var("OTEL_EXPORTER_OTLP_ENDPOINT", &endpoint);
+    std::env::set_var("NO_PROXY", "127.0.0.1");
+    server::otel::init();
+    server::otel::record(&Metric {
+        timestamp: String::new(),
+        operation: "OTEL_MOCK_COLLECTOR".to_string(),
+        execution_time_ms: 12.5,
+        memory_mb: 0.25,
+        network_latency_ms: 1.0,
+        status_code: 201,
+        language: "rust".to_string(),
+        request_id: None,
+    });
+    server::otel::shutdown();
+
+    let contains = |body: &[u8], text: &str| body.windows(text.len()).any(|w| w == text.as_bytes());
+    let (path, body) = requests.recv_timeout(Duration::from_secs(10)).expect("no export reached the mock collector");
+    assert_eq!(path, "/v1/metrics");
+    for text in ["OTEL_MOCK_COLLECTOR", "execution_time_ms", "memory_mb", "network_latency_ms", "status_code"] {
+        assert!(contains(&body, text), "export is missing {}", text);
+    }
+}
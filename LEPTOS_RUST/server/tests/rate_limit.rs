@@ -0,0 +1,41 @@
+// RATE_LIMIT_RPS: a burst past the bucket gets 429 with Retry-After, /health never does. Its own test
+// binary, since the limit is read when the app is built and tests/api.rs runs unlimited
+
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use rusqlite::Connection;
+use server::handlers::create_app;
+use tower::ServiceExt;
+
+const RPS: usize = 5;
+
+#[tokio::test]
+async fn burst_past_the_limit_is_rejected() {
+    const DB_URI: &str = "file:rate-limit-tests?mode=memory&cache=shared";
+    let scratch = std::env::temp_dir().join(format!("server-rate-limit-tests-{}", std::process::id()));
+    std::fs::create_dir_all(&scratch).unwrap();
+    std::env::set_current_dir(&scratch).unwrap();
+    std::env::set_var("DB_PATH", DB_URI);
+    std::env::set_var("RATE_LIMIT_RPS", RPS.to_string());
+    let _keep_alive = Connection::open(DB_URI).unwrap();
+    let app = create_app().unwrap();
+    let get = |uri: &str| app.clone().oneshot(Request::get(uri).body(Body::empty()).unwrap());
+
+    // The bucket starts with one second's worth of requests
+    for _ in 0..RPS {
+        assert_eq!(get("/api/items/count").await.unwrap().status(), StatusCode::OK);
+    }
+    let res = get("/api/items/count").await.unwrap();
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    let retry_after: u64 = res.headers()[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+    assert!(retry_after >= 1);
+    assert!(res.headers().contains_key("x-request-id"));
+
+    for _ in 0..RPS * 2 {
+        assert_eq!(get("/health").await.unwrap().status(), StatusCode::OK);
+    }
+
+    // Under the limit again once a token has refilled
+    tokio::time::sleep(std::time::Duration::from_millis(1000 / RPS as u64 + 50)).await;
+    assert_eq!(get("/api/items/count").await.unwrap().status(), StatusCode::OK);
+}
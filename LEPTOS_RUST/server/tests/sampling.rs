@@ -0,0 +1,43 @@
+// METRICS_SAMPLE_RATE: 0 records no server metric, 1 records every one. Its own test binary, since the
+// rate is read once per process and tests/api.rs runs at the default of 1
+
+use axum::body::Body;
+use axum::http::{Method, Request, StatusCode};
+use rusqlite::Connection;
+use serde_json::Value;
+use server::handlers::create_app;
+use server::sampling::decide;
+use tower::ServiceExt;
+
+#[test]
+fn rate_zero_never_samples_and_one_always_does() {
+    assert!((0..10_000).all(|_| !decide(0.0)));
+    assert!((0..10_000).all(|_| decide(1.0)));
+    let kept = (0..10_000).filter(|_| decide(0.5)).count();
+    assert!((4_000..6_000).contains(&kept), "{} of 10000 kept at 0.5", kept);
+}
+
+#[tokio::test]
+async fn rate_zero_records_nothing() {
+    const DB_URI: &str = "file:sampling-tests?mode=memory&cache=shared";
+    let scratch = std::env::temp_dir().join(format!("server-sampling-tests-{}", std::process::id()));
+    std::fs::create_dir_all(&scratch).unwrap();
+    std::env::set_current_dir(&scratch).unwrap();
+    std::env::set_var("DB_PATH", DB_URI);
+    std::env::set_var("METRICS_SAMPLE_RATE", "0");
+    let _keep_alive = Connection::open(DB_URI).unwrap();
+    let app = create_app().unwrap();
+    let call = |method: Method, uri: &str, body: &str| {
+        let req = Request::builder().method(method).uri(uri).header("content-type", "application/json");
+        app.clone().oneshot(req.body(Body::from(body.to_string())).unwrap())
+    };
+
+    for i in 0..20 {
+        let res = call(Method::POST, "/api/create", &format!(r#"{{"name":"unsampled {}"}}"#, i)).await.unwrap();
+        assert_eq!(res.status(), StatusCode::CREATED);
+    }
+    assert_eq!(call(Method::GET, "/api/read", "").await.unwrap().status(), StatusCode::OK);
+    let res = call(Method::GET, "/api/metrics", "").await.unwrap();
+    let metrics: Value = serde_json::from_slice(&hyper::body::to_bytes(res.into_body()).await.unwrap()).unwrap();
+    assert_eq!(metrics, serde_json::json!([]));
+}
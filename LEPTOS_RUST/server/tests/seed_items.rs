@@ -0,0 +1,47 @@
+// SEED_ITEMS: a fresh database starts with the file's items instead of the single example item. Its own
+// test binary, since it needs database files of its own and sets SEED_ITEMS for the whole process
+
+use rusqlite::Connection;
+use server::handlers::create_app;
+
+fn names(db: &std::path::Path) -> Vec<String> {
+    let conn = Connection::open(db).unwrap();
+    let mut stmt = conn.prepare("SELECT name FROM items ORDER BY rowid").unwrap();
+    let names = stmt.query_map([], |row| row.get(0)).unwrap().map(Result::unwrap).collect();
+    names
+}
+
+#[test]
+fn seed_file_fills_a_fresh_database_only() {
+    let scratch = std::env::temp_dir().join(format!("server-seed-items-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&scratch);
+    std::fs::create_dir_all(&scratch).unwrap();
+    std::env::set_current_dir(&scratch).unwrap();
+    let (seed, bad_seed) = (scratch.join("seed.json"), scratch.join("bad-seed.json"));
+    std::fs::write(&seed, r#"[{"name":"first","description":"one"},{"name":"second"},{"name":"third","description":null}]"#).unwrap();
+    std::fs::write(&bad_seed, r#"[{"name":"fine"},{"description":"no name"}]"#).unwrap();
+
+    let db = scratch.join("seeded.sqlite");
+    std::env::set_var("DB_PATH", &db);
+    std::env::set_var("SEED_ITEMS", &seed);
+    let _ = create_app().unwrap();
+    assert_eq!(names(&db), ["first", "second", "third"]);
+    // An existing database isn't seeded again
+    let _ = create_app().unwrap();
+    assert_eq!(names(&db).len(), 3);
+
+    // A bad seed file fails startup, naming the element, before the database file is created
+    let db = scratch.join("never-created.sqlite");
+    std::env::set_var("DB_PATH", &db);
+    std::env::set_var("SEED_ITEMS", &bad_seed);
+    let err = create_app().err().unwrap();
+    assert!(err.contains("element 1"), "{}", err);
+    assert!(!db.exists());
+
+    // Without SEED_ITEMS a fresh database gets the example item
+    let db = scratch.join("example.sqlite");
+    std::env::set_var("DB_PATH", &db);
+    std::env::remove_var("SEED_ITEMS");
+    let _ = create_app().unwrap();
+    assert_eq!(names(&db), ["Example Item"]);
+}
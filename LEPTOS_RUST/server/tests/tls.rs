@@ -0,0 +1,25 @@
+// TLS_CERT / TLS_KEY loading: a file that is missing or isn't PEM is an error naming both paths, which main
+// reports before exiting instead of panicking
+
+use server::utils::load_tls_config;
+
+#[tokio::test]
+async fn missing_cert_path_is_an_error() {
+    let dir = std::env::temp_dir();
+    let cert = dir.join("server-tls-test-missing-cert.pem");
+    let key = dir.join("server-tls-test-missing-key.pem");
+    let err = load_tls_config(cert.to_str().unwrap(), key.to_str().unwrap()).await.err().unwrap();
+    assert!(err.contains("server-tls-test-missing-cert.pem"), "{}", err);
+    assert!(err.contains("server-tls-test-missing-key.pem"), "{}", err);
+}
+
+#[tokio::test]
+async fn files_that_are_not_pem_are_an_error() {
+    let dir = std::env::temp_dir().join(format!("server-tls-test-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let (cert, key) = (dir.join("cert.pem"), dir.join("key.pem"));
+    std::fs::write(&cert, "not a certificate").unwrap();
+    std::fs::write(&key, "not a key").unwrap();
+    let err = load_tls_config(cert.to_str().unwrap(), key.to_str().unwrap()).await.err().unwrap();
+    assert!(err.starts_with("failed to load TLS_CERT"), "{}", err);
+}
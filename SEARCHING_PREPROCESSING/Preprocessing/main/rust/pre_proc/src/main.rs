@@ -1,41 +1,147 @@
 #![allow(unused)]
 
-use std::{fs::File, time::Instant};
-use sysinfo::{Pid, System};
+use std::{
+    fs::File,
+    io::{IsTerminal, Seek, SeekFrom},
+    time::{Duration, Instant},
+};
 
 use anyhow::Result;
+use bench_utils::Timer;
+use bench_utils::stats::mean_std;
 use polars::prelude::*;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
+use serde::de::DeserializeOwned;
 use std::collections::HashMap;
 
-fn process_info(sys: &mut System, pid: Pid, print_log: String, start_time: &Instant) {
-    //println!("============={}================",print_log);
-    sys.refresh_all();
-    if let Some(process) = sys.process(pid) {
-        println!("Process name: {}", process.name());
-        println!("Executable path: {:?}", process.exe());
-        println!(
-            "Memory usage: {:.2} MB",
-            process.memory() as f64 / 1024.0 / 1024.0
-        );
-    } else {
-        println!("Process not found!");
+// Errors surfaced by the pipeline instead of panicking mid-run
+#[derive(Debug)]
+enum PreprocError {
+    Io(std::io::Error),
+    Polars(PolarsError),
+    NoNumericColumn,
+    ColumnMissing(String),
+    EmptyInput,
+}
+
+impl std::fmt::Display for PreprocError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PreprocError::Io(e) => write!(f, "I/O error: {}", e),
+            PreprocError::Polars(e) => write!(f, "Polars error: {}", e),
+            PreprocError::NoNumericColumn => {
+                write!(f, "no numeric column with missing values to impute")
+            }
+            PreprocError::ColumnMissing(c) => write!(f, "expected column '{}' not found", c),
+            PreprocError::EmptyInput => write!(f, "no CSV data on stdin"),
+        }
+    }
+}
+
+impl std::error::Error for PreprocError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PreprocError::Io(e) => Some(e),
+            PreprocError::Polars(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for PreprocError {
+    fn from(e: std::io::Error) -> Self {
+        PreprocError::Io(e)
+    }
+}
+
+impl From<PolarsError> for PreprocError {
+    fn from(e: PolarsError) -> Self {
+        PreprocError::Polars(e)
+    }
+}
+
+fn load_csv(path: &str) -> Result<DataFrame, PreprocError> {
+    load_csv_opts(path, b',', true)
+}
+
+// Input path that reads the CSV from stdin, e.g. `python gen.py | pre_proc -`
+const STDIN_PATH: &str = "-";
+
+// For tab/semicolon/pipe separated exports. Headerless files get Polars' generated
+// names (column_1, column_2, ...)
+fn load_csv_opts(path: &str, delimiter: u8, has_header: bool) -> Result<DataFrame, PreprocError> {
+    if path == STDIN_PATH {
+        return read_csv(std::io::stdin().lock(), delimiter, has_header);
     }
-    println!("Till -- {} : {:#?}", print_log, start_time.elapsed());
+    let file = File::open(path)?;
+    let options = CsvReadOptions::default()
+        .with_has_header(has_header)
+        .map_parse_options(|p| p.with_separator(delimiter));
+    let df = CsvReader::new(file).with_options(options).finish()?;
+    Ok(df)
 }
 
-fn load_csv(path: &str) -> Result<DataFrame> {
-    let file = File::open(path).unwrap();
-    let df = CsvReader::new(file).finish().unwrap();
+// CSV from any reader (stdin, a Cursor). The input is buffered in memory first because
+// Polars needs a seekable source; input that is empty or only whitespace is an error
+fn read_csv(
+    mut reader: impl std::io::Read,
+    delimiter: u8,
+    has_header: bool,
+) -> Result<DataFrame, PreprocError> {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+    if buf.iter().all(u8::is_ascii_whitespace) {
+        return Err(PreprocError::EmptyInput);
+    }
+    let options = CsvReadOptions::default()
+        .with_has_header(has_header)
+        .map_parse_options(|p| p.with_separator(delimiter));
+    let df = CsvReader::new(std::io::Cursor::new(buf))
+        .with_options(options)
+        .finish()?;
     Ok(df)
 }
 
+// Writes `df` as CSV with a header row, `null_value` standing in for every null cell. The default
+// of an empty field matches pandas' `to_csv`, but `read_csv` only reads it back as missing for
+// non-string columns, so `NA` or `NULL` keeps the round trip unambiguous
+fn write_csv(
+    writer: impl std::io::Write,
+    df: &mut DataFrame,
+    null_value: &str,
+) -> Result<(), PreprocError> {
+    CsvWriter::new(writer)
+        .include_header(true)
+        .with_null_value(null_value.to_string())
+        .finish(df)?;
+    Ok(())
+}
+
+// Polars' estimate of the memory the frame's buffers hold, in MB (1024 * 1024 bytes, like the process
+// figures). Buffers shared between frames count in each, so this isolates the data from the process overhead
+// rather than adding up to the process total
+fn estimated_size_mb(df: &DataFrame) -> f64 {
+    df.estimated_size() as f64 / (1024.0 * 1024.0)
+}
+
+// Fails with the first of `columns` the schema doesn't contain
+fn require_columns(schema: &Schema, columns: &[String]) -> Result<(), PreprocError> {
+    match columns.iter().find(|c| schema.get(c.as_str()).is_none()) {
+        Some(c) => Err(PreprocError::ColumnMissing(c.to_string())),
+        None => Ok(()),
+    }
+}
+
 fn get_column_types(df: &DataFrame) -> (Vec<String>, Vec<String>) {
+    get_schema_column_types(df.schema())
+}
+
+fn get_schema_column_types(schema: &Schema) -> (Vec<String>, Vec<String>) {
     let mut num_cols = Vec::new();
     let mut cat_cols = Vec::new();
 
-    for field in df.schema().iter_fields() {
+    for field in schema.iter_fields() {
         match field.dtype() {
             //For numerical datatypes
             DataType::Float32 | DataType::Float64 | DataType::Int32 | DataType::Int64 => {
@@ -70,6 +176,54 @@ fn column_most_missing(df: &DataFrame, columns: &[String]) -> Option<String> {
     best_col
 }
 
+// Progress of a long step: (step name, fraction complete in 0..=1). Chunked steps call it after every
+// chunk, the last call with 1.0
+type Progress<'a> = &'a dyn Fn(&str, f64);
+
+// Owned progress sink, as the pipeline keeps it
+type ProgressSink = Box<dyn Fn(&str, f64)>;
+
+// Rows per chunk of a progress-reporting column transform
+const PROGRESS_CHUNK_ROWS: usize = 1 << 16;
+
+// Default progress sink: one bar on stderr redrawn in place, ended with a newline at 1.0
+fn stderr_progress(step: &str, fraction: f64) {
+    const WIDTH: usize = 30;
+    let filled = (fraction.clamp(0.0, 1.0) * WIDTH as f64).round() as usize;
+    eprint!(
+        "\r{} [{}{}] {:>3.0}%",
+        step,
+        "#".repeat(filled),
+        " ".repeat(WIDTH - filled),
+        fraction * 100.0
+    );
+    if fraction >= 1.0 {
+        eprintln!();
+    }
+}
+
+// `f` over every non-null value of `ca`, PROGRESS_CHUNK_ROWS rows at a time, reporting after each chunk
+fn apply_chunked(
+    ca: &Float64Chunked,
+    step: &str,
+    progress: Progress,
+    f: impl Fn(f64) -> f64,
+) -> PolarsResult<Float64Chunked> {
+    let len = ca.len();
+    let mut out = Float64Chunked::full_null(ca.name().clone(), 0);
+    let mut offset = 0;
+    while offset < len {
+        let rows = PROGRESS_CHUNK_ROWS.min(len - offset);
+        out.append(&ca.slice(offset as i64, rows).apply(|opt| opt.map(&f)))?;
+        offset += rows;
+        progress(step, offset as f64 / len as f64);
+    }
+    if len == 0 {
+        progress(step, 1.0);
+    }
+    Ok(out.rechunk().into_owned())
+}
+
 fn impute_numerical(df: &mut DataFrame, column: &str, strategy: &str) -> PolarsResult<()> {
     let new_col = format!("{}_imputed_{}", column, strategy);
 
@@ -91,7 +245,7 @@ fn impute_numerical(df: &mut DataFrame, column: &str, strategy: &str) -> PolarsR
                     df.column(column)?.dtype()
                 )
                 .into(),
-            ))
+            ));
         }
     };
 
@@ -99,6 +253,28 @@ fn impute_numerical(df: &mut DataFrame, column: &str, strategy: &str) -> PolarsR
     Ok(())
 }
 
+// Applies `strategy` to every numeric column that has nulls, like pandas `df.fillna` over the whole frame.
+// Returns the columns it imputed, in frame order
+// Progress is reported per column
+fn impute_all_numeric(
+    df: &mut DataFrame,
+    strategy: &str,
+    progress: Progress,
+) -> PolarsResult<Vec<String>> {
+    let (num_cols, _) = get_column_types(df);
+    let mut imputed = Vec::new();
+    for column in num_cols {
+        if df.column(&column)?.null_count() > 0 {
+            imputed.push(column);
+        }
+    }
+    for (i, column) in imputed.iter().enumerate() {
+        impute_numerical(df, column, strategy)?;
+        progress("Imputation", (i + 1) as f64 / imputed.len() as f64);
+    }
+    Ok(imputed)
+}
+
 // fn process_categorical(
 //     df: &DataFrame,
 //     column: &str,
@@ -126,24 +302,41 @@ fn impute_numerical(df: &mut DataFrame, column: &str, strategy: &str) -> PolarsR
 //     Ok((df, new_col))
 // }
 
-fn normalize_column(df: &mut DataFrame, column: &str, method: &str) -> PolarsResult<()> {
+fn normalize_column(
+    df: &mut DataFrame,
+    column: &str,
+    method: &str,
+    progress: Progress,
+) -> PolarsResult<()> {
     let s = df.column(column)?.f64()?;
     let new_col = format!("{}_normalized_{}", column, method);
 
     let mut norm = match method {
         "minmax" => {
-            let min = s.min().unwrap();
-            let max = s.max().unwrap();
+            let (min, max) = match (s.min(), s.max()) {
+                (Some(min), Some(max)) => (min, max),
+                _ => {
+                    return Err(PolarsError::ComputeError(
+                        format!("column '{}' has no values to normalize", column).into(),
+                    ));
+                }
+            };
             //println!("Normalizing '{}' with minmax: min={:.4}, max={:.4}", column, min, max);
-            s.apply(|opt| opt.map(|v| (v - min) / (max - min)))
-                .into_series()
-        }
-        // "zscore" => {
-        //     let mean = s.mean().unwrap();
-        //     let std = s.std_as_series(1).f64()?.get(0).unwrap();
-        //     //println!("Normalizing '{}' with zscore: mean={:.4}, std={:.4}", column, mean, std);
-        //     s.apply(|opt| opt.map(|v| (v - mean) / std)).into_series()
-        // }
+            apply_chunked(s, "Normalise", progress, |v| (v - min) / (max - min))?.into_series()
+        }
+        "zscore" => {
+            // Welford rather than Polars' sum of squares, which loses precision on large reimbursement values.
+            // Sample std (ddof=1) like pandas' .std() in new_proc.py, so both sides produce the same column
+            let values: Vec<f64> = s.into_iter().flatten().collect();
+            if values.is_empty() {
+                return Err(PolarsError::ComputeError(
+                    format!("column '{}' has no values to normalize", column).into(),
+                ));
+            }
+            let (mean, std) = mean_std(&values, 1);
+            //println!("Normalizing '{}' with zscore: mean={:.4}, std={:.4}", column, mean, std);
+            apply_chunked(s, "Normalise", progress, |v| (v - mean) / std)?.into_series()
+        }
         _ => {
             //println!("Unknown method '{}', no normalization applied", method);
             s.clone().into_series()
@@ -156,6 +349,31 @@ fn normalize_column(df: &mut DataFrame, column: &str, method: &str) -> PolarsRes
     Ok(())
 }
 
+// How far a z-scored column's mean may be from 0, and its standard deviation from 1
+const NORM_TOLERANCE: f64 = 1e-6;
+
+// Sanity check of a normalize_column output column: minmax values must all lie in [0, 1], zscore
+// values must have mean 0 and sample standard deviation (ddof=1) 1 within NORM_TOLERANCE. A constant source column
+// divides by zero and leaves NaN everywhere, which fails both. Nulls are skipped; a column
+// with no values fails
+fn verify_normalized(df: &DataFrame, column: &str, method: &str) -> PolarsResult<bool> {
+    let values: Vec<f64> = df.column(column)?.f64()?.into_iter().flatten().collect();
+    if values.is_empty() {
+        return Ok(false);
+    }
+    match method {
+        "minmax" => Ok(values.iter().all(|v| (0.0..=1.0).contains(v))),
+        "zscore" => {
+            // Same definition normalize_column divides by
+            let (mean, std) = mean_std(&values, 1);
+            Ok(mean.abs() <= NORM_TOLERANCE && (std - 1.0).abs() <= NORM_TOLERANCE)
+        }
+        _ => Err(PolarsError::ComputeError(
+            format!("unknown normalisation method '{}'", method).into(),
+        )),
+    }
+}
+
 // fn convert_type(df: &DataFrame, column: &str, dtype: DataType) -> Result<(DataFrame, String)> {
 //     let mut df = df.clone();
 //     let new_col = format!("{}_as_{:?}", column, dtype);
@@ -177,6 +395,178 @@ fn add_column(
     Ok(())
 }
 
+// Replaces NaN and +/-Inf in every float column with `replace_with` (None writes a null).
+// Transforms like minmax on a constant column or divide-by-zero ratios produce these silently.
+// Returns the number of values replaced.
+fn sanitize_numeric(df: &mut DataFrame, replace_with: Option<f64>) -> PolarsResult<usize> {
+    let float_cols: Vec<(String, DataType)> = df
+        .schema()
+        .iter_fields()
+        .filter(|f| matches!(f.dtype(), DataType::Float32 | DataType::Float64))
+        .map(|f| (f.name().to_string(), f.dtype().clone()))
+        .collect();
+
+    let mut replaced = 0;
+    for (name, dtype) in float_cols {
+        let s = df.column(&name)?.cast(&DataType::Float64)?;
+        let ca = s.f64()?;
+        let bad = ca
+            .into_iter()
+            .filter(|v| v.is_some_and(|v| !v.is_finite()))
+            .count();
+        if bad == 0 {
+            continue;
+        }
+        replaced += bad;
+        let mut cleaned = ca
+            .apply(|opt| opt.and_then(|v| if v.is_finite() { Some(v) } else { replace_with }))
+            .into_series()
+            .cast(&dtype)?;
+        cleaned.rename(name.as_str().into());
+        df.with_column(cleaned)?;
+    }
+    Ok(replaced)
+}
+
+// Casts every String column whose non-null values all parse as numbers (surrounding whitespace allowed)
+// to Float64, the way pandas infers a numeric dtype. A column with a single non-numeric value is left alone,
+// and an all-null String column (an empty CSV column) is cast too. Returns the coerced column names in schema order.
+fn auto_coerce_numeric_strings(df: &mut DataFrame) -> PolarsResult<Vec<String>> {
+    let string_cols: Vec<String> = df
+        .schema()
+        .iter_fields()
+        .filter(|f| matches!(f.dtype(), DataType::String))
+        .map(|f| f.name().to_string())
+        .collect();
+
+    let mut coerced = Vec::new();
+    for name in string_cols {
+        let ca = df.column(&name)?.str()?.clone();
+        let parsed: Option<Vec<Option<f64>>> = ca
+            .iter()
+            .map(|v| match v {
+                Some(v) => v.trim().parse::<f64>().ok().map(Some),
+                None => Some(None),
+            })
+            .collect();
+        if let Some(values) = parsed {
+            df.with_column(
+                Float64Chunked::from_iter_options(name.as_str().into(), values.into_iter())
+                    .into_series(),
+            )?;
+            coerced.push(name);
+        }
+    }
+    Ok(coerced)
+}
+
+// Equal-width histogram of a numeric column, the data behind pandas `.hist()`.
+// Returns one row per bin: `bin_start` (left edge) and `count`; every bin is (max - min) / bins wide
+// and the last one also holds the maximum. Nulls, NaN and +/-Inf are left out; a column without values gives no rows.
+fn histogram(df: &DataFrame, column: &str, bins: usize) -> PolarsResult<DataFrame> {
+    if bins == 0 {
+        return Err(PolarsError::ComputeError(
+            "histogram needs at least one bin".into(),
+        ));
+    }
+    let s = df.column(column)?.cast(&DataType::Float64)?;
+    let values: Vec<f64> = s
+        .f64()?
+        .iter()
+        .flatten()
+        .filter(|v| v.is_finite())
+        .collect();
+    if values.is_empty() {
+        return df!("bin_start" => Vec::<f64>::new(), "count" => Vec::<u32>::new());
+    }
+    let (min, max) = values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), &v| {
+            (lo.min(v), hi.max(v))
+        });
+
+    // A constant column has zero width: everything lands in the first bin
+    let width = (max - min) / bins as f64;
+    let mut counts = vec![0u32; bins];
+    for v in values {
+        let bin = if width > 0.0 {
+            (((v - min) / width) as usize).min(bins - 1)
+        } else {
+            0
+        };
+        counts[bin] += 1;
+    }
+    let starts: Vec<f64> = (0..bins).map(|i| min + i as f64 * width).collect();
+    df!("bin_start" => starts, "count" => counts)
+}
+
+// Distinct non-null values per column, highest first (ties keep the order of `columns`).
+// Decides one-hot vs label encoding for the categorical columns
+fn cardinality(df: &DataFrame, columns: &[String]) -> PolarsResult<Vec<(String, usize)>> {
+    let mut counts = Vec::with_capacity(columns.len());
+    for c in columns {
+        counts.push((c.clone(), df.column(c)?.drop_nulls().n_unique()?));
+    }
+    counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+    Ok(counts)
+}
+
+// Distinct non-null values of a string column, sorted
+fn sorted_categories(ca: &StringChunked) -> Vec<&str> {
+    let mut values: Vec<&str> = ca.iter().flatten().collect();
+    values.sort_unstable();
+    values.dedup();
+    values
+}
+
+// Adds `{column}_label`: the index of each value among the sorted distinct values, null stays null
+fn label_encode(df: &mut DataFrame, column: &str) -> PolarsResult<()> {
+    let ca = df.column(column)?.str()?.clone();
+    let categories = sorted_categories(&ca);
+    let codes = UInt32Chunked::from_iter_options(
+        format!("{}_label", column).into(),
+        ca.iter()
+            .map(|v| v.and_then(|v| categories.binary_search(&v).ok().map(|i| i as u32))),
+    );
+    df.with_column(codes.into_series())?;
+    Ok(())
+}
+
+// Adds one boolean column `{column}_{value}` per distinct value, like pandas `get_dummies`.
+// A null row is false in every indicator column
+fn one_hot_encode(df: &mut DataFrame, column: &str) -> PolarsResult<()> {
+    let ca = df.column(column)?.str()?.clone();
+    for value in sorted_categories(&ca) {
+        let indicator: Vec<bool> = ca.iter().map(|v| v == Some(value)).collect();
+        df.with_column(BooleanChunked::from_slice(
+            format!("{}_{}", column, value).into(),
+            &indicator,
+        ))?;
+    }
+    Ok(())
+}
+
+// One-hot encodes columns with at most `max_one_hot` distinct values and label encodes the rest.
+// Returns (column, distinct values, encoding) in cardinality order
+fn encode_categorical(
+    df: &mut DataFrame,
+    columns: &[String],
+    max_one_hot: usize,
+) -> PolarsResult<Vec<(String, usize, &'static str)>> {
+    let mut encoded = Vec::new();
+    for (column, distinct) in cardinality(df, columns)? {
+        let encoding = if distinct <= max_one_hot {
+            one_hot_encode(df, &column)?;
+            "one-hot"
+        } else {
+            label_encode(df, &column)?;
+            "label"
+        };
+        encoded.push((column, distinct, encoding));
+    }
+    Ok(encoded)
+}
+
 fn filter_rows(df: &mut DataFrame, column: &str) -> PolarsResult<()> {
     let mask = df.column(column)?.f64()?.gt(0.0);
     *df = df.filter(&mask)?;
@@ -203,7 +593,7 @@ fn select_drop_columns(
 ) -> PolarsResult<DataFrame> {
     let mut df = df.clone();
     if let Some(cols) = select {
-        let col_vec: Vec<&str> = cols.iter().map(|&c| c).collect();
+        let col_vec: Vec<&str> = cols.to_vec();
         df = df.select(col_vec)?;
     }
     if let Some(cols) = drop {
@@ -214,7 +604,7 @@ fn select_drop_columns(
     Ok(df)
 }
 
-fn sample_df(df: &DataFrame, frac: f64) -> Result<DataFrame> {
+fn sample_df(df: &DataFrame, frac: f64) -> Result<DataFrame, PreprocError> {
     let n = (df.height() as f64 * frac).round() as usize;
     let mut indices: Vec<usize> = (0..df.height()).collect();
     indices.shuffle(&mut thread_rng());
@@ -225,162 +615,1841 @@ fn sample_df(df: &DataFrame, frac: f64) -> Result<DataFrame> {
     ))?)
 }
 
-fn full_preprocessing_pipeline(path: &str) -> Result<()> {
-    //println!("Starting preprocessing pipeline...");
+// Every k-th row starting at row 0, k = 1/frac. Reproducible, like pandas `df.iloc[::k]`
+fn sample_df_systematic(df: &DataFrame, frac: f64) -> Result<DataFrame, PreprocError> {
+    let k = (1.0 / frac).round().max(1.0) as usize;
+    let take: Vec<u32> = (0..df.height()).step_by(k).map(|x| x as u32).collect();
+    Ok(df.take(&UInt32Chunked::from_vec("idx".into(), take))?)
+}
 
-    // Start timer
-    let start_time = Instant::now();
+// First `frac` of the rows, like pandas `df.iloc[:n]`
+fn sample_df_head(df: &DataFrame, frac: f64) -> Result<DataFrame, PreprocError> {
+    let n = (df.height() as f64 * frac).round() as usize;
+    Ok(df.head(Some(n)))
+}
 
-    // Initialize system info
-    let mut sys = System::new_all();
-    sys.refresh_all();
+// Row selection used by the Sampling step
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum SampleMethod {
+    Random,
+    Systematic,
+    Head,
+}
 
-    // Get current process ID
-    let pid = sysinfo::get_current_pid().unwrap();
-    process_info(
-        &mut sys,
-        pid,
-        String::from("Initial Process info"),
-        &start_time,
-    );
+impl SampleMethod {
+    fn parse(s: &str) -> Option<SampleMethod> {
+        match s {
+            "random" => Some(SampleMethod::Random),
+            "systematic" => Some(SampleMethod::Systematic),
+            "head" => Some(SampleMethod::Head),
+            _ => None,
+        }
+    }
 
-    //===================================================================================================================
-    let mut df = load_csv(path)?;
-    let (rows, cols) = df.shape();
-    //println!("DataFrame shape: ({}, {})", rows, cols);
-    process_info(
-        &mut sys,
-        pid,
-        String::from("After Loading CSV"),
-        &start_time,
-    );
-    //===================================================================================================================
-    /*
-    for field in df.schema().iter_names_and_dtypes() {
-        //println!("{:#?}", field);
-    }
-    */
-
-    let mut df = df
-        .lazy()
-        .with_column(col("BENE_DEATH_DT").cast(DataType::Float64))
-        .collect()?;
-
-    process_info(
-        &mut sys,
-        pid,
-        String::from("Type Casting \'BENE_DEATH_DT\'"),
-        &start_time,
-    );
+    fn name(self) -> &'static str {
+        match self {
+            SampleMethod::Random => "random",
+            SampleMethod::Systematic => "systematic",
+            SampleMethod::Head => "head",
+        }
+    }
+
+    fn sample(self, df: &DataFrame, frac: f64) -> Result<DataFrame, PreprocError> {
+        match self {
+            SampleMethod::Random => sample_df(df, frac),
+            SampleMethod::Systematic => sample_df_systematic(df, frac),
+            SampleMethod::Head => sample_df_head(df, frac),
+        }
+    }
+}
 
-    /*
-    for field in df.schema().iter_names_and_dtypes() {
-        //println!("{:#?}", field);
+// Sorts the frame by one column. `stable` keeps rows with equal keys in their original order
+// (Polars' maintain_order); unstable is faster, but ties can come out in any order
+fn sort_column(
+    df: &DataFrame,
+    column: &str,
+    descending: bool,
+    stable: bool,
+) -> PolarsResult<DataFrame> {
+    df.sort(
+        [column],
+        SortMultipleOptions::new()
+            .with_order_descending(descending)
+            .with_maintain_order(stable),
+    )
+}
+
+// Exponential search over a column already sorted ascending, returning the row index of `target`.
+// Nulls are skipped; an unsorted column is an error rather than a silently wrong answer.
+fn search_sorted_column(df: &DataFrame, column: &str, target: f64) -> PolarsResult<Option<usize>> {
+    let s = df.column(column)?.cast(&DataType::Float64)?;
+    let values: Vec<(usize, f64)> = s
+        .f64()?
+        .iter()
+        .enumerate()
+        .filter_map(|(row, v)| v.map(|v| (row, v)))
+        .collect();
+    if values.windows(2).any(|w| w[0].1 > w[1].1) {
+        return Err(PolarsError::ComputeError(
+            format!("column '{}' is not sorted ascending", column).into(),
+        ));
+    }
+    if values.is_empty() {
+        return Ok(None);
     }
-    */
 
-    //=======================================================================================================================
+    // Double the bound until it passes the target, then binary search inside the last jump
+    let mut bound = 1;
+    while bound < values.len() && values[bound].1 < target {
+        bound *= 2;
+    }
+    let (mut low, mut high) = (bound / 2, bound.min(values.len() - 1));
+    while low <= high {
+        let mid = low + (high - low) / 2;
+        if values[mid].1 == target {
+            return Ok(Some(values[mid].0));
+        } else if values[mid].1 < target {
+            low = mid + 1;
+        } else if mid == 0 {
+            break;
+        } else {
+            high = mid - 1;
+        }
+    }
+    Ok(None)
+}
 
-    let (num_cols, cat_cols) = get_column_types(&df);
-    process_info(
-        &mut sys,
-        pid,
-        String::from("Getting column Types"),
-        &start_time,
-    );
-    // //println!("Numerical Columns : {:#?}",num_cols);
-    // //println!("Categorical Columns : {:#?}",cat_cols);
+// JSON equivalent of one cell; NaN/Inf floats have no JSON form and become null
+fn any_value_to_json(v: AnyValue) -> serde_json::Value {
+    use serde_json::Value;
+    match v {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(b) => Value::Bool(b),
+        AnyValue::Int8(n) => n.into(),
+        AnyValue::Int16(n) => n.into(),
+        AnyValue::Int32(n) => n.into(),
+        AnyValue::Int64(n) => n.into(),
+        AnyValue::UInt8(n) => n.into(),
+        AnyValue::UInt16(n) => n.into(),
+        AnyValue::UInt32(n) => n.into(),
+        AnyValue::UInt64(n) => n.into(),
+        AnyValue::Float32(f) => {
+            serde_json::Number::from_f64(f as f64).map_or(Value::Null, Value::Number)
+        }
+        AnyValue::Float64(f) => serde_json::Number::from_f64(f).map_or(Value::Null, Value::Number),
+        other => match other.get_str() {
+            Some(s) => Value::String(s.to_string()),
+            None => Value::String(other.to_string()),
+        },
+    }
+}
 
-    //=======================================================================================================================
+// Converts every row into a `T` keyed by column name, the way an API serving DataFrame data
+// would build its row structs (the server's `Item`). Columns are walked one at a time and
+// scattered into per-row maps, so the cost measured is the columnar-to-row transpose itself.
+// Missing struct fields and type mismatches are reported with the offending row index.
+fn dataframe_to_rows<T: DeserializeOwned>(df: &DataFrame) -> PolarsResult<Vec<T>> {
+    let mut rows: Vec<serde_json::Map<String, serde_json::Value>> = (0..df.height())
+        .map(|_| serde_json::Map::with_capacity(df.width()))
+        .collect();
+    for column in df.get_columns() {
+        let name = column.name().to_string();
+        for (row, v) in rows.iter_mut().zip(column.as_materialized_series().iter()) {
+            row.insert(name.clone(), any_value_to_json(v));
+        }
+    }
+    rows.into_iter()
+        .enumerate()
+        .map(|(i, row)| {
+            serde_json::from_value(serde_json::Value::Object(row))
+                .map_err(|e| PolarsError::ComputeError(format!("row {}: {}", i, e).into()))
+        })
+        .collect()
+}
+
+const DEFAULT_PATH: &str = r"C:\Users\pm018586\OneDrive - Zelis Healthcare\Documents\Presentations\Data Preprocessing Python VS Rust\Datasets\176541_DE1_0_2008_Beneficiary_Summary_File_Sample_1\DE1_0_2008_Beneficiary_Summary_File_Sample_1.csv";
+
+// Spread of one checkpoint's delta over the measured runs of `--repeat`
+struct CheckpointStats {
+    label: String,
+    runs: usize,
+    median_ms: f64,
+    stddev_ms: f64,
+}
 
-    // For numeric column, we assume at least one exists
-    let num_col = column_most_missing(&df, &num_cols)
-        .expect("No numeric col found")
-        .clone(); // make an owned String
+// Per-checkpoint median and (population) standard deviation of the deltas. Every run of one
+// pipeline passes the same checkpoints, which are matched up by position
+fn checkpoint_stats(runs: &[Vec<(String, Duration)>]) -> Vec<CheckpointStats> {
+    let Some(first) = runs.first() else {
+        return Vec::new();
+    };
+    (0..first.len())
+        .map(|i| {
+            let mut ms: Vec<f64> = runs
+                .iter()
+                .map(|run| run[i].1.as_secs_f64() * 1000.0)
+                .collect();
+            ms.sort_by(|a, b| a.total_cmp(b));
+            let n = ms.len();
+            let median_ms = if n % 2 == 1 {
+                ms[n / 2]
+            } else {
+                (ms[n / 2 - 1] + ms[n / 2]) / 2.0
+            };
+            let (_, stddev_ms) = mean_std(&ms, 0);
+            CheckpointStats {
+                label: first[i].0.clone(),
+                runs: n,
+                median_ms,
+                stddev_ms,
+            }
+        })
+        .collect()
+}
 
-    // For categorical column, handle None safely
-    let cat_col: Option<String> = column_most_missing(&df, &cat_cols).map(|col| col.clone()); // clone the &String into owned String
+// Columns one step of `Pipeline::run` reads and creates, used by `validate`
+struct StepColumns {
+    name: &'static str,
+    reads: Vec<String>,
+    creates: Vec<String>,
+}
 
-    // Print
-    //println!("Numerical column: {}", num_col);
+// Configuration for the benchmark pipeline.
+// `run` executes every step with a process_info checkpoint after each one,
+// `plan` resolves the same steps and their target columns without processing any data.
+struct Pipeline {
+    path: String,
+    cast_col: String,
+    norm_col: String,
+    // One of NORM_METHODS
+    norm_method: String,
+    select_col: String,
+    drop_col: String,
+    sample_frac: f64,
+    sample_method: SampleMethod,
+    delimiter: u8,
+    has_header: bool,
+    // (column, strategy) pairs to impute; empty means auto-pick the most-missing numeric column
+    impute: Vec<(String, String)>,
+    // Strategy for every numeric column with nulls; takes precedence over `impute`
+    impute_all: Option<String>,
+    // Time converting the loaded (full-width) frame into row structs
+    to_rows: bool,
+    // Bin count for a histogram of `norm_col` after sanitizing; None skips the step
+    histogram_bins: Option<usize>,
+    // Keep tied rows in order in both sorts of `norm_col`
+    stable_sort: bool,
+    // Encode the categorical columns, one-hot up to this many distinct values and label above;
+    // None skips the step
+    max_one_hot: Option<usize>,
+    // Replace the `cast_col` cast with auto_coerce_numeric_strings over every column
+    auto_coerce: bool,
+    // Called during imputation and normalisation; a stderr bar when stderr is a terminal, otherwise None
+    progress: Option<ProgressSink>,
+    // One `label,elapsed_ms,memory_mb` line per checkpoint instead of the process block
+    quiet: bool,
+    // CSV file the processed frame is written to, like the Python script's `--out`; None keeps it in memory
+    output: Option<String>,
+    // Written for null cells in `output`
+    null_value: String,
+}
 
-    if let Some(col) = cat_col {
-        //println!("Most missing categorical column: {}", col);
-    } else {
-        //println!("No categorical column found");
+impl Pipeline {
+    fn new(path: &str) -> Self {
+        Pipeline {
+            path: path.to_string(),
+            cast_col: String::from("BENE_DEATH_DT"),
+            norm_col: String::from("MEDREIMB_CAR"),
+            norm_method: String::from("minmax"),
+            select_col: String::from("BENE_COUNTY_CD"),
+            drop_col: String::from("SP_STRKETIA"),
+            sample_frac: 0.1,
+            sample_method: SampleMethod::Random,
+            delimiter: b',',
+            has_header: true,
+            impute: Vec::new(),
+            impute_all: None,
+            to_rows: false,
+            histogram_bins: None,
+            stable_sort: false,
+            max_one_hot: None,
+            auto_coerce: false,
+            progress: std::io::stderr()
+                .is_terminal()
+                .then(|| Box::new(stderr_progress) as ProgressSink),
+            quiet: false,
+            output: None,
+            null_value: String::new(),
+        }
     }
-    process_info(
-        &mut sys,
-        pid,
-        String::from("Detect most number of missing values"),
-        &start_time,
-    );
 
-    //=======================================================================================================================
+    fn with_output(mut self, path: Option<String>, null_value: &str) -> Self {
+        self.output = path;
+        self.null_value = null_value.to_string();
+        self
+    }
 
-    impute_numerical(&mut df, &num_col, "mean")?;
-    process_info(&mut sys, pid, String::from("Imputation"), &start_time);
+    fn with_quiet(mut self, quiet: bool) -> Self {
+        self.quiet = quiet;
+        self
+    }
 
-    //=======================================================================================================================
-    // let (df, cat_processed) = process_categorical(&df, &cat_col, "mode", true, true)?;
+    fn with_progress(mut self, progress: Option<ProgressSink>) -> Self {
+        self.progress = progress;
+        self
+    }
 
-    let norm_col = String::from("MEDREIMB_CAR");
-    normalize_column(&mut df, &norm_col, "minmax")?;
-    process_info(&mut sys, pid, String::from("Normalise"), &start_time);
-    //=======================================================================================================================
-    // let (df, num_as_int) = convert_type(&df, &num_imputed, DataType::Int64)?;
+    fn with_auto_coerce(mut self, auto_coerce: bool) -> Self {
+        self.auto_coerce = auto_coerce;
+        self
+    }
 
-    add_column(&mut df, "column_squared", &norm_col, |v| v * v)?;
-    process_info(&mut sys, pid, String::from("Add Column"), &start_time);
-    //=======================================================================================================================
+    fn with_encoding(mut self, max_one_hot: Option<usize>) -> Self {
+        self.max_one_hot = max_one_hot;
+        self
+    }
 
-    filter_rows(&mut df, &norm_col)?;
-    process_info(&mut sys, pid, String::from("Filter"), &start_time);
-    //=======================================================================================================================
-    let mut df = df
-        .sort(
-            [&norm_col],
-            SortMultipleOptions::new().with_order_descending(false),
-        )
-        .unwrap();
-    process_info(&mut sys, pid, String::from("Sort - Ascending"), &start_time);
-    let mut df = df
-        .sort(
-            [&norm_col],
-            SortMultipleOptions::new().with_order_descending(false),
-        )
-        .unwrap();
-    process_info(
-        &mut sys,
-        pid,
-        String::from("Sort - Descending"),
-        &start_time,
-    );
-    //=======================================================================================================================
-    let drop_col = String::from("SP_STRKETIA");
-    let select_col = String::from("BENE_COUNTY_CD");
-    process_info(&mut sys, pid, String::from("Creating Vars"), &start_time);
-
-    let df_selected = select_drop_columns(&mut df, Some(&[&select_col]), None)?;
-    process_info(&mut sys, pid, String::from("Column Selection"), &start_time);
-
-    let df_drop = select_drop_columns(&mut df, None, Some(&[&drop_col]))?;
-    process_info(&mut sys, pid, String::from("Column Drop"), &start_time);
-    //=======================================================================================================================
-    let df_sampled = sample_df(&df_selected, 0.1)?;
-    process_info(&mut sys, pid, String::from("Sampling"), &start_time);
-
-    // let mut rename_map = HashMap::new();
-    // rename_map.insert(num_norm.as_str(), "normalized_value");
-    // let df = rename_columns(&df, rename_map)?;
-    // let df_agg = aggregate_df(&df, &cat_processed, "normalized_value")?;
-    // //println!("✅ Aggregated result:\n{df_agg}");
-    // //println!("✅ Sampled subset:\n{df_sampled}");
+    fn with_stable_sort(mut self, stable: bool) -> Self {
+        self.stable_sort = stable;
+        self
+    }
+
+    fn with_norm_col(mut self, column: &str) -> Self {
+        self.norm_col = column.to_string();
+        self
+    }
+
+    fn with_norm_method(mut self, method: &str) -> Self {
+        self.norm_method = method.to_string();
+        self
+    }
+
+    fn with_histogram(mut self, bins: Option<usize>) -> Self {
+        self.histogram_bins = bins;
+        self
+    }
+
+    fn with_to_rows(mut self, to_rows: bool) -> Self {
+        self.to_rows = to_rows;
+        self
+    }
+
+    fn with_impute_all(mut self, strategy: Option<String>) -> Self {
+        self.impute_all = strategy;
+        self
+    }
+
+    fn with_sample_method(mut self, method: SampleMethod) -> Self {
+        self.sample_method = method;
+        self
+    }
+
+    fn with_csv_format(mut self, delimiter: u8, has_header: bool) -> Self {
+        self.delimiter = delimiter;
+        self.has_header = has_header;
+        self
+    }
+
+    fn with_impute(mut self, impute: Vec<(String, String)>) -> Self {
+        self.impute = impute;
+        self
+    }
+
+    // The column-level steps of `run`, in run order. `--impute-all` targets depend on the data,
+    // so its imputation step lists no columns
+    fn step_columns(&self) -> Vec<StepColumns> {
+        let step = |name, reads: &[&String], creates: Vec<String>| StepColumns {
+            name,
+            reads: reads.iter().map(|c| c.to_string()).collect(),
+            creates,
+        };
+        let (impute_reads, impute_creates) = if self.impute_all.is_some() {
+            (Vec::new(), Vec::new())
+        } else {
+            self.impute
+                .iter()
+                .map(|(c, strategy)| (c, format!("{}_imputed_{}", c, strategy)))
+                .unzip()
+        };
+        let cast_reads: Vec<&String> = if self.auto_coerce {
+            Vec::new()
+        } else {
+            vec![&self.cast_col]
+        };
+        vec![
+            step("Type cast", &cast_reads, Vec::new()),
+            step("Imputation", &impute_reads, impute_creates),
+            step(
+                "Normalise",
+                &[&self.norm_col],
+                vec![format!("{}_normalized_{}", self.norm_col, self.norm_method)],
+            ),
+            step(
+                "Add column",
+                &[&self.norm_col],
+                vec![String::from("column_squared")],
+            ),
+            step("Filter", &[&self.norm_col], Vec::new()),
+            step("Sort", &[&self.norm_col], Vec::new()),
+            step("Column selection", &[&self.select_col], Vec::new()),
+            step("Column drop", &[&self.drop_col], Vec::new()),
+        ]
+    }
+
+    // Columns the CSV itself must provide: everything a step reads that no earlier step creates
+    fn required_columns(&self) -> Vec<String> {
+        let mut created: Vec<String> = Vec::new();
+        let mut required: Vec<String> = Vec::new();
+        for step in self.step_columns() {
+            for c in step.reads {
+                if !created.contains(&c) && !required.contains(&c) {
+                    required.push(c);
+                }
+            }
+            created.extend(step.creates);
+        }
+        required
+    }
+
+    // Checks the configured columns against the step order before any data is read:
+    // a step can't use a column that only a later step creates, and normalise/add column must
+    // not read a column whose nulls are being imputed (imputation fills a copy, so they'd still
+    // see the nulls and carry them into the filter and sort).
+    fn validate(&self) -> Result<(), String> {
+        let steps = self.step_columns();
+        for (i, step) in steps.iter().enumerate() {
+            for c in &step.reads {
+                if let Some(later) = steps[i + 1..].iter().find(|s| s.creates.contains(c)) {
+                    return Err(format!(
+                        "{} uses '{}', which is only created by the later {} step",
+                        step.name, c, later.name
+                    ));
+                }
+            }
+        }
+        if let Some((c, strategy)) = self.impute.iter().find(|(c, _)| *c == self.norm_col) {
+            return Err(format!(
+                "Normalise reads '{}' before its nulls are imputed (imputation writes '{}_imputed_{}'); \
+                 normalise the imputed column instead with --norm-col {}_imputed_{}",
+                c, c, strategy, c, strategy
+            ));
+        }
+        Ok(())
+    }
+
+    // Ordered description of the steps `run` would perform.
+    // Only the schema and per-column null counts are computed (lazily), to resolve the imputation target.
+    // Stdin can't be scanned lazily, so it is read in full.
+    fn plan(&self) -> Result<Vec<String>, PreprocError> {
+        let mut lf = if self.path == STDIN_PATH {
+            load_csv_opts(&self.path, self.delimiter, self.has_header)?.lazy()
+        } else {
+            if !std::path::Path::new(&self.path).exists() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!("{} not found", self.path),
+                )
+                .into());
+            }
+            LazyCsvReader::new(PlPath::new(&self.path))
+                .with_separator(self.delimiter)
+                .with_has_header(self.has_header)
+                .finish()?
+        };
+        require_columns(&*lf.collect_schema()?, &self.required_columns())?;
+        // Which columns auto-coercion casts depends on the values, so its plan shows the loaded types
+        let mut lf = if self.auto_coerce {
+            lf
+        } else {
+            lf.with_column(col(&self.cast_col).cast(DataType::Float64))
+        };
+        let schema = lf.collect_schema()?;
+        let null_counts = lf.null_count().collect()?;
+        let (num_cols, cat_cols) = get_schema_column_types(&schema);
+
+        let mut impute_target: Option<(String, usize)> = None;
+        for c in &num_cols {
+            let missing = null_counts
+                .column(c)?
+                .get(0)?
+                .extract::<usize>()
+                .unwrap_or(0);
+            if missing > impute_target.as_ref().map_or(0, |(_, m)| *m) {
+                impute_target = Some((c.clone(), missing));
+            }
+        }
+        let impute_step = if let Some(strategy) = &self.impute_all {
+            let targets: Vec<&String> = num_cols
+                .iter()
+                .filter(|c| {
+                    null_counts
+                        .column(c)
+                        .ok()
+                        .and_then(|s| s.get(0).ok())
+                        .and_then(|v| v.extract::<usize>())
+                        .unwrap_or(0)
+                        > 0
+                })
+                .collect();
+            if targets.is_empty() {
+                format!(
+                    "Impute all numeric ({}): no numeric column has missing values",
+                    strategy
+                )
+            } else {
+                let targets: Vec<String> = targets
+                    .iter()
+                    .map(|c| format!("{} -> {}_imputed_{}", c, c, strategy))
+                    .collect();
+                format!("Impute all numeric ({}): {}", strategy, targets.join(", "))
+            }
+        } else if !self.impute.is_empty() {
+            let targets: Vec<String> = self
+                .impute
+                .iter()
+                .map(|(c, strategy)| format!("{} ({}) -> {}_imputed_{}", c, strategy, c, strategy))
+                .collect();
+            format!("Impute: {}", targets.join(", "))
+        } else {
+            match impute_target {
+                Some((c, missing)) => format!(
+                    "Impute (mean, auto): {} ({} nulls) -> {}_imputed_mean",
+                    c, missing, c
+                ),
+                None => String::from("Impute (mean, auto): no numeric column has missing values"),
+            }
+        };
+
+        let sort_kind = if self.stable_sort {
+            "stable"
+        } else {
+            "unstable"
+        };
+        let mut steps = vec![format!(
+            "Load CSV: {} (delimiter {:?}, {})",
+            if self.path == STDIN_PATH {
+                "stdin"
+            } else {
+                &self.path
+            },
+            self.delimiter as char,
+            if self.has_header {
+                "header"
+            } else {
+                "no header"
+            }
+        )];
+        if self.to_rows {
+            steps.push(format!(
+                "DataFrame to rows: {} columns per row (serde_json map)",
+                schema.len()
+            ));
+        }
+        steps.extend([
+            if self.auto_coerce {
+                String::from(
+                    "Type cast: auto, String columns whose values all parse as numbers -> Float64",
+                )
+            } else {
+                format!("Type cast: {} -> Float64", self.cast_col)
+            },
+            format!(
+                "Column types: {} numeric, {} categorical",
+                num_cols.len(),
+                cat_cols.len()
+            ),
+        ]);
+        if let Some(max_one_hot) = self.max_one_hot {
+            steps.push(format!(
+                "Encode categorical: one-hot up to {} distinct values, label above",
+                max_one_hot
+            ));
+        }
+        steps.extend([
+            impute_step,
+            format!(
+                "Normalise ({}): {} -> {}_normalized_{}",
+                self.norm_method, self.norm_col, self.norm_col, self.norm_method
+            ),
+            format!(
+                "Verify normalisation: {}_normalized_{} ({}, warns if it fails)",
+                self.norm_col,
+                self.norm_method,
+                match self.norm_method.as_str() {
+                    "zscore" => "mean 0, sample std 1",
+                    _ => "values in [0, 1]",
+                }
+            ),
+            format!("Add column: column_squared = {}^2", self.norm_col),
+            String::from("Sanitize: NaN/Inf in float columns -> null"),
+        ]);
+        if let Some(bins) = self.histogram_bins {
+            steps.push(format!("Histogram: {} into {} bins", self.norm_col, bins));
+        }
+        steps.extend([
+            format!("Filter: {} > 0", self.norm_col),
+            format!("Sort ascending ({}): {}", sort_kind, self.norm_col),
+            format!(
+                "Search sorted: {} (exponential search for the middle row's value)",
+                self.norm_col
+            ),
+            format!("Sort descending ({}): {}", sort_kind, self.norm_col),
+            format!("Select columns: {}", self.select_col),
+            format!("Drop columns: {}", self.drop_col),
+            format!(
+                "Sample ({}): {}% of selected rows",
+                self.sample_method.name(),
+                self.sample_frac * 100.0
+            ),
+            match &self.output {
+                Some(path) => format!(
+                    "Output: write the frame after the column drop (every row, sorted descending) to {} (nulls as '{}')",
+                    path, self.null_value
+                ),
+                None => String::from("Output: none (results stay in memory)"),
+            },
+        ]);
+        Ok(steps
+            .into_iter()
+            .enumerate()
+            .map(|(i, step)| format!("{:>2}. {}", i + 1, step))
+            .collect())
+    }
+
+    fn run(&self) -> Result<(), PreprocError> {
+        let output = self.create_output()?;
+        self.run_once(None, output.as_ref()).map(|_| ())
+    }
+
+    // The --out file, created once before any pass so creating it is never part of a timed checkpoint
+    fn create_output(&self) -> Result<Option<File>, PreprocError> {
+        Ok(match &self.output {
+            Some(path) => Some(File::create(path)?),
+            None => None,
+        })
+    }
+
+    // One pass of the pipeline. `loaded` skips reading the CSV, so the "After Loading CSV"
+    // checkpoint then only measures the clone. `output` is emptied before the timer starts and
+    // then rewritten, so with --repeat it ends up holding the last pass's frame.
+    // Returns the loaded frame for the next pass and the (label, delta) of every checkpoint in order
+    fn run_once(
+        &self,
+        loaded: Option<&DataFrame>,
+        mut output: Option<&File>,
+    ) -> Result<(DataFrame, Vec<(String, Duration)>), PreprocError> {
+        //println!("Starting preprocessing pipeline...");
+        if let Some(file) = output.as_mut() {
+            file.set_len(0)?;
+            file.seek(SeekFrom::Start(0))?;
+        }
+
+        // Start timer
+        let mut timer = Timer::new().with_quiet(self.quiet);
+        let mut checkpoints = Vec::new();
+        let mut checkpoint = |label: &str| {
+            let (_, delta) = timer.process_info(label);
+            checkpoints.push((label.to_string(), delta));
+        };
+        checkpoint("Initial Process info");
+        let no_progress = |_: &str, _: f64| {};
+        let progress: Progress = self.progress.as_deref().unwrap_or(&no_progress);
+
+        //===================================================================================================================
+        let mut df = match loaded {
+            Some(df) => df.clone(),
+            None => load_csv_opts(&self.path, self.delimiter, self.has_header)?,
+        };
+        let loaded = df.clone();
+        require_columns(df.schema(), &self.required_columns())?;
+        let (rows, cols) = df.shape();
+        //println!("DataFrame shape: ({}, {})", rows, cols);
+        checkpoint("After Loading CSV");
+        // Quiet output is only the checkpoint lines
+        let report_size = |stage: &str, df: &DataFrame| {
+            if !self.quiet {
+                println!("DataFrame size {}: {:.2} MB", stage, estimated_size_mb(df));
+            }
+        };
+        report_size("after loading", &df);
+        if self.to_rows {
+            let rows: Vec<serde_json::Map<String, serde_json::Value>> = dataframe_to_rows(&df)?;
+            //println!("Converted {} rows of {} columns", rows.len(), cols);
+            checkpoint("DataFrame to Rows");
+        }
+        //===================================================================================================================
+        /*
+        for field in df.schema().iter_names_and_dtypes() {
+            //println!("{:#?}", field);
+        }
+        */
+
+        let mut df = if self.auto_coerce {
+            let mut df = df;
+            let coerced = auto_coerce_numeric_strings(&mut df)?;
+            //println!("Coerced to Float64: {:?}", coerced);
+            checkpoint("Auto Coercing Numeric Strings");
+            df
+        } else {
+            let df = df
+                .lazy()
+                .with_column(col(&self.cast_col).cast(DataType::Float64))
+                .collect()?;
+            checkpoint(&format!("Type Casting \'{}\'", self.cast_col));
+            df
+        };
+
+        /*
+        for field in df.schema().iter_names_and_dtypes() {
+            //println!("{:#?}", field);
+        }
+        */
+
+        //=======================================================================================================================
+
+        let (num_cols, cat_cols) = get_column_types(&df);
+        checkpoint("Getting column Types");
+        // //println!("Numerical Columns : {:#?}",num_cols);
+        // //println!("Categorical Columns : {:#?}",cat_cols);
+        if let Some(max_one_hot) = self.max_one_hot {
+            let encoded = encode_categorical(&mut df, &cat_cols, max_one_hot)?;
+            //println!("Encoded (column, distinct, encoding): {:?}", encoded);
+            checkpoint("Encode Categorical");
+        }
+
+        //=======================================================================================================================
+
+        // Explicit --impute / --impute-all targets skip detection
+        let impute = if let Some(strategy) = &self.impute_all {
+            let imputed = impute_all_numeric(&mut df, strategy, progress)?;
+            //println!("Imputed {:?} with {}", imputed, strategy);
+            Vec::new()
+        } else if self.impute.is_empty() {
+            let num_col =
+                column_most_missing(&df, &num_cols).ok_or(PreprocError::NoNumericColumn)?;
+
+            // For categorical column, handle None safely
+            let cat_col: Option<String> = column_most_missing(&df, &cat_cols);
+
+            // Print
+            //println!("Numerical column: {}", num_col);
+
+            if let Some(col) = cat_col {
+                //println!("Most missing categorical column: {}", col);
+            } else {
+                //println!("No categorical column found");
+            }
+            checkpoint("Detect most number of missing values");
+            vec![(num_col, String::from("mean"))]
+        } else {
+            self.impute.clone()
+        };
+
+        //=======================================================================================================================
+
+        for (i, (column, strategy)) in impute.iter().enumerate() {
+            impute_numerical(&mut df, column, strategy)?;
+            progress("Imputation", (i + 1) as f64 / impute.len() as f64);
+        }
+        checkpoint("Imputation");
+
+        //=======================================================================================================================
+        // let (df, cat_processed) = process_categorical(&df, &cat_col, "mode", true, true)?;
+
+        let norm_col = self.norm_col.clone();
+        normalize_column(&mut df, &norm_col, &self.norm_method, progress)?;
+        checkpoint("Normalise");
+        let normalized = format!("{}_normalized_{}", norm_col, self.norm_method);
+        if !verify_normalized(&df, &normalized, &self.norm_method)? {
+            eprintln!(
+                "Warning: '{}' is not a valid {} normalisation of '{}' (is the column constant?)",
+                normalized, self.norm_method, norm_col
+            );
+        }
+        checkpoint("Verify Normalisation");
+        //=======================================================================================================================
+        // let (df, num_as_int) = convert_type(&df, &num_imputed, DataType::Int64)?;
+
+        add_column(&mut df, "column_squared", &norm_col, |v| v * v)?;
+        checkpoint("Add Column");
+        report_size("after derived columns", &df);
+        let replaced = sanitize_numeric(&mut df, None)?;
+        //println!("Sanitized {} NaN/Inf values", replaced);
+        checkpoint("Sanitize");
+        if let Some(bins) = self.histogram_bins {
+            let hist = histogram(&df, &norm_col, bins)?;
+            //println!("Histogram of '{}':\n{}", norm_col, hist);
+            checkpoint("Histogram");
+        }
+        //=======================================================================================================================
+
+        filter_rows(&mut df, &norm_col)?;
+        checkpoint("Filter");
+        //=======================================================================================================================
+        let mut df = sort_column(&df, &norm_col, false, self.stable_sort)?;
+        checkpoint("Sort - Ascending");
+        let middle = df.column(&norm_col)?.f64()?.get(df.height() / 2);
+        if let Some(target) = middle {
+            let found = search_sorted_column(&df, &norm_col, target)?;
+            //println!("Exponential search for {} in '{}': {:?}", target, norm_col, found);
+        }
+        checkpoint("Search Sorted Column");
+        let mut df = sort_column(&df, &norm_col, true, self.stable_sort)?;
+        checkpoint("Sort - Descending");
+        //=======================================================================================================================
+        let drop_col = self.drop_col.clone();
+        let select_col = self.select_col.clone();
+        checkpoint("Creating Vars");
+
+        let df_selected = select_drop_columns(&df, Some(&[&select_col]), None)?;
+        checkpoint("Column Selection");
+
+        let mut df_drop = select_drop_columns(&df, None, Some(&[&drop_col]))?;
+        checkpoint("Column Drop");
+        //=======================================================================================================================
+        let df_sampled = self.sample_method.sample(&df_selected, self.sample_frac)?;
+        checkpoint("Sampling");
+        report_size("after sampling", &df_sampled);
+        // The full processed frame minus the dropped column, not the one-column sample: every
+        // derived column and row is there to compare against the Python side's output
+        if let Some(file) = output {
+            write_csv(
+                std::io::BufWriter::new(file),
+                &mut df_drop,
+                &self.null_value,
+            )?;
+            checkpoint("Save CSV");
+        }
+        // Quiet output is only the checkpoint lines
+        if !self.quiet {
+            println!(
+                "Polars threads: {}",
+                polars_core::POOL.current_num_threads()
+            );
+        }
+
+        // let mut rename_map = HashMap::new();
+        // rename_map.insert(num_norm.as_str(), "normalized_value");
+        // let df = rename_columns(&df, rename_map)?;
+        // let df_agg = aggregate_df(&df, &cat_processed, "normalized_value")?;
+        // //println!("✅ Aggregated result:\n{df_agg}");
+        // //println!("✅ Sampled subset:\n{df_sampled}");
+        Ok((loaded, checkpoints))
+    }
+
+    // Runs the pipeline `runs` times, reusing the frame loaded by the first run, which is
+    // discarded as warmup. Returns the median and stddev of each checkpoint's delta
+    fn run_repeated(&self, runs: usize) -> Result<Vec<CheckpointStats>, PreprocError> {
+        let output = self.create_output()?;
+        let (loaded, _) = self.run_once(None, output.as_ref())?;
+        let mut measured = Vec::with_capacity(runs - 1);
+        for _ in 1..runs {
+            measured.push(self.run_once(Some(&loaded), output.as_ref())?.1);
+        }
+        Ok(checkpoint_stats(&measured))
+    }
+}
+
+struct Args {
+    path: String,
+    dry_run: bool,
+    threads: Option<usize>,
+    scaling: bool,
+    impute: Vec<(String, String)>,
+    delimiter: u8,
+    has_header: bool,
+    sample: SampleMethod,
+    impute_all: Option<String>,
+    to_rows: bool,
+    histogram: Option<usize>,
+    norm_col: Option<String>,
+    norm_method: String,
+    stable_sort: bool,
+    repeat: usize,
+    encode: Option<usize>,
+    auto_coerce: bool,
+    quiet: bool,
+    out: Option<String>,
+    null_value: String,
+}
+
+// Single-byte field separator; `tab` and `\t` are accepted for tab-separated files
+fn parse_delimiter(value: &str) -> Result<u8> {
+    match value {
+        "tab" | "\\t" | "\t" => Ok(b'\t'),
+        v if v.len() == 1 => Ok(v.as_bytes()[0]),
+        _ => anyhow::bail!(
+            "--delimiter expects a single character or 'tab', got '{}'",
+            value
+        ),
+    }
+}
+
+// Methods normalize_column understands
+const NORM_METHODS: [&str; 2] = ["minmax", "zscore"];
+
+// Imputation strategies impute_numerical understands
+const IMPUTE_STRATEGIES: [&str; 3] = ["mean", "min", "zero"];
+
+// `col:strategy`, e.g. `BENE_DEATH_DT:mean`
+fn parse_impute_spec(spec: &str) -> Result<(String, String)> {
+    match spec.rsplit_once(':') {
+        Some((column, strategy)) if !column.is_empty() && IMPUTE_STRATEGIES.contains(&strategy) => {
+            Ok((column.to_string(), strategy.to_string()))
+        }
+        _ => anyhow::bail!(
+            "--impute expects col:strategy with strategy one of {}, got '{}'",
+            IMPUTE_STRATEGIES.join("/"),
+            spec
+        ),
+    }
+}
+
+// Usage: pre_proc [--dry-run] [--threads N] [--scaling] [--impute col:strategy]...
+//                 [--impute-all strategy] [--delimiter C] [--no-header]
+//                 [--sample random|systematic|head] [--to-rows]
+//                 [--histogram bins] [--norm-col column] [--norm-method minmax|zscore]
+//                 [--stable-sort]
+//                 [--repeat N] [--encode max_one_hot] [--quiet | -q]
+//                 [--out file.csv] [--null-value S] [path | -]
+// A path of `-` reads the CSV from stdin. --quiet (or QUIET=1) prints one `label,elapsed_ms,memory_mb`
+// line per checkpoint instead of the process block, and no progress bar. --out writes the processed
+// frame after the column drop (every row, normalised and derived columns included, sorted descending; not
+// the one-column sample) as CSV with nulls as --null-value (default: an empty field, like pandas)
+fn parse_args() -> Result<Args> {
+    let mut args = Args {
+        path: DEFAULT_PATH.to_string(),
+        dry_run: false,
+        threads: None,
+        scaling: false,
+        impute: Vec::new(),
+        delimiter: b',',
+        has_header: true,
+        sample: SampleMethod::Random,
+        impute_all: None,
+        to_rows: false,
+        histogram: None,
+        norm_col: None,
+        norm_method: String::from("minmax"),
+        stable_sort: false,
+        repeat: 1,
+        encode: None,
+        auto_coerce: false,
+        quiet: false,
+        out: None,
+        null_value: String::new(),
+    };
+    let mut it = std::env::args().skip(1);
+    while let Some(arg) = it.next() {
+        match arg.as_str() {
+            "--dry-run" => args.dry_run = true,
+            "--scaling" => args.scaling = true,
+            "--threads" => {
+                let n = it.next().and_then(|v| v.parse::<usize>().ok());
+                match n {
+                    Some(n) if n > 0 => args.threads = Some(n),
+                    _ => anyhow::bail!("--threads expects a positive integer"),
+                }
+            }
+            "--impute" => {
+                let spec = it.next().unwrap_or_default();
+                args.impute.push(parse_impute_spec(&spec)?);
+            }
+            "--impute-all" => {
+                let strategy = it.next().unwrap_or_default();
+                if !IMPUTE_STRATEGIES.contains(&strategy.as_str()) {
+                    anyhow::bail!(
+                        "--impute-all expects one of {}, got '{}'",
+                        IMPUTE_STRATEGIES.join("/"),
+                        strategy
+                    );
+                }
+                args.impute_all = Some(strategy);
+            }
+            "--delimiter" => args.delimiter = parse_delimiter(&it.next().unwrap_or_default())?,
+            "--no-header" => args.has_header = false,
+            "--to-rows" => args.to_rows = true,
+            "--stable-sort" => args.stable_sort = true,
+            "--auto-coerce" => args.auto_coerce = true,
+            "--quiet" | "-q" => args.quiet = true,
+            "--out" => match it.next() {
+                Some(path) if !path.is_empty() => args.out = Some(path),
+                _ => anyhow::bail!("--out expects a file path"),
+            },
+            "--null-value" => match it.next() {
+                Some(value) => args.null_value = value,
+                None => anyhow::bail!("--null-value expects the text to write for nulls"),
+            },
+            "--norm-col" => match it.next() {
+                Some(column) if !column.is_empty() => args.norm_col = Some(column),
+                _ => anyhow::bail!("--norm-col expects a column name"),
+            },
+            "--norm-method" => {
+                let method = it.next().unwrap_or_default();
+                if !NORM_METHODS.contains(&method.as_str()) {
+                    anyhow::bail!(
+                        "--norm-method expects one of {}, got '{}'",
+                        NORM_METHODS.join("/"),
+                        method
+                    );
+                }
+                args.norm_method = method;
+            }
+            "--encode" => {
+                let n = it.next().and_then(|v| v.parse::<usize>().ok());
+                match n {
+                    Some(n) => args.encode = Some(n),
+                    _ => anyhow::bail!(
+                        "--encode expects the largest distinct-value count to one-hot encode"
+                    ),
+                }
+            }
+            "--repeat" => {
+                let n = it.next().and_then(|v| v.parse::<usize>().ok());
+                match n {
+                    Some(n) if n >= 2 => args.repeat = n,
+                    _ => anyhow::bail!(
+                        "--repeat expects a run count of at least 2 (the first is warmup)"
+                    ),
+                }
+            }
+            "--histogram" => {
+                let bins = it.next().and_then(|v| v.parse::<usize>().ok());
+                match bins {
+                    Some(bins) if bins > 0 => args.histogram = Some(bins),
+                    _ => anyhow::bail!("--histogram expects a positive bin count"),
+                }
+            }
+            "--sample" => {
+                let method = it.next().unwrap_or_default();
+                args.sample = SampleMethod::parse(&method).ok_or_else(|| {
+                    anyhow::anyhow!(
+                        "--sample expects random, systematic or head, got '{}'",
+                        method
+                    )
+                })?;
+            }
+            flag if flag.starts_with("--") => anyhow::bail!("unknown flag {}", flag),
+            path => args.path = path.to_string(),
+        }
+    }
+    if args.impute_all.is_some() && !args.impute.is_empty() {
+        anyhow::bail!("--impute and --impute-all can't be combined");
+    }
+    if args.scaling && args.path == STDIN_PATH {
+        anyhow::bail!(
+            "--scaling re-reads the input in every run, so it needs a file path, not stdin"
+        );
+    }
+    if args.scaling && args.repeat > 1 {
+        anyhow::bail!("--repeat and --scaling can't be combined");
+    }
+    Ok(args)
+}
+
+// Runs the full pipeline in a child process at 1, 2 and 4 Polars threads and reports the speedup.
+// Separate processes are needed because the Polars thread pool is fixed once initialised.
+fn run_scaling(args: &Args) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    let mut baseline = None;
+    println!("threads,elapsed_ms,speedup");
+    for threads in [1, 2, 4] {
+        let start = Instant::now();
+        let mut cmd = std::process::Command::new(&exe);
+        cmd.args(["--threads", &threads.to_string()]);
+        for (column, strategy) in &args.impute {
+            cmd.args(["--impute", &format!("{}:{}", column, strategy)]);
+        }
+        cmd.args(["--delimiter", &(args.delimiter as char).to_string()]);
+        if !args.has_header {
+            cmd.arg("--no-header");
+        }
+        cmd.args(["--sample", args.sample.name()]);
+        if let Some(strategy) = &args.impute_all {
+            cmd.args(["--impute-all", strategy]);
+        }
+        if args.to_rows {
+            cmd.arg("--to-rows");
+        }
+        if let Some(bins) = args.histogram {
+            cmd.args(["--histogram", &bins.to_string()]);
+        }
+        if let Some(column) = &args.norm_col {
+            cmd.args(["--norm-col", column]);
+        }
+        cmd.args(["--norm-method", &args.norm_method]);
+        if args.stable_sort {
+            cmd.arg("--stable-sort");
+        }
+        if let Some(n) = args.encode {
+            cmd.args(["--encode", &n.to_string()]);
+        }
+        if args.auto_coerce {
+            cmd.arg("--auto-coerce");
+        }
+        if args.quiet {
+            cmd.arg("--quiet");
+        }
+        if let Some(path) = &args.out {
+            cmd.args(["--out", path, "--null-value", &args.null_value]);
+        }
+        let status = cmd
+            .arg(&args.path)
+            .stdout(std::process::Stdio::null())
+            .status()?;
+        if !status.success() {
+            anyhow::bail!("pipeline run with {} threads failed", threads);
+        }
+        let elapsed = start.elapsed().as_secs_f64() * 1000.0;
+        let base = *baseline.get_or_insert(elapsed);
+        println!("{},{:.2},{:.2}x", threads, elapsed, base / elapsed);
+    }
     Ok(())
 }
 
 fn main() -> Result<()> {
-    let path = r"C:\Users\pm018586\OneDrive - Zelis Healthcare\Documents\Presentations\Data Preprocessing Python VS Rust\Datasets\176541_DE1_0_2008_Beneficiary_Summary_File_Sample_1\DE1_0_2008_Beneficiary_Summary_File_Sample_1.csv";
-    full_preprocessing_pipeline(path)?;
+    let args = parse_args()?;
+    if let Some(n) = args.threads {
+        // SAFETY: no other threads exist yet; Polars reads this when its pool is first used
+        unsafe { std::env::set_var("POLARS_MAX_THREADS", n.to_string()) };
+    }
+    if args.scaling {
+        return run_scaling(&args);
+    }
+
+    let mut pipeline = Pipeline::new(&args.path)
+        .with_impute(args.impute.clone())
+        .with_csv_format(args.delimiter, args.has_header)
+        .with_sample_method(args.sample)
+        .with_impute_all(args.impute_all.clone())
+        .with_to_rows(args.to_rows)
+        .with_histogram(args.histogram)
+        .with_norm_method(&args.norm_method)
+        .with_stable_sort(args.stable_sort)
+        .with_encoding(args.encode)
+        .with_auto_coerce(args.auto_coerce)
+        .with_output(args.out.clone(), &args.null_value)
+        .with_quiet(args.quiet || bench_utils::quiet_from_env());
+    if pipeline.quiet {
+        pipeline = pipeline.with_progress(None);
+    }
+    if let Some(column) = &args.norm_col {
+        pipeline = pipeline.with_norm_col(column);
+    }
+    pipeline.validate().map_err(anyhow::Error::msg)?;
+    if args.dry_run {
+        for step in pipeline.plan()? {
+            println!("{}", step);
+        }
+        return Ok(());
+    }
+    if args.repeat > 1 {
+        let stats = pipeline.run_repeated(args.repeat)?;
+        println!(
+            "Repeat: {} runs, first discarded as warmup, later runs reuse the loaded DataFrame",
+            args.repeat
+        );
+        println!("checkpoint,runs,median_ms,stddev_ms");
+        for s in &stats {
+            println!(
+                "{},{},{:.3},{:.3}",
+                s.label, s.runs, s.median_ms, s.stddev_ms
+            );
+        }
+        return Ok(());
+    }
+    pipeline.run()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame_with_nulls() -> DataFrame {
+        df!(
+            "id" => ["a", "b", "c"],
+            "value" => [Some(1.5), None, Some(3.0)],
+            "cat" => [None, Some("x"), Some("y")]
+        )
+        .unwrap()
+    }
+
+    fn csv_text(null_value: &str) -> String {
+        let mut out = Vec::new();
+        write_csv(&mut out, &mut frame_with_nulls(), null_value).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn write_csv_defaults_nulls_to_an_empty_field() {
+        assert_eq!(csv_text(""), "id,value,cat\na,1.5,\nb,,x\nc,3.0,y\n");
+    }
+
+    #[test]
+    fn write_csv_writes_the_null_value_for_every_null() {
+        assert_eq!(csv_text("NA"), "id,value,cat\na,1.5,NA\nb,NA,x\nc,3.0,y\n");
+    }
+
+    // Writes `contents` to a file in the temp dir, unique per process and test, and returns its path
+    fn temp_csv(name: &str, contents: &str) -> String {
+        let path =
+            std::env::temp_dir().join(format!("pre_proc_{}_{}.csv", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        path.to_string_lossy().into_owned()
+    }
+
+    const SMALL_CSV: &str = "id,death,reimb,county,stroke\n\
+                             1,,10.0,a,1\n\
+                             2,5,20.0,b,2\n\
+                             3,,30.0,a,1\n";
+
+    // The default pipeline pointed at SMALL_CSV's columns
+    fn small_pipeline(path: &str) -> Pipeline {
+        Pipeline {
+            cast_col: String::from("death"),
+            select_col: String::from("county"),
+            drop_col: String::from("stroke"),
+            ..Pipeline::new(path)
+                .with_norm_col("reimb")
+                .with_progress(None)
+        }
+    }
+
+    #[test]
+    fn plan_lists_the_steps_and_resolved_columns() {
+        let path = temp_csv("plan", SMALL_CSV);
+        let plan = small_pipeline(&path)
+            .with_output(Some(String::from("out.csv")), "NA")
+            .plan()
+            .unwrap();
+        let expected = [
+            format!(" 1. Load CSV: {} (delimiter ',', header)", path),
+            String::from(" 2. Type cast: death -> Float64"),
+            String::from(" 3. Column types: 4 numeric, 1 categorical"),
+            String::from(" 4. Impute (mean, auto): death (2 nulls) -> death_imputed_mean"),
+            String::from(" 5. Normalise (minmax): reimb -> reimb_normalized_minmax"),
+            String::from(
+                " 6. Verify normalisation: reimb_normalized_minmax (values in [0, 1], warns if it fails)",
+            ),
+            String::from(" 7. Add column: column_squared = reimb^2"),
+            String::from(" 8. Sanitize: NaN/Inf in float columns -> null"),
+            String::from(" 9. Filter: reimb > 0"),
+            String::from("10. Sort ascending (unstable): reimb"),
+            String::from(
+                "11. Search sorted: reimb (exponential search for the middle row's value)",
+            ),
+            String::from("12. Sort descending (unstable): reimb"),
+            String::from("13. Select columns: county"),
+            String::from("14. Drop columns: stroke"),
+            String::from("15. Sample (random): 10% of selected rows"),
+            String::from(
+                "16. Output: write the frame after the column drop (every row, sorted descending) to out.csv (nulls as 'NA')",
+            ),
+        ];
+        assert_eq!(plan, expected);
+    }
+
+    #[test]
+    fn plan_shows_optional_steps_only_when_enabled() {
+        let path = temp_csv("plan_optional", SMALL_CSV);
+        let plan = small_pipeline(&path)
+            .with_histogram(Some(4))
+            .with_encoding(Some(3))
+            .plan()
+            .unwrap();
+        assert!(plan.contains(&String::from(
+            " 4. Encode categorical: one-hot up to 3 distinct values, label above"
+        )));
+        assert!(plan.contains(&String::from("10. Histogram: reimb into 4 bins")));
+        assert_eq!(
+            plan.last().unwrap(),
+            "18. Output: none (results stay in memory)"
+        );
+    }
+
+    #[test]
+    fn search_sorted_column_finds_present_values_and_misses_absent_ones() {
+        let df = df!("v" => [Some(1.0), None, Some(3.0), Some(5.0), Some(7.0), Some(9.0)]).unwrap();
+        // Row indices count the null row
+        assert_eq!(search_sorted_column(&df, "v", 1.0).unwrap(), Some(0));
+        assert_eq!(search_sorted_column(&df, "v", 3.0).unwrap(), Some(2));
+        assert_eq!(search_sorted_column(&df, "v", 9.0).unwrap(), Some(5));
+        for absent in [0.0, 4.0, 10.0] {
+            assert_eq!(search_sorted_column(&df, "v", absent).unwrap(), None);
+        }
+    }
+
+    #[test]
+    fn search_sorted_column_handles_integer_and_empty_columns() {
+        let df = df!("n" => [2i64, 4, 6, 8], "empty" => [None::<f64>, None, None, None]).unwrap();
+        assert_eq!(search_sorted_column(&df, "n", 6.0).unwrap(), Some(2));
+        assert_eq!(search_sorted_column(&df, "empty", 1.0).unwrap(), None);
+    }
+
+    #[test]
+    fn search_sorted_column_rejects_an_unsorted_column() {
+        let df = df!("v" => [1.0, 3.0, 2.0]).unwrap();
+        let err = search_sorted_column(&df, "v", 2.0).unwrap_err();
+        assert!(err.to_string().contains("not sorted ascending"), "{}", err);
+    }
+
+    #[test]
+    fn sanitize_numeric_replaces_nan_and_inf_and_counts_them() {
+        let mut df = df!(
+            "x" => [1.0, f64::NAN, f64::INFINITY, f64::NEG_INFINITY],
+            "y" => [Some(f64::NAN), None, Some(2.0), Some(3.0)],
+            "n" => [1i64, 2, 3, 4]
+        )
+        .unwrap();
+        assert_eq!(sanitize_numeric(&mut df, Some(0.0)).unwrap(), 4);
+        let x: Vec<Option<f64>> = df.column("x").unwrap().f64().unwrap().iter().collect();
+        assert_eq!(x, [Some(1.0), Some(0.0), Some(0.0), Some(0.0)]);
+        // Existing nulls stay null
+        let y: Vec<Option<f64>> = df.column("y").unwrap().f64().unwrap().iter().collect();
+        assert_eq!(y, [Some(0.0), None, Some(2.0), Some(3.0)]);
+        assert_eq!(sanitize_numeric(&mut df, Some(0.0)).unwrap(), 0);
+    }
+
+    #[test]
+    fn sanitize_numeric_writes_nulls_and_keeps_float32() {
+        let mut df = df!("f" => [1.0f32, f32::NAN, f32::INFINITY]).unwrap();
+        assert_eq!(sanitize_numeric(&mut df, None).unwrap(), 2);
+        let f = df.column("f").unwrap();
+        assert_eq!(f.dtype(), &DataType::Float32);
+        assert_eq!(f.null_count(), 2);
+    }
+
+    #[test]
+    fn missing_file_is_an_io_error() {
+        let path = std::env::temp_dir().join("pre_proc_no_such_file.csv");
+        let path = path.to_str().unwrap();
+        match load_csv(path) {
+            Err(PreprocError::Io(e)) => assert_eq!(e.kind(), std::io::ErrorKind::NotFound),
+            other => panic!("expected Io(NotFound), got {:?}", other),
+        }
+        assert!(matches!(
+            small_pipeline(path).plan(),
+            Err(PreprocError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn missing_column_is_reported_by_name() {
+        let path = temp_csv("missing_column", SMALL_CSV);
+        let pipeline = small_pipeline(&path).with_norm_col("MEDREIMB_CAR");
+        match pipeline.plan() {
+            Err(PreprocError::ColumnMissing(c)) => assert_eq!(c, "MEDREIMB_CAR"),
+            other => panic!("expected ColumnMissing, got {:?}", other),
+        }
+        let err = pipeline.with_quiet(true).run().unwrap_err();
+        assert_eq!(err.to_string(), "expected column 'MEDREIMB_CAR' not found");
+    }
+
+    #[test]
+    fn no_column_with_nulls_is_no_numeric_column() {
+        let path = temp_csv("no_nulls", "id,death,reimb,county,stroke\n1,2,10.0,a,1\n");
+        assert!(matches!(
+            small_pipeline(&path).with_quiet(true).run(),
+            Err(PreprocError::NoNumericColumn)
+        ));
+    }
+
+    const TWO_NULL_COLUMNS_CSV: &str = "id,death,reimb,county,stroke,extra\n\
+                                        1,,10.0,a,1,4.0\n\
+                                        2,4,20.0,b,2,\n\
+                                        3,,30.0,a,1,8.0\n\
+                                        4,8,40.0,c,2,2.0\n";
+
+    // Runs `pipeline` with --out and reads the written frame back, rows in id order
+    fn run_to_frame(pipeline: Pipeline, name: &str) -> DataFrame {
+        let out = temp_csv(name, "");
+        pipeline
+            .with_quiet(true)
+            .with_output(Some(out.clone()), "")
+            .run()
+            .unwrap();
+        sort_column(&load_csv(&out).unwrap(), "id", false, true).unwrap()
+    }
+
+    fn f64_values(df: &DataFrame, column: &str) -> Vec<Option<f64>> {
+        let s = df.column(column).unwrap().cast(&DataType::Float64).unwrap();
+        s.f64().unwrap().iter().collect()
+    }
+
+    #[test]
+    fn explicit_impute_targets_each_use_their_strategy() {
+        let path = temp_csv("impute_explicit", TWO_NULL_COLUMNS_CSV);
+        let pipeline = small_pipeline(&path).with_impute(vec![
+            (String::from("death"), String::from("min")),
+            (String::from("extra"), String::from("zero")),
+        ]);
+        let df = run_to_frame(pipeline, "impute_explicit_out");
+        assert_eq!(
+            f64_values(&df, "death_imputed_min"),
+            [Some(4.0), Some(4.0), Some(4.0), Some(8.0)]
+        );
+        assert_eq!(
+            f64_values(&df, "extra_imputed_zero"),
+            [Some(4.0), Some(0.0), Some(8.0), Some(2.0)]
+        );
+        assert!(df.column("death_imputed_mean").is_err());
+    }
+
+    #[test]
+    fn without_targets_the_most_missing_column_is_imputed_with_the_mean() {
+        let path = temp_csv("impute_auto", TWO_NULL_COLUMNS_CSV);
+        let df = run_to_frame(small_pipeline(&path), "impute_auto_out");
+        assert_eq!(
+            f64_values(&df, "death_imputed_mean"),
+            [Some(6.0), Some(4.0), Some(6.0), Some(8.0)]
+        );
+        assert!(df.column("extra_imputed_mean").is_err());
+    }
+
+    #[test]
+    fn load_csv_opts_reads_a_tab_separated_file() {
+        let path = temp_csv("tab", "name\tscore\nann\t1.5\nbob\t2.5\n");
+        let df = load_csv_opts(&path, b'\t', true).unwrap();
+        assert_eq!(df.get_column_names(), ["name", "score"]);
+        assert_eq!(f64_values(&df, "score"), [Some(1.5), Some(2.5)]);
+    }
+
+    #[test]
+    fn load_csv_opts_names_headerless_columns() {
+        let path = temp_csv("headerless", "ann|1\nbob|2\n");
+        let df = load_csv_opts(&path, b'|', false).unwrap();
+        assert_eq!(df.get_column_names(), ["column_1", "column_2"]);
+        assert_eq!(df.height(), 2);
+    }
+
+    fn row_ids(df: &DataFrame) -> Vec<i64> {
+        df.column("row")
+            .unwrap()
+            .i64()
+            .unwrap()
+            .into_no_null_iter()
+            .collect()
+    }
+
+    #[test]
+    fn systematic_sample_takes_evenly_spaced_rows() {
+        let df = df!("row" => (0..20i64).collect::<Vec<_>>()).unwrap();
+        assert_eq!(
+            row_ids(&sample_df_systematic(&df, 0.25).unwrap()),
+            [0, 4, 8, 12, 16]
+        );
+        assert_eq!(
+            row_ids(&sample_df_systematic(&df, 0.3).unwrap()),
+            [0, 3, 6, 9, 12, 15, 18]
+        );
+        // Same rows every time, no RNG involved
+        assert_eq!(
+            row_ids(&SampleMethod::Systematic.sample(&df, 0.25).unwrap()),
+            [0, 4, 8, 12, 16]
+        );
+    }
+
+    #[test]
+    fn head_sample_returns_the_first_rows() {
+        let df = df!("row" => (0..20i64).collect::<Vec<_>>()).unwrap();
+        assert_eq!(row_ids(&sample_df_head(&df, 0.2).unwrap()), [0, 1, 2, 3]);
+        assert_eq!(
+            row_ids(&SampleMethod::Head.sample(&df, 1.0).unwrap()).len(),
+            20
+        );
+    }
+
+    #[test]
+    fn impute_all_numeric_imputes_every_numeric_column_with_nulls() {
+        let mut df = df!(
+            "a" => [Some(1.0), None, Some(3.0)],
+            "full" => [1.0, 2.0, 3.0],
+            "b" => [None, Some(10i64), Some(20)],
+            "s" => [None, Some("x"), Some("y")]
+        )
+        .unwrap();
+        let imputed = impute_all_numeric(&mut df, "mean", &|_, _| {}).unwrap();
+        assert_eq!(imputed, ["a", "b"]);
+        assert_eq!(
+            f64_values(&df, "a_imputed_mean"),
+            [Some(1.0), Some(2.0), Some(3.0)]
+        );
+        assert_eq!(df.column("b_imputed_mean").unwrap().null_count(), 0);
+        assert!(df.column("full_imputed_mean").is_err());
+        assert!(df.column("s_imputed_mean").is_err());
+    }
+
+    #[derive(serde::Deserialize, Debug, PartialEq)]
+    struct TestRow {
+        id: i64,
+        name: String,
+        price: Option<f64>,
+    }
+
+    #[test]
+    fn dataframe_to_rows_builds_one_struct_per_row() {
+        let df = df!(
+            "id" => [1i64, 2],
+            "name" => ["pen", "ink"],
+            "price" => [Some(1.5), None],
+            "unused" => [true, false]
+        )
+        .unwrap();
+        let rows: Vec<TestRow> = dataframe_to_rows(&df).unwrap();
+        assert_eq!(
+            rows,
+            [
+                TestRow {
+                    id: 1,
+                    name: String::from("pen"),
+                    price: Some(1.5)
+                },
+                TestRow {
+                    id: 2,
+                    name: String::from("ink"),
+                    price: None
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn dataframe_to_rows_reports_the_failing_row() {
+        let df = df!("id" => [Some(1i64), None], "name" => ["pen", "ink"]).unwrap();
+        let err = dataframe_to_rows::<TestRow>(&df).unwrap_err();
+        assert!(err.to_string().contains("row 1"), "{}", err);
+    }
+
+    #[test]
+    fn histogram_of_a_uniform_column_has_roughly_equal_bins() {
+        let mut values: Vec<Option<f64>> = (0..=100).map(|v| Some(v as f64)).collect();
+        values.extend([None, Some(f64::NAN)]);
+        let df = df!("v" => values).unwrap();
+        let hist = histogram(&df, "v", 10).unwrap();
+        assert_eq!(
+            f64_values(&hist, "bin_start"),
+            (0..10).map(|i| Some(i as f64 * 10.0)).collect::<Vec<_>>()
+        );
+        let counts: Vec<u32> = hist
+            .column("count")
+            .unwrap()
+            .u32()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        // The last bin also holds the maximum; the null and NaN are left out
+        assert_eq!(counts, [10, 10, 10, 10, 10, 10, 10, 10, 10, 11]);
+    }
+
+    #[test]
+    fn histogram_edge_cases() {
+        let df = df!("c" => [5.0, 5.0], "none" => [None::<f64>, None]).unwrap();
+        let hist = histogram(&df, "c", 3).unwrap();
+        let counts: Vec<u32> = hist
+            .column("count")
+            .unwrap()
+            .u32()
+            .unwrap()
+            .into_no_null_iter()
+            .collect();
+        assert_eq!(counts, [2, 0, 0]);
+        assert_eq!(histogram(&df, "none", 3).unwrap().height(), 0);
+        assert!(histogram(&df, "c", 0).is_err());
+    }
+
+    #[test]
+    fn validate_accepts_normalising_the_imputed_column() {
+        let pipeline = small_pipeline("unused.csv")
+            .with_impute(vec![(String::from("reimb"), String::from("mean"))])
+            .with_norm_col("reimb_imputed_mean");
+        assert_eq!(pipeline.validate(), Ok(()));
+        assert_eq!(small_pipeline("unused.csv").validate(), Ok(()));
+    }
+
+    #[test]
+    fn validate_rejects_normalising_before_imputing() {
+        let err = small_pipeline("unused.csv")
+            .with_impute(vec![(String::from("reimb"), String::from("mean"))])
+            .validate()
+            .unwrap_err();
+        assert!(
+            err.starts_with("Normalise reads 'reimb' before its nulls are imputed"),
+            "{}",
+            err
+        );
+        assert!(err.contains("--norm-col reimb_imputed_mean"), "{}", err);
+    }
+
+    #[test]
+    fn validate_rejects_a_column_a_later_step_creates() {
+        let pipeline = Pipeline {
+            select_col: String::from("column_squared"),
+            ..small_pipeline("unused.csv")
+        };
+        assert_eq!(pipeline.validate(), Ok(()));
+        let pipeline = small_pipeline("unused.csv").with_norm_col("column_squared");
+        assert_eq!(
+            pipeline.validate().unwrap_err(),
+            "Normalise uses 'column_squared', which is only created by the later Add column step"
+        );
+    }
+
+    #[test]
+    fn descending_sort_reverses_the_ascending_order() {
+        let df = df!("k" => [3.0, 1.0, 4.0, 1.5, 9.0, 2.6]).unwrap();
+        let ascending = f64_values(&sort_column(&df, "k", false, false).unwrap(), "k");
+        let mut descending = f64_values(&sort_column(&df, "k", true, false).unwrap(), "k");
+        assert!(ascending.windows(2).all(|w| w[0] <= w[1]));
+        descending.reverse();
+        assert_eq!(ascending, descending);
+    }
+
+    #[test]
+    fn stable_sort_keeps_tied_rows_in_order() {
+        let n = 10_000i64;
+        let df = df!(
+            "k" => (0..n).map(|i| i % 3).collect::<Vec<_>>(),
+            "row" => (0..n).collect::<Vec<_>>()
+        )
+        .unwrap();
+        for descending in [false, true] {
+            let sorted = sort_column(&df, "k", descending, true).unwrap();
+            let keys: Vec<i64> = sorted
+                .column("k")
+                .unwrap()
+                .i64()
+                .unwrap()
+                .into_no_null_iter()
+                .collect();
+            let rows = row_ids(&sorted);
+            for i in 1..rows.len() {
+                if keys[i] == keys[i - 1] {
+                    assert!(rows[i] > rows[i - 1], "tie order lost at {}", i);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn checkpoint_stats_are_the_median_and_stddev_per_checkpoint() {
+        let ms = |v: u64| Duration::from_millis(v);
+        let runs: Vec<Vec<(String, Duration)>> = [[10, 1], [30, 1], [20, 1], [40, 1]]
+            .iter()
+            .map(|run| {
+                vec![
+                    (String::from("Load"), ms(run[0])),
+                    (String::from("Sort"), ms(run[1])),
+                ]
+            })
+            .collect();
+        let stats = checkpoint_stats(&runs);
+        assert_eq!(stats.len(), 2);
+        assert_eq!((stats[0].label.as_str(), stats[0].runs), ("Load", 4));
+        assert!((stats[0].median_ms - 25.0).abs() < 1e-9);
+        assert!((stats[0].stddev_ms - 125f64.sqrt()).abs() < 1e-9);
+        assert_eq!(stats[1].label, "Sort");
+        assert!((stats[1].median_ms - 1.0).abs() < 1e-9);
+        assert!(stats[1].stddev_ms.abs() < 1e-9);
+        assert!(checkpoint_stats(&[]).is_empty());
+    }
+
+    #[test]
+    fn run_repeated_reports_every_checkpoint_of_the_measured_runs() {
+        let path = temp_csv("repeat", SMALL_CSV);
+        let pipeline = small_pipeline(&path).with_quiet(true);
+        let (_, checkpoints) = pipeline.run_once(None, None).unwrap();
+        let stats = pipeline.run_repeated(3).unwrap();
+        let labels: Vec<&str> = stats.iter().map(|s| s.label.as_str()).collect();
+        let expected: Vec<&str> = checkpoints.iter().map(|(l, _)| l.as_str()).collect();
+        assert_eq!(labels, expected);
+        for s in &stats {
+            // The first of the 3 runs is warmup
+            assert_eq!(s.runs, 2);
+            assert!(s.median_ms >= 0.0 && s.stddev_ms >= 0.0, "{}", s.label);
+        }
+    }
+
+    #[test]
+    fn read_csv_reads_from_any_reader() {
+        let input = std::io::Cursor::new("id;score\n1;2.5\n2;\n");
+        let df = read_csv(input, b';', true).unwrap();
+        assert_eq!(df.get_column_names(), ["id", "score"]);
+        assert_eq!(f64_values(&df, "score"), [Some(2.5), None]);
+    }
+
+    #[test]
+    fn read_csv_rejects_empty_input() {
+        for input in ["", " \n\t\n"] {
+            assert!(matches!(
+                read_csv(std::io::Cursor::new(input), b',', true),
+                Err(PreprocError::EmptyInput)
+            ));
+        }
+        assert_eq!(PreprocError::EmptyInput.to_string(), "no CSV data on stdin");
+    }
+
+    #[test]
+    fn cardinality_counts_distinct_non_null_values_highest_first() {
+        let df = df!(
+            "two" => [Some("a"), Some("b"), None, Some("a")],
+            "three" => ["x", "y", "z", "x"],
+            "one" => [None, Some("k"), Some("k"), None]
+        )
+        .unwrap();
+        let columns = [
+            String::from("two"),
+            String::from("three"),
+            String::from("one"),
+        ];
+        assert_eq!(
+            cardinality(&df, &columns).unwrap(),
+            [
+                (String::from("three"), 3),
+                (String::from("two"), 2),
+                (String::from("one"), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn encode_categorical_picks_one_hot_or_label_by_cardinality() {
+        let mut df = df!(
+            "low" => [Some("a"), Some("b"), None],
+            "high" => ["x", "z", "y"]
+        )
+        .unwrap();
+        let columns = [String::from("low"), String::from("high")];
+        let encoded = encode_categorical(&mut df, &columns, 2).unwrap();
+        assert_eq!(
+            encoded,
+            [
+                (String::from("high"), 3, "label"),
+                (String::from("low"), 2, "one-hot")
+            ]
+        );
+        let labels: Vec<Option<u32>> = df
+            .column("high_label")
+            .unwrap()
+            .u32()
+            .unwrap()
+            .iter()
+            .collect();
+        assert_eq!(labels, [Some(0), Some(2), Some(1)]);
+        let a: Vec<Option<bool>> = df.column("low_a").unwrap().bool().unwrap().iter().collect();
+        assert_eq!(a, [Some(true), Some(false), Some(false)]);
+        assert!(df.column("low_b").is_ok());
+    }
+
+    #[test]
+    fn auto_coerce_casts_only_all_numeric_string_columns() {
+        let mut df = df!(
+            "numeric" => [Some("1"), Some(" 2.5 "), None, Some("-3e2")],
+            "mixed" => [Some("1"), Some("2"), Some("n/a"), None],
+            "empty" => [None::<&str>, None, None, None],
+            "already" => [1i64, 2, 3, 4]
+        )
+        .unwrap();
+        assert_eq!(
+            auto_coerce_numeric_strings(&mut df).unwrap(),
+            ["numeric", "empty"]
+        );
+        assert_eq!(
+            f64_values(&df, "numeric"),
+            [Some(1.0), Some(2.5), None, Some(-300.0)]
+        );
+        assert_eq!(df.column("empty").unwrap().dtype(), &DataType::Float64);
+        assert_eq!(df.column("mixed").unwrap().dtype(), &DataType::String);
+        assert_eq!(df.column("already").unwrap().dtype(), &DataType::Int64);
+    }
+
+    #[test]
+    fn apply_chunked_reports_progress_after_every_chunk() {
+        let calls = std::cell::RefCell::new(Vec::new());
+        let record =
+            |step: &str, fraction: f64| calls.borrow_mut().push((step.to_string(), fraction));
+        let len = PROGRESS_CHUNK_ROWS * 2 + 10;
+        let ca = Float64Chunked::from_iter_options(
+            "v".into(),
+            (0..len).map(|i| if i == 3 { None } else { Some(i as f64) }),
+        );
+        let out = apply_chunked(&ca, "Normalise", &record, |v| v * 2.0).unwrap();
+        assert_eq!(out.len(), len);
+        assert_eq!(out.get(3), None);
+        assert_eq!(out.get(len - 1), Some((len - 1) as f64 * 2.0));
+
+        let calls = calls.into_inner();
+        assert_eq!(calls.len(), 3);
+        assert!(calls.iter().all(|(step, _)| step == "Normalise"));
+        assert!(calls.windows(2).all(|w| w[0].1 < w[1].1));
+        assert_eq!(calls.last().unwrap().1, 1.0);
+    }
+
+    #[test]
+    fn normalize_column_reports_completion_even_when_empty() {
+        let calls = std::cell::Cell::new(0);
+        let count = |_: &str, fraction: f64| {
+            assert!((0.0..=1.0).contains(&fraction));
+            calls.set(calls.get() + 1);
+        };
+        let mut df = df!("v" => [1.0, 2.0, 3.0]).unwrap();
+        normalize_column(&mut df, "v", "zscore", &count).unwrap();
+        assert_eq!(calls.get(), 1);
+        let empty = Float64Chunked::full_null("v".into(), 0);
+        apply_chunked(&empty, "Normalise", &count, |v| v).unwrap();
+        assert_eq!(calls.get(), 2);
+    }
+
+    #[test]
+    fn verify_normalized_passes_correct_normalisations() {
+        let mut df = df!("v" => [Some(2.0), None, Some(4.0), Some(9.0), Some(1.0e6)]).unwrap();
+        for method in NORM_METHODS {
+            normalize_column(&mut df, "v", method, &|_, _| {}).unwrap();
+            let column = format!("v_normalized_{}", method);
+            assert!(
+                verify_normalized(&df, &column, method).unwrap(),
+                "{}",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn verify_normalized_fails_a_constant_column() {
+        let mut df = df!("c" => [3.0, 3.0, 3.0]).unwrap();
+        for method in NORM_METHODS {
+            normalize_column(&mut df, "c", method, &|_, _| {}).unwrap();
+            let column = format!("c_normalized_{}", method);
+            assert!(
+                !verify_normalized(&df, &column, method).unwrap(),
+                "{}",
+                method
+            );
+        }
+    }
+
+    #[test]
+    fn verify_normalized_checks_the_bounds() {
+        let df = df!(
+            "out_of_range" => [0.0, 0.5, 1.2],
+            "empty" => [None::<f64>, None, None]
+        )
+        .unwrap();
+        assert!(!verify_normalized(&df, "out_of_range", "minmax").unwrap());
+        assert!(!verify_normalized(&df, "out_of_range", "zscore").unwrap());
+        assert!(!verify_normalized(&df, "empty", "minmax").unwrap());
+        assert!(verify_normalized(&df, "out_of_range", "log").is_err());
+    }
+
+    #[test]
+    fn adding_a_derived_column_increases_the_estimated_size() {
+        let mut df = df!("v" => (0..10_000).map(|i| i as f64).collect::<Vec<_>>()).unwrap();
+        let before = estimated_size_mb(&df);
+        assert!(before > 0.0);
+        add_column(&mut df, "squared", "v", |v| v * v).unwrap();
+        let after = estimated_size_mb(&df);
+        assert!(after > before, "{} <= {}", after, before);
+        // 10,000 f64 values are 80,000 bytes
+        assert!((after - before - 80_000.0 / (1024.0 * 1024.0)).abs() < 0.01);
+    }
+}
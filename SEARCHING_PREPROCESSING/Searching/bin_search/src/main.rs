@@ -1,74 +1,50 @@
 #![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
 
 const ARRAY_SIZE : usize = 10_00_000;
-const MIN : i32 = 1000;
-const MAX : i32 = 10000;
 
 use std::{time::Instant, fs::File};
-use sysinfo::{Pid, System};
+use bench_utils::Timer;
 use std::cmp::Ordering;
  
-//Random value generation
-use rand::Rng;
+use search_core::persist::load_or_generate;
+use search_core::probes::run_probes;
+use search_core::keyed::{binary_search, binary_search_counted, max_comparisons, worst_case_hit, Found};
+use search_core::{cold_warm, BenchArgs, Dtype, SearchKey, SortOrder};
 
-fn process_info(sys: &mut System, pid: Pid,print_log: String, start_time: &Instant) {
-    println!("============={}================",print_log);
-    sys.refresh_all();
-    if let Some(process) = sys.process(pid) {
-        println!("Process name: {}", process.name());
-        println!("Executable path: {:?}", process.exe());
-        println!("Memory usage: {:.2} MB", process.memory() as f64 / 1024.0 / 1024.0);
-    } else {
-        println!("Process not found!");
-    }
-    println!("Till -- {} : {:#?}",print_log,start_time.elapsed());
-}
-
-
-fn generate_sorted_random_array(n: usize) -> Vec<i32> {
-    let mut rng = rand::thread_rng();
-    let mut arr: Vec<i32> = (0..n).map(|_| rng.gen_range(MIN..MAX)).collect();
-    arr.sort();
-    arr
-}
-
-//Perform binary search and return the index of the element found else give None
-fn binary_search(arr: &[i32], target: i32) -> Option<usize> {
-    let (mut low, mut high) = (0, arr.len() as isize - 1);
-    while low <= high {
-        let mid = (low + high) / 2;
-        match arr[mid as usize].cmp(&target) {
-            Ordering::Equal => return Some(mid as usize),
-            Ordering::Less => low = mid + 1,
-            Ordering::Greater => high = mid - 1,
-        }
+fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
     }
-    None
 }
 
-fn main() {
+fn run<T: SearchKey>(args: &BenchArgs) {
     
     // Start timer
-    let start_time = Instant::now();
-
-    // Initialize system info
-    let mut sys = System::new_all();
-    sys.refresh_all();
-
-    // Get current process ID
-    let pid = sysinfo::get_current_pid().unwrap();
-    process_info(&mut sys, pid,String::from("Before Binary Search"),&start_time);
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before Binary Search");
+    println!("Element type : {}", T::NAME);
 
     //=====================================================================================================
-    let sorted_array = generate_sorted_random_array(ARRAY_SIZE);
+    let sorted_array = load_or_generate::<T>(ARRAY_SIZE, args.order, args.cache.as_deref(), args.unique).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    assert!(args.order.is_sorted(&sorted_array), "generated array is not in {:?} order", args.order);
     //println!("{:?}", sorted_array);
-    process_info(&mut sys, pid,String::from("Array Generation & Sort"),&start_time);
+    timer.process_info("Array Generation & Sort");
 
     let first = &sorted_array[0];
     let last = &sorted_array[ARRAY_SIZE - 1];
     let middle = &sorted_array[ARRAY_SIZE/2];
-    let el_les = 50;
-    let el_grt = 10006;
+    let el_les = T::below_range();
+    let el_grt = T::above_range();
     println!(
         "First : {} , Last : {} , Middle : {} , Element < MIN : {} , Element > MAX {}",
         first,
@@ -76,20 +52,50 @@ fn main() {
         middle,
         el_les,
         el_grt);
+    //Cold vs warm timing of the first element probe, the warmup also leaves caches warm for the probes below
+    let (cold, warm) = cold_warm(&sorted_array, *first, args.warmup, |arr: &[T], target: T| binary_search(arr, target, args.order).found());
+    println!("Cold First Element : {:#?} , Warm First Element : {:#?} ({} warmup searches)", cold, warm, args.warmup);
+    timer.process_info("Warmup");
     //=====================================================================================================
-    println!("Bin Search First Element : {:#?}",binary_search(&sorted_array,*first).unwrap());
-    process_info(&mut sys, pid,String::from("First Element Search"),&start_time);
-    println!("Bin Search Last Element : {:#?}",binary_search(&sorted_array,*last).unwrap());
-    process_info(&mut sys, pid,String::from("Last Element Search"),&start_time);
-    println!("Bin Search Middle Element : {:#?}",binary_search(&sorted_array,*middle).unwrap());
-    process_info(&mut sys, pid,String::from("Middle Element Search"),&start_time);
+    println!("Bin Search First Element : {:#?}",binary_search(&sorted_array,*first,args.order).found().unwrap());
+    timer.process_info("First Element Search");
+    println!("Bin Search Last Element : {:#?}",binary_search(&sorted_array,*last,args.order).found().unwrap());
+    timer.process_info("Last Element Search");
+    println!("Bin Search Middle Element : {:#?}",binary_search(&sorted_array,*middle,args.order).found().unwrap());
+    timer.process_info("Middle Element Search");
 
     println!("=================================");
-    println!("Bin Search Element < {MIN} : {:#?}",binary_search(&sorted_array,el_les));
-    process_info(&mut sys, pid,String::from("Element < MIN Search"),&start_time);
+    println!("Bin Search Element < {} : {:?}",T::LOW,binary_search(&sorted_array,el_les,args.order));
+    timer.process_info("Element < MIN Search");
     
     println!("=================================");
-    println!("Bin Search Element > {MAX} : {:#?}",binary_search(&sorted_array,el_grt));
-    process_info(&mut sys, pid,String::from("Element > MAX Search"),&start_time);
+    println!("Bin Search Element > {} : {:?}",T::HIGH,binary_search(&sorted_array,el_grt,args.order));
+    timer.process_info("Element > MAX Search");
+
+    //Extra --probes / --random-probes targets, one CSV row each
+    if run_probes(args, &sorted_array, "binary", |arr: &[T], target: T| binary_search(arr, target, args.order).found()) {
+        timer.process_info("Extra Probes");
+    }
+
+    //--worst-case: comparison counts of the deepest paths against the floor(log2 n) + 1 bound
+    if args.worst_case {
+        let bound = max_comparisons(ARRAY_SIZE);
+        let (_, middle_count) = binary_search_counted(&sorted_array, *middle, args.order);
+        let (hit, hit_count) = worst_case_hit(&sorted_array, args.order).unwrap();
+        let (_, low_count) = binary_search_counted(&sorted_array, el_les, args.order);
+        let (_, high_count) = binary_search_counted(&sorted_array, el_grt, args.order);
+        println!("=================================");
+        println!("Comparison bound floor(log2 {}) + 1 : {}", ARRAY_SIZE, bound);
+        println!("Middle Element : {} comparisons", middle_count);
+        println!("Worst Hit : {} in {} comparisons", hit, hit_count);
+        println!("Miss < MIN : {} comparisons , Miss > MAX : {} comparisons", low_count, high_count);
+        //Missing past the side of the larger halves walks the full depth, so one of the misses must reach the bound
+        let at_bound = hit_count <= bound && low_count.max(high_count) == bound && low_count.min(high_count) <= bound;
+        println!("Worst case at bound : {}", at_bound);
+        timer.process_info("Worst Case Probes");
+        if !at_bound {
+            std::process::exit(2);
+        }
+    }
     
 }
\ No newline at end of file
@@ -0,0 +1,143 @@
+#![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
+
+const ARRAY_SIZE : usize = 10_00_000;
+const QUERIES : usize = 1_000;
+
+use std::collections::BTreeMap;
+use std::time::Instant;
+use bench_utils::Timer;
+
+//Random value generation
+use rand::Rng;
+
+use search_core::persist::load_or_generate;
+use search_core::keyed::count_in_range;
+use search_core::{BenchArgs, Dtype, SearchKey, SortOrder};
+
+//Value -> number of occurrences. A BTreeMap<T, ()> would keep one key per distinct value, and the default
+//i32 array holds each value about a hundred times, so the tree stores the multiplicity instead
+fn build_tree<T: SearchKey>(arr: &[T]) -> BTreeMap<T, usize> {
+    let mut tree = BTreeMap::new();
+    for &v in arr {
+        *tree.entry(v).or_insert(0) += 1;
+    }
+    tree
+}
+
+//Elements in [lo, hi]. BTreeMap::range panics when lo > hi, so a reversed range counts 0 up front
+fn count_in_tree<T: SearchKey>(tree: &BTreeMap<T, usize>, lo: T, hi: T) -> usize {
+    if lo > hi {
+        return 0;
+    }
+    tree.range(lo..=hi).map(|(_, &n)| n).sum()
+}
+
+//Same query mix as range_count: mostly ranges inside the generated values, 1 in 8 reversed (lo > hi)
+//and 1 in 8 entirely below or above the array
+fn generate_ranges<T: SearchKey>(n: usize) -> Vec<(T, T)> {
+    let mut rng = rand::thread_rng();
+    (0..n).map(|i| {
+        let a = rng.gen_range(T::LOW..T::HIGH);
+        let b = rng.gen_range(T::LOW..T::HIGH);
+        match i % 8 {
+            6 => (a.max(b), a.min(b)),
+            7 if i % 16 == 7 => (T::below_range(), T::below_range()),
+            7 => (T::above_range(), T::above_range()),
+            _ => (a.min(b), a.max(b)),
+        }
+    }).collect()
+}
+
+fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    if args.order == SortOrder::Descending {
+        eprintln!("btree_range only supports ascending arrays, ignoring --order desc");
+    }
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
+    }
+}
+
+fn run<T: SearchKey>(args: &BenchArgs) {
+
+    // Start timer
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before BTree Range");
+    println!("Element type : {}", T::NAME);
+
+    //=====================================================================================================
+    if args.probes.is_some() || args.random_probes.is_some() {
+        eprintln!("btree_range generates its own ranges, ignoring --probes/--random-probes");
+    }
+    let sorted_array = load_or_generate::<T>(ARRAY_SIZE, SortOrder::Ascending, args.cache.as_deref(), args.unique).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    timer.process_info("Array Generation & Sort");
+
+    let start = Instant::now();
+    let tree = build_tree(&sorted_array);
+    let build_time = start.elapsed();
+    println!("BTreeMap Build : {:#?} for {} elements ({} distinct keys)", build_time, ARRAY_SIZE, tree.len());
+    timer.process_info("BTreeMap Build");
+
+    let ranges = generate_ranges::<T>(QUERIES);
+    timer.process_info("Range Generation");
+    //=====================================================================================================
+    let start = Instant::now();
+    let bin_counts: Vec<usize> = ranges.iter().map(|&(lo, hi)| count_in_range(&sorted_array, lo, hi)).collect();
+    let bin_time = start.elapsed();
+    println!("Sorted Array Counts : {:#?} for {} ranges", bin_time, QUERIES);
+    timer.process_info("Sorted Array Counts");
+
+    let start = Instant::now();
+    let tree_counts: Vec<usize> = ranges.iter().map(|&(lo, hi)| count_in_tree(&tree, lo, hi)).collect();
+    let tree_time = start.elapsed();
+    println!("BTreeMap Counts : {:#?} for {} ranges", tree_time, QUERIES);
+    timer.process_info("BTreeMap Counts");
+    //=====================================================================================================
+    println!("=================================");
+    let mismatches = bin_counts.iter().zip(&tree_counts).filter(|(b, t)| b != t).count();
+    println!("Counts consistent : {} ({} mismatches)", mismatches == 0, mismatches);
+    let bin_per_query = bin_time.as_secs_f64() / QUERIES as f64;
+    let tree_per_query = tree_time.as_secs_f64() / QUERIES as f64;
+    println!("Per range : Sorted Array {:.1} ns , BTreeMap {:.1} ns , Ratio {:.1}x",
+        bin_per_query * 1e9, tree_per_query * 1e9, tree_per_query / bin_per_query);
+    //The tree walks every key inside the range while the array takes two binary searches, so building
+    //the tree only pays off when it is kept up to date through inserts the array would have to shift for
+    println!("Including build : Sorted Array {:#?} , BTreeMap {:#?}", bin_time, build_time + tree_time);
+    if mismatches > 0 {
+        std::process::exit(2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tree_count_matches_count_in_range() {
+        let arr: Vec<i32> = search_core::generate_sorted_random_array(5_000, SortOrder::Ascending);
+        let tree = build_tree(&arr);
+        assert_eq!(tree.values().sum::<usize>(), arr.len());
+        for (lo, hi) in generate_ranges::<i32>(2_000) {
+            assert_eq!(count_in_tree(&tree, lo, hi), count_in_range(&arr, lo, hi), "range {}..={}", lo, hi);
+        }
+    }
+
+    #[test]
+    fn reversed_and_outside_ranges_count_zero() {
+        let arr: Vec<u64> = search_core::generate_sorted_random_array(1_000, SortOrder::Ascending);
+        let tree = build_tree(&arr);
+        assert_eq!(count_in_tree(&tree, u64::HIGH, u64::LOW), 0);
+        assert_eq!(count_in_tree(&tree, u64::below_range(), u64::below_range()), 0);
+        assert_eq!(count_in_tree(&tree, u64::above_range(), u64::above_range()), 0);
+        assert_eq!(count_in_tree(&tree, arr[0], arr[arr.len() - 1]), arr.len());
+    }
+}
@@ -0,0 +1,143 @@
+#![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
+
+const ARRAY_SIZE : usize = 10_00_000;
+const QUERIES : usize = 10_00_000;
+
+use std::{time::Instant, fs::File};
+use std::collections::HashMap;
+use std::hash::Hash;
+use bench_utils::Timer;
+use std::cmp::Ordering;
+
+//Random value generation
+use rand::Rng;
+
+use search_core::persist::load_or_generate;
+use search_core::{ BenchArgs, Dtype, SearchKey, SortOrder};
+
+//Perform binary search and return the index of the element found else give None
+fn binary_search<T: SearchKey>(arr: &[T], target: T) -> Option<usize> {
+    let (mut low, mut high) = (0, arr.len() as isize - 1);
+    while low <= high {
+        let mid = (low + high) / 2;
+        match arr[mid as usize].cmp(&target) {
+            Ordering::Equal => return Some(mid as usize),
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid - 1,
+        }
+    }
+    None
+}
+
+//Value -> index of its first occurrence, the "build once" side of the tradeoff
+fn build_index<T: SearchKey + Hash>(arr: &[T]) -> HashMap<T, usize> {
+    let mut map = HashMap::with_capacity(arr.len());
+    for (i, &v) in arr.iter().enumerate() {
+        map.entry(v).or_insert(i);
+    }
+    map
+}
+
+//Query mix: 3 in 4 are values taken from the array, the rest sit below the generated range (misses)
+fn generate_queries<T: SearchKey>(arr: &[T], n: usize) -> Vec<T> {
+    let mut rng = rand::thread_rng();
+    (0..n).map(|i| if i % 4 == 3 { T::below_range() } else { arr[rng.gen_range(0..arr.len())] }).collect()
+}
+
+fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    if args.order == SortOrder::Descending {
+        eprintln!("hash_search only supports ascending arrays, ignoring --order desc");
+    }
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
+    }
+}
+
+fn run<T: SearchKey + Hash>(args: &BenchArgs) {
+    
+    // Start timer
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before HashMap vs Binary Search");
+    println!("Element type : {}", T::NAME);
+
+    //=====================================================================================================
+    if args.probes.is_some() || args.random_probes.is_some() {
+        eprintln!("hash_search generates its own queries, ignoring --probes/--random-probes");
+    }
+    let sorted_array = load_or_generate::<T>(ARRAY_SIZE, SortOrder::Ascending, args.cache.as_deref(), args.unique).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    timer.process_info("Array Generation & Sort");
+
+    let queries = generate_queries(&sorted_array, QUERIES);
+    timer.process_info("Query Generation");
+    //=====================================================================================================
+    let start = Instant::now();
+    let index = build_index(&sorted_array);
+    let build_time = start.elapsed();
+    println!("HashMap Build : {:#?} ({} distinct keys)", build_time, index.len());
+    timer.process_info("HashMap Build");
+
+    let start = Instant::now();
+    let map_hits: Vec<bool> = queries.iter().map(|q| index.contains_key(q)).collect();
+    let map_time = start.elapsed();
+    println!("HashMap Lookups : {:#?} for {} queries", map_time, QUERIES);
+    timer.process_info("HashMap Lookups");
+
+    let start = Instant::now();
+    let bin_hits: Vec<bool> = queries.iter().map(|&q| binary_search(&sorted_array, q).is_some()).collect();
+    let bin_time = start.elapsed();
+    println!("Binary Search Lookups : {:#?} for {} queries", bin_time, QUERIES);
+    timer.process_info("Binary Search Lookups");
+    //=====================================================================================================
+    println!("=================================");
+    println!("Membership consistent : {}", map_hits == bin_hits);
+    let map_per_query = map_time.as_secs_f64() / QUERIES as f64;
+    let bin_per_query = bin_time.as_secs_f64() / QUERIES as f64;
+    println!("Per query : HashMap {:.1} ns , Binary Search {:.1} ns", map_per_query * 1e9, bin_per_query * 1e9);
+    //Building pays off once the per-query saving has covered the build cost
+    if bin_per_query > map_per_query {
+        let break_even = (build_time.as_secs_f64() / (bin_per_query - map_per_query)).ceil();
+        println!("Break-even : HashMap wins after {} queries", break_even);
+    } else {
+        println!("Break-even : never, binary search is as fast per query");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn index_lookup_agrees_with_binary_search() {
+        let arr: Vec<i32> = search_core::generate_sorted_random_array(5_000, SortOrder::Ascending);
+        let index = build_index(&arr);
+        for q in generate_queries(&arr, 2_000) {
+            let hashed = index.get(&q).copied();
+            let searched = binary_search(&arr, q);
+            assert_eq!(hashed.is_some(), searched.is_some(), "target {}", q);
+            if let (Some(h), Some(b)) = (hashed, searched) {
+                assert_eq!(arr[h], q);
+                assert_eq!(arr[b], q);
+                //The index keeps the first occurrence, so nothing before it may hold the target
+                assert!(h <= b && (h == 0 || arr[h - 1] < q));
+            }
+        }
+    }
+
+    #[test]
+    fn below_range_queries_miss() {
+        let arr: Vec<i64> = search_core::generate_sorted_random_array(1_000, SortOrder::Ascending);
+        let index = build_index(&arr);
+        assert_eq!(index.get(&i64::below_range()), None);
+        assert_eq!(binary_search(&arr, i64::below_range()), None);
+    }
+}
@@ -1,86 +1,158 @@
 #![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
 
 const ARRAY_SIZE : usize = 10_00_000;
-const MIN : i32 = 1000;
-const MAX : i32 = 10000;
 
 use std::{time::Instant, fs::File};
-use sysinfo::{Pid, System};
+use std::fs::OpenOptions;
+use std::hint::black_box;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+use bench_utils::Timer;
 use std::cmp::Ordering;
- 
+
 //Random value generation
 use rand::Rng;
+ 
+use search_core::persist::load_or_generate;
+use search_core::probes::run_probes;
+use search_core::keyed::{binary_search, interpolation_search, Found};
+use search_core::{cold_warm, generate_sorted_random_array, BenchArgs, Dtype, SearchKey, SortOrder};
 
-fn process_info(sys: &mut System, pid: Pid,print_log: String, start_time: &Instant) {
-    println!("============={}================",print_log);
-    sys.refresh_all();
-    if let Some(process) = sys.process(pid) {
-        println!("Process name: {}", process.name());
-        println!("Executable path: {:?}", process.exe());
-        println!("Memory usage: {:.2} MB", process.memory() as f64 / 1024.0 / 1024.0);
-    } else {
-        println!("Process not found!");
+//Crossover mode: array sizes 2^4 .. 2^CROSSOVER_MAX_POW, each searched for CROSSOVER_QUERIES random present values
+const CROSSOVER_MIN_POW : u32 = 4;
+const CROSSOVER_MAX_POW : u32 = 22;
+//Debug builds scan the whole array with keyed::check_sorted at the start of every search (see keyed::SearchError),
+//which makes each search O(n), so they stop early
+const CROSSOVER_MAX_POW_DEBUG : u32 = 12;
+const CROSSOVER_QUERIES : usize = 10_000;
+const CROSSOVER_HEADER : &str = "dtype,order,array_size,queries,binary_ns,interpolation_ns,faster";
+
+//Mean time per search of binary and interpolation search at one array size
+struct CrossoverRow {
+    size: usize,
+    binary_ns: f64,
+    interpolation_ns: f64,
+}
+
+impl CrossoverRow {
+    fn faster(&self) -> &'static str {
+        if self.interpolation_ns < self.binary_ns { "interpolation" } else { "binary" }
     }
-    println!("Till -- {} : {:#?}",print_log,start_time.elapsed());
 }
 
+//Mean ns per search over `queries`, after one untimed pass so both algorithms start with warm caches
+fn mean_search_ns<T: SearchKey>(arr: &[T], queries: &[T], search: impl Fn(&[T], T) -> Option<usize>) -> f64 {
+    for &q in queries {
+        black_box(search(arr, black_box(q)));
+    }
+    let start = Instant::now();
+    for &q in queries {
+        black_box(search(arr, black_box(q)));
+    }
+    start.elapsed().as_nanos() as f64 / queries.len() as f64
+}
 
-fn generate_sorted_random_array(n: usize) -> Vec<i32> {
+//Times both searches on uniform random arrays of doubling size, the same queries for both at each size
+fn crossover_rows<T: SearchKey>(order: SortOrder, max_pow: u32) -> Vec<CrossoverRow> {
     let mut rng = rand::thread_rng();
-    let mut arr: Vec<i32> = (0..n).map(|_| rng.gen_range(MIN..MAX)).collect();
-    arr.sort();
-    arr
+    (CROSSOVER_MIN_POW..=max_pow).map(|pow| {
+        let size = 1usize << pow;
+        let arr = generate_sorted_random_array::<T>(size, order);
+        let queries: Vec<T> = (0..CROSSOVER_QUERIES).map(|_| arr[rng.gen_range(0..size)]).collect();
+        let binary_ns = mean_search_ns(&arr, &queries, |a: &[T], t: T| binary_search(a, t, order).found());
+        let interpolation_ns = mean_search_ns(&arr, &queries, |a: &[T], t: T| interpolation_search(a, t, order).found());
+        CrossoverRow { size, binary_ns, interpolation_ns }
+    }).collect()
 }
 
-fn interpolation_search(arr: &[i32], target: i32) -> Option<usize> {
-    let mut low = 0usize;
-    let mut high = arr.len() - 1;
+//Smallest size from which the winner at the largest size wins at every larger size too, so a noisy
+//flip at one size doesn't count. None when that algorithm was already ahead at the smallest size
+fn crossover_point(rows: &[CrossoverRow]) -> Option<usize> {
+    let winner = rows.last()?.faster();
+    let from = rows.iter().rposition(|r| r.faster() != winner)? + 1;
+    Some(rows[from].size)
+}
 
-    while low <= high && arr[low] <= target && arr[high] >= target {
-        if arr[high] == arr[low] {
-            if arr[low] == target {
-                return Some(low);
-            } else {
-                return None;
-            }
-        }
-        let pos = low + (((high - low) as f64 * 
-            (target - arr[low]) as f64 / (arr[high] - arr[low]) as f64) as usize);
-        if arr[pos] == target {
-            return Some(pos);
-        } else if arr[pos] < target {
-            low = pos + 1;
-        } else {
-            if pos == 0 { break; }
-            high = pos - 1;
-        }
+fn append_crossover_rows<T: SearchKey>(path: &str, order: SortOrder, rows: &[CrossoverRow]) -> std::io::Result<()> {
+    let exists = Path::new(path).exists();
+    let mut w = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+    if !exists {
+        writeln!(w, "{}", CROSSOVER_HEADER)?;
+    }
+    let order = match order {
+        SortOrder::Ascending => "asc",
+        SortOrder::Descending => "desc",
+    };
+    for r in rows {
+        writeln!(w, "{},{},{},{},{:.1},{:.1},{}", T::NAME, order, r.size, CROSSOVER_QUERIES, r.binary_ns, r.interpolation_ns, r.faster())?;
+    }
+    w.flush()
+}
+
+//--crossover: prints the per-size table and the crossover size, and appends the table to `path`
+fn run_crossover<T: SearchKey>(args: &BenchArgs, path: &str) {
+    let max_pow = if cfg!(debug_assertions) {
+        eprintln!("Debug build: sizes stop at 2^{} and every timed search includes an O(n) sortedness scan, use --release", CROSSOVER_MAX_POW_DEBUG);
+        CROSSOVER_MAX_POW_DEBUG
+    } else {
+        CROSSOVER_MAX_POW
+    };
+    println!("Element type : {} , {} queries per size", T::NAME, CROSSOVER_QUERIES);
+    println!("{:>12}{:>14}{:>20}{:>16}", "array_size", "binary_ns", "interpolation_ns", "faster");
+    let rows = crossover_rows::<T>(args.order, max_pow);
+    for r in &rows {
+        println!("{:>12}{:>14.1}{:>20.1}{:>16}", r.size, r.binary_ns, r.interpolation_ns, r.faster());
+    }
+    println!("=================================");
+    let winner = rows.last().map(|r| r.faster()).unwrap_or("binary");
+    match crossover_point(&rows) {
+        Some(size) => println!("Crossover : {} search is faster from {} elements on", winner, size),
+        None => println!("Crossover : none, {} search is faster at every size", winner),
+    }
+    match append_crossover_rows::<T>(path, args.order, &rows) {
+        Ok(()) => println!("Appended {} crossover rows to {}", rows.len(), path),
+        Err(e) => eprintln!("Could not write {} ({})", path, e),
     }
-    None
 }
 
 fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
+    }
+}
+
+fn run<T: SearchKey>(args: &BenchArgs) {
+    if let Some(path) = &args.crossover {
+        run_crossover::<T>(args, path);
+        return;
+    }
     
     // Start timer
-    let start_time = Instant::now();
-
-    // Initialize system info
-    let mut sys = System::new_all();
-    sys.refresh_all();
-
-    // Get current process ID
-    let pid = sysinfo::get_current_pid().unwrap();
-    process_info(&mut sys, pid,String::from("Before Interpolation Search"),&start_time);
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before Interpolation Search");
+    println!("Element type : {}", T::NAME);
 
     //=====================================================================================================
-    let sorted_array = generate_sorted_random_array(ARRAY_SIZE);
+    let sorted_array = load_or_generate::<T>(ARRAY_SIZE, args.order, args.cache.as_deref(), args.unique).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    assert!(args.order.is_sorted(&sorted_array), "generated array is not in {:?} order", args.order);
     //println!("{:?}", sorted_array);
-    process_info(&mut sys, pid,String::from("Array Generation & Sort"),&start_time);
+    timer.process_info("Array Generation & Sort");
 
     let first = &sorted_array[0];
     let last = &sorted_array[ARRAY_SIZE - 1];
     let middle = &sorted_array[ARRAY_SIZE/2];
-    let el_les = 50;
-    let el_grt = 10006;
+    let el_les = T::below_range();
+    let el_grt = T::above_range();
     println!(
         "First : {} , Last : {} , Middle : {} , Element < MIN : {} , Element > MAX {}",
         first,
@@ -88,20 +160,58 @@ fn main() {
         middle,
         el_les,
         el_grt);
+    //Cold vs warm timing of the first element probe, the warmup also leaves caches warm for the probes below
+    let (cold, warm) = cold_warm(&sorted_array, *first, args.warmup, |arr: &[T], target: T| interpolation_search(arr, target, args.order).found());
+    println!("Cold First Element : {:#?} , Warm First Element : {:#?} ({} warmup searches)", cold, warm, args.warmup);
+    timer.process_info("Warmup");
     //=====================================================================================================
-    println!("Interpolation Search First Element : {:#?}",interpolation_search(&sorted_array,*first).unwrap());
-    process_info(&mut sys, pid,String::from("First Element Search"),&start_time);
-    println!("Interpolation Search Last Element : {:#?}",interpolation_search(&sorted_array,*last).unwrap());
-    process_info(&mut sys, pid,String::from("Last Element Search"),&start_time);
-    println!("Interpolation Search Middle Element : {:#?}",interpolation_search(&sorted_array,*middle).unwrap());
-    process_info(&mut sys, pid,String::from("Middle Element Search"),&start_time);
+    println!("Interpolation Search First Element : {:#?}",interpolation_search(&sorted_array,*first,args.order).found().unwrap());
+    timer.process_info("First Element Search");
+    println!("Interpolation Search Last Element : {:#?}",interpolation_search(&sorted_array,*last,args.order).found().unwrap());
+    timer.process_info("Last Element Search");
+    println!("Interpolation Search Middle Element : {:#?}",interpolation_search(&sorted_array,*middle,args.order).found().unwrap());
+    timer.process_info("Middle Element Search");
 
     println!("=================================");
-    println!("Interpolation Search Element < {MIN} : {:#?}",interpolation_search(&sorted_array,el_les));
-    process_info(&mut sys, pid,String::from("Element < MIN Search"),&start_time);
+    println!("Interpolation Search Element < {} : {:?}",T::LOW,interpolation_search(&sorted_array,el_les,args.order));
+    timer.process_info("Element < MIN Search");
     
     println!("=================================");
-    println!("Interpolation Search Element > {MAX} : {:#?}",interpolation_search(&sorted_array,el_grt));
-    process_info(&mut sys, pid,String::from("Element > MAX Search"),&start_time);
+    println!("Interpolation Search Element > {} : {:?}",T::HIGH,interpolation_search(&sorted_array,el_grt,args.order));
+    timer.process_info("Element > MAX Search");
+
+    //Extra --probes / --random-probes targets, one CSV row each
+    if run_probes(args, &sorted_array, "interpolation", |arr: &[T], target: T| interpolation_search(arr, target, args.order).found()) {
+        timer.process_info("Extra Probes");
+    }
     
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossover_runs_over_small_sizes() {
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            let rows = crossover_rows::<i64>(order, CROSSOVER_MIN_POW + 3);
+            let sizes: Vec<usize> = rows.iter().map(|r| r.size).collect();
+            assert_eq!(sizes, vec![16, 32, 64, 128]);
+            for r in &rows {
+                assert!(r.binary_ns.is_finite() && r.binary_ns > 0.0);
+                assert!(r.interpolation_ns.is_finite() && r.interpolation_ns > 0.0);
+            }
+            if let Some(size) = crossover_point(&rows) {
+                assert!(sizes.contains(&size));
+            }
+        }
+    }
+
+    #[test]
+    fn crossover_point_ignores_an_early_flip() {
+        let row = |size, binary_ns, interpolation_ns| CrossoverRow { size, binary_ns, interpolation_ns };
+        let rows = [row(16, 1.0, 2.0), row(32, 2.0, 1.0), row(64, 2.0, 3.0), row(128, 3.0, 2.0), row(256, 4.0, 2.0)];
+        assert_eq!(crossover_point(&rows), Some(128));
+        assert_eq!(crossover_point(&rows[3..]), None);
+        assert_eq!(crossover_point(&[]), None);
+    }
+}
@@ -1,77 +1,112 @@
 #![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
 
 const ARRAY_SIZE : usize = 10_00_000;
-const MIN : i32 = 1000;
-const MAX : i32 = 10000;
+//Adaptive vs fixed step comparison: queries per data shape, and value clusters of the clustered array
+const QUERIES : usize = 10_000;
+const CLUSTERS : usize = 8;
 
 use std::{time::Instant, fs::File};
-use sysinfo::{Pid, System};
+use bench_utils::Timer;
 use std::cmp::Ordering;
  
+use search_core::persist::load_or_generate;
+use search_core::probes::run_probes;
+use search_core::keyed::{adaptive_jump_search, binary_search, jump_search, Found};
+use search_core::{cold_warm, BenchArgs, Dtype, SearchKey, SortOrder};
+
 //Random value generation
 use rand::Rng;
 
-fn process_info(sys: &mut System, pid: Pid,print_log: String, start_time: &Instant) {
-    println!("============={}================",print_log);
-    sys.refresh_all();
-    if let Some(process) = sys.process(pid) {
-        println!("Process name: {}", process.name());
-        println!("Executable path: {:?}", process.exe());
-        println!("Memory usage: {:.2} MB", process.memory() as f64 / 1024.0 / 1024.0);
-    } else {
-        println!("Process not found!");
+
+fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
     }
-    println!("Till -- {} : {:#?}",print_log,start_time.elapsed());
 }
 
-
-fn generate_sorted_random_array(n: usize) -> Vec<i32> {
+//n values packed into CLUSTERS narrow bands (each 1/1000 of the value range wide) at random spots,
+//the shape where an interpolated position estimate is furthest off
+fn clustered_array<T: SearchKey>(n: usize, order: SortOrder) -> Vec<T> {
     let mut rng = rand::thread_rng();
-    let mut arr: Vec<i32> = (0..n).map(|_| rng.gen_range(MIN..MAX)).collect();
+    let (low, high) = (T::LOW.widen(), T::HIGH.widen());
+    let width = ((high - low) / 1000).max(1);
+    let centers: Vec<i128> = (0..CLUSTERS).map(|_| rng.gen_range(low..high - width)).collect();
+    let mut arr: Vec<T> = (0..n).map(|i| T::narrow(centers[i % CLUSTERS] + rng.gen_range(0..width))).collect();
     arr.sort();
+    if order == SortOrder::Descending {
+        arr.reverse();
+    }
     arr
 }
 
+//Half the queries are elements of arr, half random values from the generated range (mostly misses on
+//clustered data), plus the edge targets: both ends and one value beyond each
+fn comparison_queries<T: SearchKey>(arr: &[T]) -> Vec<T> {
+    let mut rng = rand::thread_rng();
+    let mut queries: Vec<T> = (0..QUERIES).map(|i| {
+        if i % 2 == 0 { arr[rng.gen_range(0..arr.len())] } else { rng.gen_range(T::LOW..T::HIGH) }
+    }).collect();
+    queries.extend([arr[0], arr[arr.len() - 1], T::below_range(), T::above_range()]);
+    queries
+}
 
-fn jump_search(arr: &[i32], target: i32) -> Option<usize> {
-    let n = arr.len();
-    let step = (n as f64).sqrt() as usize;
-    let mut prev = 0;
-    while prev < n && arr[prev.min(n - 1)] < target {
-        prev += step;
-    }
-    let start = prev.saturating_sub(step);
-    for i in start..prev.min(n) {
-        if arr[i] == target {
-            return Some(i as usize);
+//Times fixed-step and adaptive jump search over the same queries, and checks every adaptive answer:
+//present exactly when binary search finds the target, and pointing at an element equal to it.
+//Returns whether all answers held
+fn compare_adaptive<T: SearchKey>(label: &str, arr: &[T], order: SortOrder) -> bool {
+    let queries = comparison_queries(arr);
+    let start = Instant::now();
+    let fixed: Vec<Option<usize>> = queries.iter().map(|&q| jump_search(arr, q, order).found()).collect();
+    let fixed_time = start.elapsed();
+    let start = Instant::now();
+    let adaptive: Vec<Option<usize>> = queries.iter().map(|&q| adaptive_jump_search(arr, q, order).found()).collect();
+    let adaptive_time = start.elapsed();
+    println!(
+        "{} : Fixed Step {:#?} , Adaptive {:#?} for {} queries ({:.2}x)",
+        label, fixed_time, adaptive_time, queries.len(),
+        fixed_time.as_secs_f64() / adaptive_time.as_secs_f64().max(f64::MIN_POSITIVE));
+    let mut consistent = true;
+    for (i, &q) in queries.iter().enumerate() {
+        let expected = binary_search(arr, q, order).found().is_some();
+        let ok = adaptive[i].is_some() == expected
+            && fixed[i].is_some() == expected
+            && adaptive[i].is_none_or(|idx| arr[idx] == q);
+        if !ok {
+            eprintln!("{} : mismatch for {} : fixed {:?} , adaptive {:?} , present {}", label, q, fixed[i], adaptive[i], expected);
+            consistent = false;
         }
     }
-    None
+    consistent
 }
 
-fn main() {
+fn run<T: SearchKey>(args: &BenchArgs) {
     
     // Start timer
-    let start_time = Instant::now();
-
-    // Initialize system info
-    let mut sys = System::new_all();
-    sys.refresh_all();
-
-    // Get current process ID
-    let pid = sysinfo::get_current_pid().unwrap();
-    process_info(&mut sys, pid,String::from("Before Jumpary Search"),&start_time);
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before Jumpary Search");
+    println!("Element type : {}", T::NAME);
 
     //=====================================================================================================
-    let sorted_array = generate_sorted_random_array(ARRAY_SIZE);
+    let sorted_array = load_or_generate::<T>(ARRAY_SIZE, args.order, args.cache.as_deref(), args.unique).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    assert!(args.order.is_sorted(&sorted_array), "generated array is not in {:?} order", args.order);
     //println!("{:?}", sorted_array);
-    process_info(&mut sys, pid,String::from("Array Generation & Sort"),&start_time);
+    timer.process_info("Array Generation & Sort");
 
     let first = &sorted_array[0];
     let last = &sorted_array[ARRAY_SIZE - 1];
     let middle = &sorted_array[ARRAY_SIZE/2];
-    let el_les = 50;
-    let el_grt = 10006;
+    let el_les = T::below_range();
+    let el_grt = T::above_range();
     println!(
         "First : {} , Last : {} , Middle : {} , Element < MIN : {} , Element > MAX {}",
         first,
@@ -79,20 +114,42 @@ fn main() {
         middle,
         el_les,
         el_grt);
+    //Cold vs warm timing of the first element probe, the warmup also leaves caches warm for the probes below
+    let (cold, warm) = cold_warm(&sorted_array, *first, args.warmup, |arr: &[T], target: T| jump_search(arr, target, args.order).found());
+    println!("Cold First Element : {:#?} , Warm First Element : {:#?} ({} warmup searches)", cold, warm, args.warmup);
+    timer.process_info("Warmup");
     //=====================================================================================================
-    println!("Jump Search First Element : {:?}",jump_search(&sorted_array,*first));
-    process_info(&mut sys, pid,String::from("First Element Search"),&start_time);
-    println!("Jump Search Last Element : {:?}",jump_search(&sorted_array,*last));
-    process_info(&mut sys, pid,String::from("Last Element Search"),&start_time);
-    println!("Jump Search Middle Element : {:?}",jump_search(&sorted_array,*middle));
-    process_info(&mut sys, pid,String::from("Middle Element Search"),&start_time);
+    println!("Jump Search First Element : {:?}",jump_search(&sorted_array,*first,args.order).found());
+    timer.process_info("First Element Search");
+    println!("Jump Search Last Element : {:?}",jump_search(&sorted_array,*last,args.order).found());
+    timer.process_info("Last Element Search");
+    println!("Jump Search Middle Element : {:?}",jump_search(&sorted_array,*middle,args.order).found());
+    timer.process_info("Middle Element Search");
 
     println!("=================================");
-    println!("Jump Search Element < {MIN} : {:#?}",jump_search(&sorted_array,el_les));
-    process_info(&mut sys, pid,String::from("Element < MIN Search"),&start_time);
+    println!("Jump Search Element < {} : {:?}",T::LOW,jump_search(&sorted_array,el_les,args.order));
+    timer.process_info("Element < MIN Search");
     
     println!("=================================");
-    println!("Jump Search Element > {MAX} : {:#?}",jump_search(&sorted_array,el_grt));
-    process_info(&mut sys, pid,String::from("Element > MAX Search"),&start_time);
+    println!("Jump Search Element > {} : {:?}",T::HIGH,jump_search(&sorted_array,el_grt,args.order));
+    timer.process_info("Element > MAX Search");
+
+    //Extra --probes / --random-probes targets, one CSV row each
+    if run_probes(args, &sorted_array, "jump", |arr: &[T], target: T| jump_search(arr, target, args.order).found()) {
+        timer.process_info("Extra Probes");
+    }
+
+    //Adaptive stride on the uniform array above and on clustered data of the same size
+    println!("=================================");
+    let clustered = clustered_array::<T>(ARRAY_SIZE, args.order);
+    timer.process_info("Clustered Array Generation");
+    let uniform_ok = compare_adaptive("Uniform Data", &sorted_array, args.order);
+    let clustered_ok = compare_adaptive("Clustered Data", &clustered, args.order);
+    timer.process_info("Adaptive vs Fixed Step");
+    if !(uniform_ok && clustered_ok) {
+        eprintln!("Adaptive jump search disagrees with binary search");
+        std::process::exit(2);
+    }
+    println!("Adaptive results consistent");
     
 }
\ No newline at end of file
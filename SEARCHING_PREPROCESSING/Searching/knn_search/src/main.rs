@@ -0,0 +1,172 @@
+#![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
+
+const ARRAY_SIZE : usize = 10_00_000;
+const K : usize = 10;
+
+use std::{time::Instant, fs::File};
+use bench_utils::Timer;
+ 
+use search_core::persist::load_or_generate;
+use search_core::keyed::lower_bound;
+use search_core::{cold_warm, BenchArgs, Dtype, SearchKey, SortOrder};
+
+//Indices of the k values closest to target, nearest first
+//Binary search for the insertion point, then grow a window outward with two pointers taking the closer side each step
+//Equal distances prefer the lower index; k larger than the array returns every index
+fn k_nearest<T: SearchKey>(arr: &[T], target: T, k: usize) -> Vec<usize> {
+    let k = k.min(arr.len());
+    if k == 0 {
+        return Vec::new();
+    }
+    let dist = |i: usize| (arr[i].widen() - target.widen()).abs();
+    let pos = lower_bound(arr, target);
+    let (mut left, mut right) = (pos, pos); // window is arr[left..right]
+    while right - left < k {
+        let take_left = match (left > 0, right < arr.len()) {
+            (true, true) => dist(left - 1) <= dist(right),
+            (true, false) => true,
+            _ => false,
+        };
+        if take_left {
+            left -= 1;
+        } else {
+            right += 1;
+        }
+    }
+    //Elements just outside the window may tie with the farthest one taken (runs of duplicates),
+    //widen over them so the lowest indices win the tie
+    let worst = dist(left).max(dist(right - 1));
+    while left > 0 && dist(left - 1) == worst {
+        left -= 1;
+    }
+    while right < arr.len() && dist(right) == worst {
+        right += 1;
+    }
+    let mut result: Vec<usize> = (left..right).collect();
+    result.sort_by_key(|&i| (dist(i), i));
+    result.truncate(k);
+    result
+}
+
+//Brute-force reference: sort every index by (distance, index) and take the first k
+fn k_nearest_brute<T: SearchKey>(arr: &[T], target: T, k: usize) -> Vec<usize> {
+    let mut idx: Vec<usize> = (0..arr.len()).collect();
+    idx.sort_by_key(|&i| ((arr[i].widen() - target.widen()).abs(), i));
+    idx.truncate(k);
+    idx
+}
+
+fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
+    }
+}
+
+fn run<T: SearchKey>(args: &BenchArgs) {
+    
+    // Start timer
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before K-Nearest Search");
+    println!("Element type : {}", T::NAME);
+
+    //=====================================================================================================
+    if args.order == SortOrder::Descending {
+        eprintln!("knn_search only supports ascending arrays, ignoring --order desc");
+    }
+    if args.probes.is_some() || args.random_probes.is_some() {
+        eprintln!("knn_search has no extra probe mode, ignoring --probes/--random-probes");
+    }
+    let sorted_array = load_or_generate::<T>(ARRAY_SIZE, SortOrder::Ascending, args.cache.as_deref(), args.unique).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    timer.process_info("Array Generation & Sort");
+
+    let first = sorted_array[0];
+    let last = sorted_array[ARRAY_SIZE - 1];
+    let middle = sorted_array[ARRAY_SIZE/2];
+    let el_les = T::below_range();
+    let el_grt = T::above_range();
+    println!(
+        "First : {} , Last : {} , Middle : {} , Element < MIN : {} , Element > MAX {} , K : {}",
+        first,
+        last,
+        middle,
+        el_les,
+        el_grt,
+        K);
+    //Cold vs warm timing of the first element probe, the warmup also leaves caches warm for the probes below
+    let (cold, warm) = cold_warm(&sorted_array, first, args.warmup, |arr: &[T], target: T| k_nearest(arr, target, K));
+    println!("Cold First Element : {:#?} , Warm First Element : {:#?} ({} warmup searches)", cold, warm, args.warmup);
+    timer.process_info("Warmup");
+    //=====================================================================================================
+    for (label, target) in [
+        ("First Element", first),
+        ("Last Element", last),
+        ("Middle Element", middle),
+        ("Element < MIN", el_les),
+        ("Element > MAX", el_grt),
+    ] {
+        println!("=================================");
+        let nearest = k_nearest(&sorted_array, target, K);
+        println!("K-Nearest {} : {:?}", label, nearest);
+        timer.process_info(&format!("{} K-Nearest Search", label));
+
+        let brute = k_nearest_brute(&sorted_array, target, K);
+        println!("Brute Force {} : {:?} (matches : {})", label, brute, brute == nearest);
+        timer.process_info(&format!("{} Brute Force", label));
+    }
+    
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Small xorshift so the arrays are reproducible without a rand dependency
+    fn sorted_with_duplicates(seed: u64, len: usize, spread: i64) -> Vec<i64> {
+        let mut state = seed;
+        let mut arr: Vec<i64> = (0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % spread as u64) as i64
+        }).collect();
+        arr.sort();
+        arr
+    }
+
+    #[test]
+    fn matches_brute_force_on_random_arrays() {
+        for seed in 1..200u64 {
+            let len = (seed % 40) as usize + 1;
+            //A spread smaller than the length forces runs of duplicates
+            let arr = sorted_with_duplicates(seed, len, (seed % 3 + 1) as i64 * 8);
+            for target in [-5, 0, 3, 7, 12, 30] {
+                for k in [1, 2, 5, len, len + 3] {
+                    assert_eq!(k_nearest(&arr, target, k), k_nearest_brute(&arr, target, k),
+                        "arr {:?} target {} k {}", arr, target, k);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn k_larger_than_array_returns_every_index() {
+        let arr = [1i64, 4, 4, 9];
+        assert_eq!(k_nearest(&arr, 4, 10), vec![1, 2, 0, 3]);
+    }
+
+    #[test]
+    fn empty_array_or_zero_k_returns_nothing() {
+        assert!(k_nearest::<i64>(&[], 3, 5).is_empty());
+        assert!(k_nearest(&[1i64, 2, 3], 2, 0).is_empty());
+    }
+}
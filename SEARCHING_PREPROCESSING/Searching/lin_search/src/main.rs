@@ -1,4 +1,5 @@
 #![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
 //1_00_00_000
 //2_50_00_000
 //5_00_00_000
@@ -6,70 +7,94 @@
 
 
 const ARRAY_SIZE : usize = 10_00_00_000;
-const MIN : i32 = 1000;
-const MAX : i32 = 10000;
+//Move-to-front comparison: list length and number of lookups, 9 in 10 of them for the hot value
+const MTF_SIZE : usize = 1_00_000;
+const MTF_QUERIES : usize = 10_000;
 
 use std::{time::Instant, fs::File};
-use sysinfo::{Pid, System};
+use bench_utils::Timer;
 use std::cmp::Ordering;
  
-//Random value generation
-use rand::Rng;
-
-fn process_info(sys: &mut System, pid: Pid,print_log: String, start_time: &Instant) {
-    println!("============={}================",print_log);
-    sys.refresh_all();
-    if let Some(process) = sys.process(pid) {
-        println!("Process name: {}", process.name());
-        println!("Executable path: {:?}", process.exe());
-        println!("Memory usage: {:.2} MB", process.memory() as f64 / 1024.0 / 1024.0);
-    } else {
-        println!("Process not found!");
+use search_core::persist::load_or_generate;
+use search_core::probes::run_probes;
+use search_core::keyed::{linear_search, linear_search_within, Found};
+use search_core::algorithms::mtf_linear_search;
+use search_core::{cold_warm, BenchArgs, Dtype, SearchKey, SortOrder};
+
+//Min/max of a sorted array are its ends, O(1); None for an empty array
+fn sorted_min<T: SearchKey>(arr: &[T], order: SortOrder) -> Option<T> {
+    match order {
+        SortOrder::Ascending => arr.first().copied(),
+        SortOrder::Descending => arr.last().copied(),
     }
-    println!("Till -- {} : {:#?}",print_log,start_time.elapsed());
 }
 
+fn sorted_max<T: SearchKey>(arr: &[T], order: SortOrder) -> Option<T> {
+    match order {
+        SortOrder::Ascending => arr.last().copied(),
+        SortOrder::Descending => arr.first().copied(),
+    }
+}
 
-fn generate_sorted_random_array(n: usize) -> Vec<i32> {
-    let mut rng = rand::thread_rng();
-    let mut arr: Vec<i32> = (0..n).map(|_| rng.gen_range(MIN..MAX)).collect();
-    arr.sort();
-    arr
+//Min/max by looking at every element, O(n), what you write when you forget the array is sorted
+fn scan_min<T: SearchKey>(arr: &[T]) -> Option<T> {
+    arr.iter().min().copied()
 }
 
-//Perform Linear search and return the index of the element found else give None
-fn linear_search(arr: &[i32], target: i32) -> Option<usize> {
-    for (i, &val) in arr.iter().enumerate() {
-        if val == target {
-            return Some(i);
-        }
-    }
-    None
+fn scan_max<T: SearchKey>(arr: &[T]) -> Option<T> {
+    arr.iter().max().copied()
+}
+
+//Skewed lookups: the hot value (the list's last element, the worst case for a plain scan) 9 times in 10,
+//otherwise an element spread over the list
+fn mtf_queries<T: SearchKey>(list: &[T]) -> Vec<T> {
+    let hot = list[list.len() - 1];
+    (0..MTF_QUERIES).map(|i| if i % 10 == 0 { list[(i * 7919) % list.len()] } else { hot }).collect()
+}
+
+//Mean position the hot value was found at, over the first and the second half of the queries
+fn hot_positions<T: SearchKey>(queries: &[T], found: &[Option<usize>], hot: T) -> (f64, f64) {
+    let half = queries.len() / 2;
+    let mean = |range: std::ops::Range<usize>| {
+        let hits: Vec<usize> = range.filter(|&i| queries[i] == hot).filter_map(|i| found[i]).collect();
+        hits.iter().sum::<usize>() as f64 / hits.len().max(1) as f64
+    };
+    (mean(0..half), mean(half..queries.len()))
 }
 
 fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
+    }
+}
+
+fn run<T: SearchKey>(args: &BenchArgs) {
     
     // Start timer
-    let start_time = Instant::now();
-
-    // Initialize system info
-    let mut sys = System::new_all();
-    sys.refresh_all();
-
-    // Get current process ID
-    let pid = sysinfo::get_current_pid().unwrap();
-    process_info(&mut sys, pid,String::from("Before Linear Search"),&start_time);
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before Linear Search");
+    println!("Element type : {}", T::NAME);
 
     //=====================================================================================================
-    let sorted_array = generate_sorted_random_array(ARRAY_SIZE);
+    let sorted_array = load_or_generate::<T>(ARRAY_SIZE, args.order, args.cache.as_deref(), args.unique).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    assert!(args.order.is_sorted(&sorted_array), "generated array is not in {:?} order", args.order);
     //println!("{:?}", sorted_array);
-    process_info(&mut sys, pid,String::from("Array Generation & Sort"),&start_time);
+    timer.process_info("Array Generation & Sort");
 
     let first = &sorted_array[0];
     let last = &sorted_array[ARRAY_SIZE - 1];
     let middle = &sorted_array[ARRAY_SIZE/2];
-    let el_les = 50;
-    let el_grt = 10006;
+    let el_les = T::below_range();
+    let el_grt = T::above_range();
     println!(
         "First : {} , Last : {} , Middle : {} , Element < MIN : {} , Element > MAX {}",
         first,
@@ -77,20 +102,123 @@ fn main() {
         middle,
         el_les,
         el_grt);
+    //Cold vs warm timing of the first element probe, the warmup also leaves caches warm for the probes below
+    let (cold, warm) = cold_warm(&sorted_array, *first, args.warmup, |arr: &[T], target: T| linear_search(arr, target, args.order).found());
+    println!("Cold First Element : {:#?} , Warm First Element : {:#?} ({} warmup searches)", cold, warm, args.warmup);
+    timer.process_info("Warmup");
     //=====================================================================================================
-    println!("Linear Search First Element : {:#?}",linear_search(&sorted_array,*first).unwrap());
-    process_info(&mut sys, pid,String::from("First Element Search"),&start_time);
-    println!("Linear Search Last Element : {:#?}",linear_search(&sorted_array,*last).unwrap());
-    process_info(&mut sys, pid,String::from("Last Element Search"),&start_time);
-    println!("Linear Search Middle Element : {:#?}",linear_search(&sorted_array,*middle).unwrap());
-    process_info(&mut sys, pid,String::from("Middle Element Search"),&start_time);
+    //--timeout-ms: each fixed probe gives up after that long instead of scanning the whole array
+    if let Some(limit) = args.timeout {
+        for (label, target) in [("First Element", *first), ("Last Element", *last), ("Middle Element", *middle), ("Element < MIN", el_les), ("Element > MAX", el_grt)] {
+            println!("Linear Search {} : {} (timeout {:#?})", label, linear_search_within(&sorted_array, target, limit), limit);
+            timer.process_info(&format!("{} Search", label));
+        }
+    } else {
+        println!("Linear Search First Element : {:#?}",linear_search(&sorted_array,*first,args.order).found().unwrap());
+        timer.process_info("First Element Search");
+        println!("Linear Search Last Element : {:#?}",linear_search(&sorted_array,*last,args.order).found().unwrap());
+        timer.process_info("Last Element Search");
+        println!("Linear Search Middle Element : {:#?}",linear_search(&sorted_array,*middle,args.order).found().unwrap());
+        timer.process_info("Middle Element Search");
 
+        println!("=================================");
+        println!("Linear Search Element < {} : {:?}",T::LOW,linear_search(&sorted_array,el_les,args.order));
+        timer.process_info("Element < MIN Search");
+        
+        println!("=================================");
+        println!("Linear Search Element > {} : {:?}",T::HIGH,linear_search(&sorted_array,el_grt,args.order));
+        timer.process_info("Element > MAX Search");
+    }
+
+    //Extra --probes / --random-probes targets, one CSV row each
+    if run_probes(args, &sorted_array, "linear", |arr: &[T], target: T| linear_search(arr, target, args.order).found()) {
+        timer.process_info("Extra Probes");
+    }
+
+    //=====================================================================================================
+    //Min/Max: O(1) from the sorted ends vs O(n) full scan
+    let start = Instant::now();
+    let (s_min, s_max) = (sorted_min(&sorted_array, args.order), sorted_max(&sorted_array, args.order));
+    let sorted_time = start.elapsed();
+    let start = Instant::now();
+    let (f_min, f_max) = (scan_min(&sorted_array), scan_max(&sorted_array));
+    let scan_time = start.elapsed();
     println!("=================================");
-    println!("Linear Search Element < {MIN} : {:#?}",linear_search(&sorted_array,el_les));
-    process_info(&mut sys, pid,String::from("Element < MIN Search"),&start_time);
-    
+    println!("Sorted Min/Max : {:?} / {:?} in {:#?}", s_min, s_max, sorted_time);
+    println!("Scan Min/Max : {:?} / {:?} in {:#?} (agree : {})", f_min, f_max, scan_time, (s_min, s_max) == (f_min, f_max));
+    println!("Scan / Sorted : {:.0}x", scan_time.as_secs_f64() / sorted_time.as_secs_f64().max(1e-9));
+    timer.process_info("Min/Max Sorted vs Scan");
+
+    //=====================================================================================================
+    //Move-to-front: the same skewed lookups on a plain list and on one reordered by every hit
+    //Spread over the whole array, so the i32 list still holds most distinct values
+    let list: Vec<T> = sorted_array.iter().step_by(ARRAY_SIZE / MTF_SIZE).copied().collect();
+    let queries = mtf_queries(&list);
+    let hot = list[MTF_SIZE - 1];
+    let start = Instant::now();
+    let plain: Vec<Option<usize>> = queries.iter().map(|&q| linear_search(&list, q, args.order).found()).collect();
+    let plain_time = start.elapsed();
+    let mut mtf_list = list.clone();
+    let start = Instant::now();
+    let mtf: Vec<Option<usize>> = queries.iter().map(|q| mtf_linear_search(&mut mtf_list, q)).collect();
+    let mtf_time = start.elapsed();
+    //Positions differ once the list is reordered, but hits and misses must match
+    let consistent = plain.iter().zip(&mtf).all(|(p, m)| p.is_some() == m.is_some())
+        && mtf_list[0] == queries[MTF_QUERIES - 1];
+    let (plain_early, plain_late) = hot_positions(&queries, &plain, hot);
+    let (mtf_early, mtf_late) = hot_positions(&queries, &mtf, hot);
     println!("=================================");
-    println!("Linear Search Element > {MAX} : {:#?}",linear_search(&sorted_array,el_grt));
-    process_info(&mut sys, pid,String::from("Element > MAX Search"),&start_time);
-    
-}
\ No newline at end of file
+    println!("Plain Linear : {:#?} , Move-to-front : {:#?} for {} lookups over {} elements", plain_time, mtf_time, MTF_QUERIES, MTF_SIZE);
+    println!("Hot value mean position (first half / second half) : Plain {:.1} / {:.1} , Move-to-front {:.1} / {:.1}",
+        plain_early, plain_late, mtf_early, mtf_late);
+    println!("Move-to-front consistent : {} , Speedup {:.1}x", consistent, plain_time.as_secs_f64() / mtf_time.as_secs_f64().max(1e-9));
+    timer.process_info("Move-to-front vs Plain");
+    if !consistent {
+        eprintln!("Move-to-front check failed: hits and misses differ from the plain search, or the last lookup isn't at the front");
+        std::process::exit(2);
+    }
+    if mtf_late > mtf_early {
+        eprintln!("Move-to-front check failed: the hot value's mean position grew from {:.1} to {:.1}", mtf_early, mtf_late);
+        std::process::exit(2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Small xorshift so the arrays are reproducible without seeding rand
+    fn sorted_random(seed: u64, len: usize, order: SortOrder) -> Vec<i64> {
+        let mut state = seed;
+        let mut arr: Vec<i64> = (0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 1000) as i64 - 500
+        }).collect();
+        arr.sort_by(|a, b| order.cmp(*a, *b));
+        arr
+    }
+
+    #[test]
+    fn sorted_and_scan_min_max_agree() {
+        for seed in 1..100u64 {
+            for order in [SortOrder::Ascending, SortOrder::Descending] {
+                let arr = sorted_random(seed, (seed % 30) as usize + 1, order);
+                assert_eq!(sorted_min(&arr, order), scan_min(&arr), "{:?}", arr);
+                assert_eq!(sorted_max(&arr, order), scan_max(&arr), "{:?}", arr);
+            }
+        }
+    }
+
+    #[test]
+    fn empty_array_has_no_min_or_max() {
+        let empty: [i64; 0] = [];
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            assert_eq!(sorted_min(&empty, order), None);
+            assert_eq!(sorted_max(&empty, order), None);
+        }
+        assert_eq!(scan_min(&empty), None);
+        assert_eq!(scan_max(&empty), None);
+    }
+}
@@ -0,0 +1,129 @@
+#![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
+
+const ARRAY_SIZE : usize = 10_00_000;
+
+use std::{time::Instant, fs::File};
+use bench_utils::Timer;
+
+use search_core::{generate_array, BenchArgs, Dtype, SearchKey, SortOrder};
+
+//Two-pointer merge of two ascending arrays, O(n + m). Equal values are all kept, a's first
+fn merge_sorted<T: SearchKey>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a[i] <= b[j] {
+            merged.push(a[i]);
+            i += 1;
+        } else {
+            merged.push(b[j]);
+            j += 1;
+        }
+    }
+    //One side is used up, the rest of the other is already in order
+    merged.extend_from_slice(&a[i..]);
+    merged.extend_from_slice(&b[j..]);
+    merged
+}
+
+//Baseline that ignores the inputs being sorted: concatenate then sort, O((n + m) log(n + m))
+fn concat_sort<T: SearchKey>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut merged = Vec::with_capacity(a.len() + b.len());
+    merged.extend_from_slice(a);
+    merged.extend_from_slice(b);
+    merged.sort();
+    merged
+}
+
+fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    if args.order == SortOrder::Descending {
+        eprintln!("merge_sorted only supports ascending arrays, ignoring --order desc");
+    }
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
+    }
+}
+
+fn run<T: SearchKey>(args: &BenchArgs) {
+    
+    // Start timer
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before Merge Sorted");
+    println!("Element type : {}", T::NAME);
+
+    //=====================================================================================================
+    //Unequal lengths so the tail copy is exercised; with --unique each side gets its own seed
+    let generate = |n: usize, seed_offset: u64| generate_array::<T>(n, SortOrder::Ascending, args.unique.map(|s| s + seed_offset)).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    let a = generate(ARRAY_SIZE, 0);
+    let b = generate(ARRAY_SIZE / 2, 1);
+    println!("Input lengths : {} + {}", a.len(), b.len());
+    timer.process_info("Array Generation & Sort");
+    //=====================================================================================================
+    let start = Instant::now();
+    let merged = merge_sorted(&a, &b);
+    let merge_time = start.elapsed();
+    println!("Two-pointer Merge : {:#?}", merge_time);
+    timer.process_info("Two-pointer Merge");
+
+    let start = Instant::now();
+    let sorted = concat_sort(&a, &b);
+    let sort_time = start.elapsed();
+    println!("Concatenate + Sort : {:#?}", sort_time);
+    timer.process_info("Concatenate + Sort");
+    //=====================================================================================================
+    println!("=================================");
+    println!("Outputs identical : {}", merged == sorted);
+    let empty: [T; 0] = [];
+    println!(
+        "Empty inputs : {}",
+        merge_sorted(&empty, &b) == b && merge_sorted(&a, &empty) == a && merge_sorted(&empty, &empty).is_empty()
+    );
+    println!("Concatenate + Sort / Merge : {:.2}x", sort_time.as_secs_f64() / merge_time.as_secs_f64());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Small xorshift so the inputs are reproducible without a rand dependency
+    fn sorted_random(seed: u64, len: usize) -> Vec<i64> {
+        let mut state = seed;
+        let mut arr: Vec<i64> = (0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 50) as i64 - 25
+        }).collect();
+        arr.sort();
+        arr
+    }
+
+    #[test]
+    fn matches_concat_sort_on_random_inputs() {
+        for seed in 1..200u64 {
+            let a = sorted_random(seed, (seed % 30) as usize);
+            let b = sorted_random(seed * 7919, (seed % 17) as usize);
+            assert_eq!(merge_sorted(&a, &b), concat_sort(&a, &b), "a {:?} b {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn empty_inputs() {
+        let a = vec![1i64, 2, 2, 5];
+        let empty: [i64; 0] = [];
+        assert_eq!(merge_sorted(&empty, &a), a);
+        assert_eq!(merge_sorted(&a, &empty), a);
+        assert!(merge_sorted(&empty, &empty).is_empty());
+        assert!(concat_sort(&empty, &empty).is_empty());
+    }
+}
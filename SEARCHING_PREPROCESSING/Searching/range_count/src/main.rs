@@ -0,0 +1,144 @@
+#![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
+
+const ARRAY_SIZE : usize = 10_00_000;
+const QUERIES : usize = 1_000;
+
+use std::time::Instant;
+use bench_utils::Timer;
+
+//Random value generation
+use rand::Rng;
+
+use search_core::persist::load_or_generate;
+use search_core::keyed::count_in_range;
+use search_core::{BenchArgs, Dtype, SearchKey, SortOrder};
+
+//Linear reference: filter every element, O(n) per query
+fn count_linear<T: SearchKey>(arr: &[T], lo: T, hi: T) -> usize {
+    arr.iter().filter(|&&v| lo <= v && v <= hi).count()
+}
+
+//Query mix: mostly ranges inside the generated values, 1 in 8 reversed (lo > hi) and 1 in 8 entirely
+//below or above the array
+fn generate_ranges<T: SearchKey>(n: usize) -> Vec<(T, T)> {
+    let mut rng = rand::thread_rng();
+    (0..n).map(|i| {
+        let a = rng.gen_range(T::LOW..T::HIGH);
+        let b = rng.gen_range(T::LOW..T::HIGH);
+        match i % 8 {
+            6 => (a.max(b), a.min(b)),
+            7 if i % 16 == 7 => (T::below_range(), T::below_range()),
+            7 => (T::above_range(), T::above_range()),
+            _ => (a.min(b), a.max(b)),
+        }
+    }).collect()
+}
+
+fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    if args.order == SortOrder::Descending {
+        eprintln!("range_count only supports ascending arrays, ignoring --order desc");
+    }
+    match args.dtype {
+        Dtype::I32 => run::<i32>(&args),
+        Dtype::I64 => run::<i64>(&args),
+        Dtype::U64 => run::<u64>(&args),
+    }
+}
+
+fn run<T: SearchKey>(args: &BenchArgs) {
+    
+    // Start timer
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before Range Count");
+    println!("Element type : {}", T::NAME);
+
+    //=====================================================================================================
+    if args.probes.is_some() || args.random_probes.is_some() {
+        eprintln!("range_count generates its own ranges, ignoring --probes/--random-probes");
+    }
+    let sorted_array = load_or_generate::<T>(ARRAY_SIZE, SortOrder::Ascending, args.cache.as_deref(), args.unique).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2)
+    });
+    timer.process_info("Array Generation & Sort");
+
+    let ranges = generate_ranges::<T>(QUERIES);
+    timer.process_info("Range Generation");
+    //=====================================================================================================
+    let start = Instant::now();
+    let bin_counts: Vec<usize> = ranges.iter().map(|&(lo, hi)| count_in_range(&sorted_array, lo, hi)).collect();
+    let bin_time = start.elapsed();
+    println!("Binary Search Counts : {:#?} for {} ranges", bin_time, QUERIES);
+    timer.process_info("Binary Search Counts");
+
+    let start = Instant::now();
+    let lin_counts: Vec<usize> = ranges.iter().map(|&(lo, hi)| count_linear(&sorted_array, lo, hi)).collect();
+    let lin_time = start.elapsed();
+    println!("Linear Filter Counts : {:#?} for {} ranges", lin_time, QUERIES);
+    timer.process_info("Linear Filter Counts");
+    //=====================================================================================================
+    println!("=================================");
+    let mismatches = bin_counts.iter().zip(&lin_counts).filter(|(b, l)| b != l).count();
+    println!("Counts consistent : {} ({} mismatches)", mismatches == 0, mismatches);
+    let (lo, hi) = (sorted_array[0], sorted_array[ARRAY_SIZE - 1]);
+    println!("Whole array : {} , Reversed range : {} , Below MIN : {} , Above MAX : {}",
+        count_in_range(&sorted_array, lo, hi),
+        count_in_range(&sorted_array, hi, lo),
+        count_in_range(&sorted_array, T::below_range(), T::below_range()),
+        count_in_range(&sorted_array, T::above_range(), T::above_range()));
+    println!("Empty array : {}", count_in_range::<T>(&[], lo, hi));
+    let bin_per_query = bin_time.as_secs_f64() / QUERIES as f64;
+    let lin_per_query = lin_time.as_secs_f64() / QUERIES as f64;
+    println!("Per range : Binary Search {:.1} ns , Linear Filter {:.1} ns , Speedup {:.0}x",
+        bin_per_query * 1e9, lin_per_query * 1e9, lin_per_query / bin_per_query);
+    if mismatches > 0 {
+        std::process::exit(2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Small xorshift so the arrays are reproducible without seeding rand
+    fn sorted_random(seed: u64, len: usize) -> Vec<i64> {
+        let mut state = seed;
+        let mut arr: Vec<i64> = (0..len).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state % 40) as i64
+        }).collect();
+        arr.sort();
+        arr
+    }
+
+    #[test]
+    fn matches_linear_count_on_random_arrays() {
+        for seed in 1..200u64 {
+            let arr = sorted_random(seed, (seed % 50) as usize);
+            for lo in -3..44 {
+                for hi in [lo - 1, lo, lo + 5, 50] {
+                    assert_eq!(count_in_range(&arr, lo, hi), count_linear(&arr, lo, hi),
+                        "arr {:?} lo {} hi {}", arr, lo, hi);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn edge_ranges() {
+        let arr = [2i64, 4, 4, 4, 9];
+        assert_eq!(count_in_range(&arr, 4, 4), 3);
+        assert_eq!(count_in_range(&arr, 9, 2), 0);
+        assert_eq!(count_in_range(&arr, -10, 1), 0);
+        assert_eq!(count_in_range(&arr, 10, 20), 0);
+        assert_eq!(count_in_range(&arr, 2, 9), arr.len());
+        assert_eq!(count_in_range::<i64>(&[], 0, 10), 0);
+    }
+}
@@ -0,0 +1,287 @@
+//Single entry point for the search benchmarks: `search <linear|binary|jump|interpolation|all> [options]`
+//Every subcommand generates one sorted array and times the same probes against it; `all` runs every
+//algorithm and prints them side by side. The per-algorithm binaries stay for the hyperfine/Python comparisons
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use clap::{Args, Parser, Subcommand};
+use search_core::keyed::{binary_search, interpolation_search, jump_search, linear_search, linear_search_within, BoundedSearch, Found, SearchResult};
+use search_core::{generate_sorted_range_array, generate_sorted_unique_array, Dtype, SearchKey, SortOrder};
+
+#[derive(Parser)]
+#[command(name = "search", about = "Search algorithm benchmarks over one generated sorted array")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Linear scan
+    Linear(Opts),
+    /// Binary search
+    Binary(Opts),
+    /// Jump search (sqrt(n) blocks)
+    Jump(Opts),
+    /// Interpolation search
+    Interpolation(Opts),
+    /// Every algorithm on the same array, as a comparison table
+    All(Opts),
+}
+
+#[derive(Args, Clone, Debug)]
+struct Opts {
+    /// Number of elements in the array
+    #[arg(long, default_value_t = 1_000_000)]
+    size: usize,
+    /// Seed for a reproducible array (random otherwise)
+    #[arg(long)]
+    seed: Option<u64>,
+    /// Distinct values only (needs a range holding at least --size values)
+    #[arg(long)]
+    unique: bool,
+    /// Smallest generated value (default: the element type's benchmark range)
+    #[arg(long)]
+    min: Option<String>,
+    /// Generated values stay below this (default: the element type's benchmark range)
+    #[arg(long)]
+    max: Option<String>,
+    /// Element type: i32, i64 or u64
+    #[arg(long, default_value = "i32", value_parser = parse_dtype)]
+    dtype: Dtype,
+    /// Sort order of the array: asc or desc
+    #[arg(long, default_value = "asc", value_parser = parse_order)]
+    order: SortOrder,
+    /// Extra targets timed after the fixed first/last/middle/<min/>max probes
+    #[arg(long, value_delimiter = ',')]
+    probes: Vec<String>,
+    /// Searches per probe; the reported time is their mean
+    #[arg(long, default_value_t = 100)]
+    iterations: usize,
+    /// Give up a linear search after this many milliseconds and report it as timed out
+    #[arg(long)]
+    timeout_ms: Option<u64>,
+}
+
+fn parse_dtype(s: &str) -> Result<Dtype, String> {
+    Dtype::parse(s).ok_or(format!("expected i32, i64 or u64, got '{}'", s))
+}
+
+fn parse_order(s: &str) -> Result<SortOrder, String> {
+    SortOrder::parse(s).ok_or(format!("expected asc or desc, got '{}'", s))
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Algorithm {
+    Linear,
+    Binary,
+    Jump,
+    Interpolation,
+}
+
+impl Algorithm {
+    const ALL: [Algorithm; 4] = [Algorithm::Linear, Algorithm::Binary, Algorithm::Jump, Algorithm::Interpolation];
+
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Linear => "linear",
+            Algorithm::Binary => "binary",
+            Algorithm::Jump => "jump",
+            Algorithm::Interpolation => "interpolation",
+        }
+    }
+
+    fn search<T: SearchKey>(self, arr: &[T], target: T, order: SortOrder) -> SearchResult {
+        match self {
+            Algorithm::Linear => linear_search(arr, target, order),
+            Algorithm::Binary => binary_search(arr, target, order),
+            Algorithm::Jump => jump_search(arr, target, order),
+            Algorithm::Interpolation => interpolation_search(arr, target, order),
+        }
+    }
+}
+
+//One timed probe of one algorithm
+struct ProbeResult {
+    found: Option<usize>,
+    mean_ns: f64,
+    //found index holds target, or nothing found and target is absent
+    correct: bool,
+    //a linear search hit --timeout-ms; found and mean_ns are meaningless
+    timed_out: bool,
+}
+
+fn main() {
+    let cli = Cli::parse();
+    let (algorithms, opts) = match cli.command {
+        Command::Linear(o) => (vec![Algorithm::Linear], o),
+        Command::Binary(o) => (vec![Algorithm::Binary], o),
+        Command::Jump(o) => (vec![Algorithm::Jump], o),
+        Command::Interpolation(o) => (vec![Algorithm::Interpolation], o),
+        Command::All(o) => (Algorithm::ALL.to_vec(), o),
+    };
+    let result = match opts.dtype {
+        Dtype::I32 => run::<i32>(&algorithms, &opts),
+        Dtype::I64 => run::<i64>(&algorithms, &opts),
+        Dtype::U64 => run::<u64>(&algorithms, &opts),
+    };
+    if let Err(e) = result {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    }
+}
+
+fn parse_value<T: SearchKey>(flag: &str, v: &str) -> Result<T, String> {
+    v.trim().parse::<T>().map_err(|_| format!("{} value '{}' is not a valid {}", flag, v, T::NAME))
+}
+
+//Just outside min..max, skipped when that would leave the element type
+fn outside_probes<T: SearchKey>(min: T, max: T) -> Vec<(String, T)> {
+    let mut probes = Vec::new();
+    let below = min.widen() - 1;
+    if T::narrow(below).widen() == below {
+        probes.push((String::from("< min"), T::narrow(below)));
+    }
+    //max itself is never generated
+    probes.push((String::from("> max"), max));
+    probes
+}
+
+fn run<T: SearchKey>(algorithms: &[Algorithm], opts: &Opts) -> Result<(), String> {
+    if opts.size == 0 || opts.iterations == 0 {
+        return Err(String::from("--size and --iterations must be at least 1"));
+    }
+    let min = opts.min.as_deref().map(|v| parse_value("--min", v)).transpose()?.unwrap_or(T::LOW);
+    let max = opts.max.as_deref().map(|v| parse_value("--max", v)).transpose()?.unwrap_or(T::HIGH);
+
+    let start = Instant::now();
+    let arr: Vec<T> = if opts.unique {
+        let mut arr = generate_sorted_unique_array(opts.size, min, max, opts.seed.unwrap_or_else(rand_seed))?;
+        if opts.order == SortOrder::Descending {
+            arr.reverse();
+        }
+        arr
+    } else {
+        generate_sorted_range_array(opts.size, min, max, opts.order, opts.seed)?
+    };
+    println!(
+        "{} {} values in {}..{} ({:?}{}), generated in {:#?}",
+        arr.len(), T::NAME, min, max, opts.order,
+        opts.seed.map(|s| format!(", seed {}", s)).unwrap_or_default(),
+        start.elapsed()
+    );
+
+    let mut probes = vec![
+        (String::from("first"), arr[0]),
+        (String::from("last"), arr[arr.len() - 1]),
+        (String::from("middle"), arr[arr.len() / 2]),
+    ];
+    probes.extend(outside_probes(min, max));
+    for v in &opts.probes {
+        probes.push((v.trim().to_string(), parse_value("--probes", v)?));
+    }
+
+    let results: Vec<Vec<ProbeResult>> = algorithms.iter()
+        .map(|&algorithm| probes.iter().map(|&(_, target)| time_probe(&arr, target, opts, algorithm)).collect())
+        .collect();
+
+    println!("{} searches per probe, mean time per search", opts.iterations);
+    if let [algorithm] = algorithms {
+        print_single(*algorithm, &probes, &results[0]);
+    } else {
+        print_comparison(algorithms, &probes, &results);
+    }
+    let wrong: Vec<String> = algorithms.iter().zip(&results)
+        .flat_map(|(a, rs)| rs.iter().zip(&probes).filter(|(r, _)| !r.correct).map(move |(_, (label, _))| format!("{} {}", a.name(), label)))
+        .collect();
+    if wrong.is_empty() {
+        println!("Every result checked against the array : ok");
+        Ok(())
+    } else {
+        Err(format!("Wrong results : {}", wrong.join(", ")))
+    }
+}
+
+fn rand_seed() -> u64 {
+    std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map(|d| d.as_nanos() as u64).unwrap_or(0)
+}
+
+fn time_probe<T: SearchKey>(arr: &[T], target: T, opts: &Opts, algorithm: Algorithm) -> ProbeResult {
+    if let (Algorithm::Linear, Some(ms)) = (algorithm, opts.timeout_ms) {
+        return time_linear_within(arr, target, opts, Duration::from_millis(ms));
+    }
+    let found = algorithm.search(arr, target, opts.order).found();
+    let start = Instant::now();
+    for _ in 0..opts.iterations {
+        let _ = black_box(algorithm.search(black_box(arr), black_box(target), opts.order));
+    }
+    let mean_ns = start.elapsed().as_nanos() as f64 / opts.iterations as f64;
+    let present = arr.binary_search_by(|v| opts.order.cmp(*v, target)).is_ok();
+    let correct = match found {
+        Some(i) => arr[i] == target,
+        None => !present,
+    };
+    ProbeResult { found, mean_ns, correct, timed_out: false }
+}
+
+//time_probe for linear search under --timeout-ms: stops at the first search that times out
+fn time_linear_within<T: SearchKey>(arr: &[T], target: T, opts: &Opts, limit: Duration) -> ProbeResult {
+    let timed_out = ProbeResult { found: None, mean_ns: f64::NAN, correct: true, timed_out: true };
+    let found = match linear_search_within(arr, target, limit) {
+        BoundedSearch::Found(i) => Some(i),
+        BoundedSearch::NotFound => None,
+        BoundedSearch::TimedOut { .. } => return timed_out,
+    };
+    let start = Instant::now();
+    for _ in 0..opts.iterations {
+        if let BoundedSearch::TimedOut { .. } = black_box(linear_search_within(black_box(arr), black_box(target), limit)) {
+            return timed_out;
+        }
+    }
+    let mean_ns = start.elapsed().as_nanos() as f64 / opts.iterations as f64;
+    let correct = match found {
+        Some(i) => arr[i] == target,
+        None => !arr.contains(&target),
+    };
+    ProbeResult { found, mean_ns, correct, timed_out: false }
+}
+
+//mean_ns, or "timed out"
+fn time_cell(r: &ProbeResult) -> String {
+    if r.timed_out { String::from("timed out") } else { format!("{:.1}", r.mean_ns) }
+}
+
+fn print_single<T: SearchKey>(algorithm: Algorithm, probes: &[(String, T)], results: &[ProbeResult]) {
+    println!("{:<14}{:>22}{:>12}{:>14}", format!("{} search", algorithm.name()), "target", "found", "mean_ns");
+    for ((label, target), r) in probes.iter().zip(results) {
+        let found = r.found.map(|i| i.to_string()).unwrap_or_else(|| String::from("-"));
+        println!("{:<14}{:>22}{:>12}{:>14}", label, target, found, time_cell(r));
+    }
+}
+
+//One row per probe, one mean_ns column per algorithm, then the per-algorithm total
+fn print_comparison<T: SearchKey>(algorithms: &[Algorithm], probes: &[(String, T)], results: &[Vec<ProbeResult>]) {
+    print!("{:<14}", "probe");
+    for a in algorithms {
+        print!("{:>16}", a.name());
+    }
+    println!();
+    for (i, (label, _)) in probes.iter().enumerate() {
+        print!("{:<14}", label);
+        for rs in results {
+            print!("{:>16}", time_cell(&rs[i]));
+        }
+        println!();
+    }
+    print!("{:<14}", "total");
+    //A timed-out probe makes the total unknown
+    for rs in results {
+        if rs.iter().any(|r| r.timed_out) {
+            print!("{:>16}", "timed out");
+        } else {
+            print!("{:>16.1}", rs.iter().map(|r| r.mean_ns).sum::<f64>());
+        }
+    }
+    println!();
+}
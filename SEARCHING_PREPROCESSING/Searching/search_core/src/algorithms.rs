@@ -0,0 +1,250 @@
+//Search algorithms over any Ord element type (strings included), for callers outside the benchmark
+//binaries such as the server's /api/items/search_id. The benchmark versions over SearchKey are in keyed.rs
+
+use std::cmp::Ordering;
+
+//True when arr is in ascending order (duplicates allowed). The searches below assume it and only debug_assert it,
+//since the O(n) scan would swamp an O(log n) search; call this first in release builds when the data comes from
+//outside. A float array holding NaN is never sorted
+pub fn check_sorted<T: PartialOrd>(arr: &[T]) -> bool {
+    arr.windows(2).all(|w| w[0] <= w[1])
+}
+
+//Index of target in arr (sorted ascending), or None
+pub fn binary_search<T: Ord>(arr: &[T], target: &T) -> Option<usize> {
+    binary_search_std(arr, target).ok()
+}
+
+//Same contract as slice::binary_search: Ok(index of target), or Err(index where target would be inserted to
+//keep arr sorted), 0 below the first element and arr.len() past the last. With duplicates Ok may be any of them
+pub fn binary_search_std<T: Ord>(arr: &[T], target: &T) -> Result<usize, usize> {
+    debug_assert!(check_sorted(arr), "binary_search: array is not sorted ascending");
+    let (mut low, mut high) = (0, arr.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match arr[mid].cmp(target) {
+            Ordering::Equal => return Ok(mid),
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+        }
+    }
+    Err(low)
+}
+
+//Search over records by one of their fields, like slice::binary_search_by_key: arr must be sorted by `key`,
+//e.g. items by id or vehicles by year. The key may borrow from the record (`|item| item.name.as_str()`).
+//With duplicate keys any of them may be returned
+pub fn binary_search_by_key<'a, T, K: Ord>(arr: &'a [T], target: &K, key: impl Fn(&'a T) -> K) -> Option<usize> {
+    debug_assert!(arr.windows(2).all(|w| key(&w[0]) <= key(&w[1])), "binary_search_by_key: array is not sorted by key");
+    let (mut low, mut high) = (0, arr.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        match key(&arr[mid]).cmp(target) {
+            Ordering::Equal => return Some(mid),
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+        }
+    }
+    None
+}
+
+//Index of the first occurrence of target, arr needn't be sorted
+pub fn linear_search<T: PartialEq>(arr: &[T], target: &T) -> Option<usize> {
+    arr.iter().position(|v| v == target)
+}
+
+//Self-organizing linear search: on a hit the found element moves to the front and everything before it shifts
+//back one place, so repeated lookups of a hot value get cheaper. Returns where target was found, before the move.
+//The reordering breaks any sort order, so the list is only good for linear searches afterwards
+pub fn mtf_linear_search<T: PartialEq>(arr: &mut [T], target: &T) -> Option<usize> {
+    let index = arr.iter().position(|v| v == target)?;
+    arr[..=index].rotate_right(1);
+    Some(index)
+}
+
+//Interpolation search for float keys (arr sorted ascending, no NaN elements)
+//The position estimate (target - arr[low]) / (arr[high] - arr[low]) is NaN or infinite when the range holds
+//an infinity, overflows (-MAX..MAX) or has a subnormal width; such a step falls back to the binary-search midpoint.
+//Every probe lies inside low..=high and the range shrinks each step, so the loop always ends.
+//A NaN target is never found
+pub fn interpolation_search_f64(arr: &[f64], target: f64) -> Option<usize> {
+    debug_assert!(check_sorted(arr), "interpolation_search_f64: array is not sorted ascending or holds NaN");
+    if arr.is_empty() || target.is_nan() {
+        return None;
+    }
+    let (mut low, mut high) = (0usize, arr.len() - 1);
+    while low <= high && arr[low] <= target && target <= arr[high] {
+        if arr[low] == arr[high] {
+            return if arr[low] == target { Some(low) } else { None };
+        }
+        let frac = (target - arr[low]) / (arr[high] - arr[low]);
+        let pos = if frac.is_finite() && (0.0..=1.0).contains(&frac) {
+            (low + (frac * (high - low) as f64) as usize).min(high)
+        } else {
+            low + (high - low) / 2
+        };
+        if arr[pos] == target {
+            return Some(pos);
+        } else if arr[pos] < target {
+            low = pos + 1;
+        } else {
+            if pos == 0 {
+                break;
+            }
+            high = pos - 1;
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_sorted_identifies_sorted_and_unsorted() {
+        assert!(check_sorted(&[1, 2, 2, 7]));
+        assert!(check_sorted::<i32>(&[]));
+        assert!(check_sorted(&["apple", "banana", "cherry"]));
+        assert!(!check_sorted(&[1, 7, 2, 9]));
+        assert!(!check_sorted(&[3, 2]));
+        assert!(!check_sorted(&[1.0, f64::NAN, 2.0]));
+    }
+
+    //Arrays whose position estimate overflows or isn't finite: infinities, gaps wider than f64::MAX
+    fn large_gap_arrays() -> Vec<Vec<f64>> {
+        let mut mixed: Vec<f64> = (0..2000).map(|i| ((i / 3) as f64).powi(3) * 1e-10).collect();
+        mixed.extend([1e200, 1e250, f64::INFINITY]);
+        vec![
+            vec![f64::NEG_INFINITY, -f64::MAX, -1e300, -1.0, 0.0, 1e-300, 1.0, 1e300, f64::MAX, f64::INFINITY],
+            vec![-f64::MAX, f64::MAX],
+            mixed,
+        ]
+    }
+
+    //Neighbours one ULP apart, and subnormals whose differences are subnormal too
+    fn near_equal_arrays() -> Vec<Vec<f64>> {
+        let mut ulps = vec![1.0f64];
+        for _ in 0..1000 {
+            ulps.push(f64::from_bits(ulps[ulps.len() - 1].to_bits() + 1));
+        }
+        let subnormals: Vec<f64> = (0..1000u64).map(f64::from_bits).collect();
+        let duplicates: Vec<f64> = (0..300).map(|i| (i / 7) as f64 * f64::EPSILON).collect();
+        vec![ulps, subnormals, duplicates]
+    }
+
+    //Every element, the midpoint of each neighbouring pair, NaN and both infinities must be found exactly when
+    //binary_search_by(total_cmp) finds them, at an index holding the target
+    fn assert_matches_binary_search(arr: &[f64]) {
+        let mut targets = arr.to_vec();
+        targets.extend(arr.windows(2).map(|w| w[0] / 2.0 + w[1] / 2.0));
+        targets.extend([f64::NAN, f64::INFINITY, f64::NEG_INFINITY]);
+        for target in targets {
+            let expected = arr.binary_search_by(|v| v.total_cmp(&target)).is_ok();
+            let found = interpolation_search_f64(arr, target);
+            assert_eq!(found.is_some(), expected, "target {:e}", target);
+            if let Some(i) = found {
+                assert_eq!(arr[i], target);
+            }
+        }
+    }
+
+    #[test]
+    fn interpolation_f64_handles_large_gaps() {
+        for arr in large_gap_arrays() {
+            assert_matches_binary_search(&arr);
+        }
+    }
+
+    #[test]
+    fn interpolation_f64_handles_near_equal_values() {
+        for arr in near_equal_arrays() {
+            assert_matches_binary_search(&arr);
+        }
+    }
+
+    //Hits, misses between elements and misses past both ends, against slice::binary_search
+    fn assert_matches_std<T: Ord + Copy + std::fmt::Debug>(arr: &[T], targets: &[T]) {
+        for target in targets {
+            match (binary_search_std(arr, target), arr.binary_search(target)) {
+                (Ok(i), Ok(_)) => assert_eq!(arr[i], *target),
+                (ours, std) => assert_eq!(ours, std, "target {:?}", target),
+            }
+        }
+    }
+
+    #[test]
+    fn binary_search_std_matches_slice_binary_search() {
+        let arr = [10i32, 20, 20, 20, 30, 40];
+        assert_matches_std(&arr, &[i32::MIN, 5, 10, 15, 20, 25, 40, 45, i32::MAX]);
+        assert_eq!(binary_search_std(&arr, &5), Err(0));
+        assert_eq!(binary_search_std(&arr, &25), Err(4));
+        assert_eq!(binary_search_std(&arr, &45), Err(arr.len()));
+        assert_eq!(binary_search_std(&arr, &30), Ok(4));
+        assert_eq!(binary_search_std::<i32>(&[], &1), Err(0));
+
+        let wide = [i64::MIN, -1, 0, i64::MAX - 1];
+        assert_matches_std(&wide, &[i64::MIN, i64::MIN + 1, -2, 0, 1, i64::MAX - 1, i64::MAX]);
+        let unsigned = [0u64, 7, u64::MAX - 1];
+        assert_matches_std(&unsigned, &[0, 1, 7, 8, u64::MAX - 1, u64::MAX]);
+        let words = ["apple", "cherry", "plum"];
+        assert_matches_std(&words, &["", "apple", "banana", "plum", "zucchini"]);
+    }
+
+    #[test]
+    fn mtf_moves_a_hit_to_the_front() {
+        let mut list = vec![10, 20, 30, 40, 50];
+        assert_eq!(mtf_linear_search(&mut list, &40), Some(3));
+        assert_eq!(list, [40, 10, 20, 30, 50]);
+        assert_eq!(mtf_linear_search(&mut list, &40), Some(0));
+        assert_eq!(list, [40, 10, 20, 30, 50]);
+        //A miss leaves the list alone
+        assert_eq!(mtf_linear_search(&mut list, &99), None);
+        assert_eq!(list, [40, 10, 20, 30, 50]);
+    }
+
+    #[test]
+    fn mtf_sequence_agrees_with_linear_and_brings_the_hot_value_forward() {
+        let original: Vec<i32> = (0..200).collect();
+        let mut list = original.clone();
+        let hot = 199;
+        let mut hot_positions = Vec::new();
+        for i in 0..100 {
+            let target = if i % 4 == 0 { (i * 37) % 250 } else { hot };
+            let found = mtf_linear_search(&mut list, &target);
+            assert_eq!(found.is_some(), linear_search(&original, &target).is_some(), "target {}", target);
+            if target == hot {
+                hot_positions.push(found.unwrap());
+            }
+            //Still a permutation of the original
+            let mut sorted = list.clone();
+            sorted.sort();
+            assert_eq!(sorted, original);
+        }
+        let half = hot_positions.len() / 2;
+        let mean = |p: &[usize]| p.iter().sum::<usize>() as f64 / p.len() as f64;
+        assert_eq!(hot_positions[0], 199);
+        assert!(mean(&hot_positions[half..]) < mean(&hot_positions[..half]));
+    }
+
+    #[test]
+    fn binary_search_by_key_on_string_and_int_fields() {
+        let mut records: Vec<(String, i32)> =
+            [("delta", 10), ("alpha", 40), ("echo", 20), ("charlie", 50), ("bravo", 30)].iter().map(|&(s, n)| (s.to_string(), n)).collect();
+
+        records.sort_by(|a, b| a.0.cmp(&b.0));
+        for (i, (name, _)) in records.iter().enumerate() {
+            assert_eq!(binary_search_by_key(&records, &name.as_str(), |r| r.0.as_str()), Some(i));
+        }
+        assert_eq!(binary_search_by_key(&records, &"aardvark", |r| r.0.as_str()), None);
+        assert_eq!(binary_search_by_key(&records, &"foxtrot", |r| r.0.as_str()), None);
+
+        records.sort_by_key(|r| r.1);
+        for (i, (_, n)) in records.iter().enumerate() {
+            assert_eq!(binary_search_by_key(&records, n, |r| r.1), Some(i));
+        }
+        assert_eq!(binary_search_by_key(&records, &25, |r| r.1), None);
+        assert_eq!(binary_search_by_key(&records, &0, |r| r.1), None);
+        assert_eq!(binary_search_by_key(&[] as &[(String, i32)], &0, |r| r.1), None);
+    }
+}
@@ -0,0 +1,453 @@
+//The benchmark searches over SearchKey arrays sorted in either order, shared by the per-algorithm
+//binaries and the `search` CLI. Each takes the array's SortOrder (linear search ignores it) and returns
+//Ok(None) for a genuine miss, keeping Err for misuse (see SearchError)
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use crate::{SearchKey, SortOrder};
+
+//Why a search couldn't give an answer, as opposed to a target that just isn't there
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SearchError {
+    EmptyInput,
+    //Debug builds scan the whole array on every search and catch any element out of order. Release builds,
+    //which the benchmarks time, only compare the first element with the last
+    Unsorted,
+}
+
+impl fmt::Display for SearchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SearchError::EmptyInput => write!(f, "cannot search an empty array"),
+            SearchError::Unsorted => write!(f, "array is not sorted in the given order"),
+        }
+    }
+}
+
+impl std::error::Error for SearchError {}
+
+pub type SearchResult = Result<Option<usize>, SearchError>;
+
+//`.found()` on a SearchResult: the index when found, None for a miss or an error
+pub trait Found {
+    fn found(self) -> Option<usize>;
+}
+
+impl Found for SearchResult {
+    fn found(self) -> Option<usize> {
+        self.ok().flatten()
+    }
+}
+
+//True when arr is sorted in `order` (duplicates allowed), the keyed counterpart of algorithms::check_sorted.
+//Release builds of the searches below don't run it, so call it once on data that didn't come from the generators
+pub fn check_sorted<T: SearchKey>(arr: &[T], order: SortOrder) -> bool {
+    order.is_sorted(arr)
+}
+
+//Checks at the start of binary, jump, interpolation and adaptive jump search. The check_sorted scan is O(n), so
+//only debug builds pay for it, and they report a failure as Err(Unsorted) rather than a debug_assert! panic so
+//callers can tell it from a miss; the endpoint comparison is all a release build's timed probe loop adds
+fn check_sorted_input<T: SearchKey>(arr: &[T], order: SortOrder) -> Result<(), SearchError> {
+    if arr.is_empty() {
+        return Err(SearchError::EmptyInput);
+    }
+    if cfg!(debug_assertions) && !check_sorted(arr, order) {
+        return Err(SearchError::Unsorted);
+    }
+    if order.cmp(arr[0], arr[arr.len() - 1]) == Ordering::Greater {
+        return Err(SearchError::Unsorted);
+    }
+    Ok(())
+}
+
+//Perform Linear search and return the index of the element found else give Ok(None)
+//Order doesn't matter to a scan, `_order` only keeps the signature in line with the other searches
+pub fn linear_search<T: SearchKey>(arr: &[T], target: T, _order: SortOrder) -> SearchResult {
+    if arr.is_empty() {
+        return Err(SearchError::EmptyInput);
+    }
+    for (i, &val) in arr.iter().enumerate() {
+        if val == target {
+            return Ok(Some(i));
+        }
+    }
+    Ok(None)
+}
+
+//Outcome of a search that may give up before finishing
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoundedSearch {
+    Found(usize),
+    NotFound,
+    //Stopped after checking the first `scanned` elements
+    TimedOut { scanned: usize },
+}
+
+impl fmt::Display for BoundedSearch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoundedSearch::Found(i) => write!(f, "{}", i),
+            BoundedSearch::NotFound => write!(f, "None"),
+            BoundedSearch::TimedOut { scanned } => write!(f, "timed out after scanning {} elements", scanned),
+        }
+    }
+}
+
+//Elements scanned between clock reads, so the timeout check costs next to nothing per element
+const TIMEOUT_CHECK_EVERY: usize = 1 << 16;
+
+//Linear search that gives up once `max_duration` has passed, for the 100M-element arrays where an absent
+//target means scanning everything. The clock is read every TIMEOUT_CHECK_EVERY elements, so it can overrun
+//by one block's scan
+pub fn linear_search_within<T: SearchKey>(arr: &[T], target: T, max_duration: Duration) -> BoundedSearch {
+    let start = Instant::now();
+    for (block, chunk) in arr.chunks(TIMEOUT_CHECK_EVERY).enumerate() {
+        let offset = block * TIMEOUT_CHECK_EVERY;
+        if let Some(i) = chunk.iter().position(|&v| v == target) {
+            return BoundedSearch::Found(offset + i);
+        }
+        let scanned = offset + chunk.len();
+        if scanned < arr.len() && start.elapsed() >= max_duration {
+            return BoundedSearch::TimedOut { scanned };
+        }
+    }
+    BoundedSearch::NotFound
+}
+
+//Perform binary search and return the index of the element found else give Ok(None)
+//`order` is the order arr is sorted in
+pub fn binary_search<T: SearchKey>(arr: &[T], target: T, order: SortOrder) -> SearchResult {
+    check_sorted_input(arr, order)?;
+    let (mut low, mut high) = (0, arr.len() as isize - 1);
+    while low <= high {
+        let mid = (low + high) / 2;
+        match order.cmp(arr[mid as usize], target) {
+            Ordering::Equal => return Ok(Some(mid as usize)),
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid - 1,
+        }
+    }
+    Ok(None)
+}
+
+//binary_search that also returns how many elements it compared with target, one per loop iteration
+pub fn binary_search_counted<T: SearchKey>(arr: &[T], target: T, order: SortOrder) -> (SearchResult, usize) {
+    if let Err(e) = check_sorted_input(arr, order) {
+        return (Err(e), 0);
+    }
+    count_comparisons(arr, target, order)
+}
+
+//binary_search_counted after the input checks, so worst_case_hit checks the array once rather than per value
+fn count_comparisons<T: SearchKey>(arr: &[T], target: T, order: SortOrder) -> (SearchResult, usize) {
+    let (mut low, mut high) = (0, arr.len() as isize - 1);
+    let mut comparisons = 0;
+    while low <= high {
+        let mid = (low + high) / 2;
+        comparisons += 1;
+        match order.cmp(arr[mid as usize], target) {
+            Ordering::Equal => return (Ok(Some(mid as usize)), comparisons),
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid - 1,
+        }
+    }
+    (Ok(None), comparisons)
+}
+
+//Most comparisons binary_search makes on n elements: floor(log2 n) + 1, the depth of its implicit tree.
+//0 for an empty array
+pub fn max_comparisons(n: usize) -> usize {
+    (usize::BITS - n.leading_zeros()) as usize
+}
+
+//The element value whose successful search takes the most comparisons, with that count. Every distinct value
+//is searched, so with duplicates (which a search can hit early) this is still the true worst case
+pub fn worst_case_hit<T: SearchKey>(arr: &[T], order: SortOrder) -> Option<(T, usize)> {
+    check_sorted_input(arr, order).ok()?;
+    let mut worst: Option<(T, usize)> = None;
+    for (i, &v) in arr.iter().enumerate() {
+        if i > 0 && arr[i - 1] == v {
+            continue;
+        }
+        let (_, comparisons) = count_comparisons(arr, v, order);
+        if worst.is_none_or(|(_, most)| comparisons > most) {
+            worst = Some((v, comparisons));
+        }
+    }
+    worst
+}
+
+//Jump search: steps of sqrt(n) until an element no longer sorts before target, then scans that block
+pub fn jump_search<T: SearchKey>(arr: &[T], target: T, order: SortOrder) -> SearchResult {
+    check_sorted_input(arr, order)?;
+    let n = arr.len();
+    let step = (n as f64).sqrt() as usize;
+    let mut prev = 0;
+    while prev < n && order.cmp(arr[prev.min(n - 1)], target) == Ordering::Less {
+        prev += step;
+    }
+    let start = prev.saturating_sub(step);
+    Ok(arr[start..(prev + 1).min(n)].iter().position(|&v| v == target).map(|i| start + i))
+}
+
+//Ranges this short are scanned by adaptive_jump_search instead of jumped through again
+const ADAPTIVE_LINEAR_BLOCK: usize = 16;
+
+//Adaptive jump search: starts at target's interpolated position (its key's share of the range's value span, as in
+//interpolation search), then jumps from there toward target in blocks of sqrt(range) until a block brackets it,
+//and repeats inside that block with the smaller stride. On uniform data the estimate is close and one or two jumps
+//do; on clustered data a poor estimate costs sqrt-sized jumps like jump_search instead of interpolation's O(n)
+//crawl. The final ADAPTIVE_LINEAR_BLOCK elements or fewer are scanned
+pub fn adaptive_jump_search<T: SearchKey>(arr: &[T], target: T, order: SortOrder) -> SearchResult {
+    check_sorted_input(arr, order)?;
+    let key = |v: T| order.key(v);
+    let target_key = key(target);
+    let (mut low, mut high) = (0usize, arr.len() - 1);
+
+    while high - low + 1 > ADAPTIVE_LINEAR_BLOCK {
+        let (low_key, high_key) = (key(arr[low]), key(arr[high]));
+        if target_key < low_key || target_key > high_key {
+            return Ok(None);
+        }
+        if low_key == high_key {
+            return Ok(Some(low));
+        }
+        let pos = low + ((high - low) as i128 * (target_key - low_key) / (high_key - low_key)) as usize;
+        let step = ((high - low + 1) as f64).sqrt() as usize;
+        match key(arr[pos]).cmp(&target_key) {
+            Ordering::Equal => return Ok(Some(pos)),
+            //arr[high] >= target, so pos < high: jump right until an element no longer sorts before target
+            Ordering::Less => {
+                let (mut from, mut next) = (pos + 1, pos + step);
+                while next < high && key(arr[next]) < target_key {
+                    from = next + 1;
+                    next += step;
+                }
+                low = from;
+                high = next.min(high);
+            }
+            //arr[low] <= target, so pos > low: jump left until an element no longer sorts after target
+            Ordering::Greater => {
+                let (mut to, mut next) = (pos - 1, pos.saturating_sub(step).max(low));
+                while next > low && key(arr[next]) > target_key {
+                    to = next - 1;
+                    next = next.saturating_sub(step).max(low);
+                }
+                low = next;
+                high = to;
+            }
+        }
+    }
+    Ok(arr[low..=high].iter().position(|&v| v == target).map(|i| low + i))
+}
+
+//Probe position is computed in i128 so the 64-bit key differences can't overflow
+//Values go through `order.key`, which increases along the array for either sort order
+//Leaving the loop early (target outside the remaining range, or below arr[0]) is a genuine miss
+pub fn interpolation_search<T: SearchKey>(arr: &[T], target: T, order: SortOrder) -> SearchResult {
+    check_sorted_input(arr, order)?;
+    let mut low = 0usize;
+    let mut high = arr.len() - 1;
+    let key = |v: T| order.key(v);
+    let target_key = key(target);
+
+    while low <= high && key(arr[low]) <= target_key && key(arr[high]) >= target_key {
+        if arr[high] == arr[low] {
+            if arr[low] == target {
+                return Ok(Some(low));
+            } else {
+                return Ok(None);
+            }
+        }
+        let pos = low + ((high - low) as i128 *
+            (target_key - key(arr[low])) / (key(arr[high]) - key(arr[low]))) as usize;
+        if arr[pos] == target {
+            return Ok(Some(pos));
+        } else if key(arr[pos]) < target_key {
+            low = pos + 1;
+        } else {
+            if pos == 0 { break; }
+            high = pos - 1;
+        }
+    }
+    Ok(None)
+}
+
+//Index of the first element >= target (arr.len() if every element is smaller), arr ascending
+pub fn lower_bound<T: SearchKey>(arr: &[T], target: T) -> usize {
+    let (mut low, mut high) = (0, arr.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if arr[mid] < target {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+//Index of the first element > target (arr.len() if none is larger), arr ascending
+pub fn upper_bound<T: SearchKey>(arr: &[T], target: T) -> usize {
+    let (mut low, mut high) = (0, arr.len());
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if arr[mid] <= target {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+    low
+}
+
+//Number of elements in lo..=hi with two binary searches, O(log n), arr ascending
+//An empty range (lo > hi) counts 0, a range outside the array's values counts 0 through the bounds meeting
+pub fn count_in_range<T: SearchKey>(arr: &[T], lo: T, hi: T) -> usize {
+    if lo > hi {
+        return 0;
+    }
+    upper_bound(arr, hi) - lower_bound(arr, lo)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    type Search = fn(&[i32], i32, SortOrder) -> SearchResult;
+
+    const SEARCHES: [Search; 4] = [binary_search, jump_search, interpolation_search, adaptive_jump_search];
+
+    //Endpoints in order, so only the debug scan can tell
+    #[test]
+    #[cfg(debug_assertions)]
+    fn unsorted_middle_is_an_error_in_debug_builds() {
+        for search in SEARCHES {
+            assert_eq!(search(&[1, 5, 3, 9], 3, SortOrder::Ascending), Err(SearchError::Unsorted));
+            assert_eq!(search(&[9, 3, 5, 1], 5, SortOrder::Descending), Err(SearchError::Unsorted));
+        }
+    }
+
+    #[test]
+    fn unsorted_endpoints_are_an_error_in_every_build() {
+        for search in SEARCHES {
+            assert_eq!(search(&[9, 3, 5, 1], 3, SortOrder::Ascending), Err(SearchError::Unsorted));
+            assert_eq!(search(&[1, 3, 5, 9], 3, SortOrder::Descending), Err(SearchError::Unsorted));
+        }
+    }
+
+    #[test]
+    fn check_sorted_follows_the_order() {
+        assert!(check_sorted(&[1, 3, 3, 9], SortOrder::Ascending));
+        assert!(!check_sorted(&[1, 5, 3, 9], SortOrder::Ascending));
+        assert!(check_sorted(&[9, 3, 3, 1], SortOrder::Descending));
+        assert!(!check_sorted(&[1, 3, 3, 9], SortOrder::Descending));
+        assert!(check_sorted::<i32>(&[], SortOrder::Descending));
+    }
+
+    #[test]
+    fn genuine_miss_is_ok_none() {
+        let arr = [1, 3, 5, 9];
+        for search in SEARCHES {
+            for target in [0, 4, 10] {
+                assert_eq!(search(&arr, target, SortOrder::Ascending), Ok(None));
+            }
+            assert_eq!(search(&arr, 5, SortOrder::Ascending), Ok(Some(2)));
+            assert_eq!(search(&[], 5, SortOrder::Ascending), Err(SearchError::EmptyInput));
+        }
+        assert_eq!(linear_search(&arr, 4, SortOrder::Ascending), Ok(None));
+        assert_eq!(binary_search(&arr, 4, SortOrder::Ascending).found(), None);
+    }
+
+    #[test]
+    fn descending_array_present_and_absent_targets() {
+        let arr = [90, 70, 70, 50, 30, 10];
+        for search in SEARCHES {
+            for (i, &v) in arr.iter().enumerate() {
+                let found = search(&arr, v, SortOrder::Descending).unwrap().unwrap();
+                assert_eq!(arr[found], v, "index {}", i);
+            }
+            for target in [100, 80, 60, 20, 0] {
+                assert_eq!(search(&arr, target, SortOrder::Descending), Ok(None));
+            }
+        }
+        assert_eq!(linear_search(&arr, 30, SortOrder::Descending), Ok(Some(4)));
+    }
+
+    //Every element, the gaps next to each one and both ends: adaptive jump search must agree with binary search
+    //on whether the target is there, and land on an equal element when it is
+    fn assert_adaptive_matches_binary(arr: &[i64], order: SortOrder) {
+        let mut targets: Vec<i64> = arr.iter().flat_map(|&v| [v - 1, v, v + 1]).collect();
+        targets.extend([i64::MIN, i64::MAX]);
+        for target in targets {
+            let expected = binary_search(arr, target, order).unwrap();
+            let found = adaptive_jump_search(arr, target, order).unwrap();
+            assert_eq!(found.is_some(), expected.is_some(), "target {}", target);
+            if let Some(i) = found {
+                assert_eq!(arr[i], target);
+            }
+        }
+    }
+
+    #[test]
+    fn adaptive_jump_matches_binary_on_uniform_and_clustered_data() {
+        let uniform = crate::generate_sorted_range_array(1_000, 0i64, 1_000_000, SortOrder::Ascending, Some(7)).unwrap();
+        //Dense runs of duplicates far apart, plus a lone outlier that skews every position estimate
+        let mut clustered: Vec<i64> = (0..1_000).map(|i| (i / 200) * 1_000_000_000 + (i % 200) / 4).collect();
+        clustered.push(i64::MAX / 2);
+        for arr in [uniform, clustered, vec![42], vec![5; 100]] {
+            assert_adaptive_matches_binary(&arr, SortOrder::Ascending);
+            let mut desc = arr.clone();
+            desc.reverse();
+            assert_adaptive_matches_binary(&desc, SortOrder::Descending);
+        }
+    }
+
+    #[test]
+    fn max_comparisons_is_floor_log2_plus_one() {
+        assert_eq!(max_comparisons(0), 0);
+        assert_eq!(max_comparisons(1), 1);
+        for pow in 1..20 {
+            assert_eq!(max_comparisons(1 << pow), pow + 1);
+            assert_eq!(max_comparisons((1 << pow) - 1), pow);
+        }
+    }
+
+    #[test]
+    fn worst_case_hit_reaches_the_bound() {
+        assert_eq!(worst_case_hit::<i32>(&[], SortOrder::Ascending), None);
+        assert_eq!(binary_search_counted::<i32>(&[], 1, SortOrder::Ascending), (Err(SearchError::EmptyInput), 0));
+        assert_eq!(worst_case_hit(&[7], SortOrder::Ascending), Some((7, 1)));
+        let sizes = (1..=40i32).chain((1..=10).map(|pow| 1 << pow));
+        for n in sizes {
+            let arr: Vec<i32> = (0..n).map(|v| v * 2).collect();
+            let bound = max_comparisons(arr.len());
+            let (value, comparisons) = worst_case_hit(&arr, SortOrder::Ascending).unwrap();
+            assert_eq!(comparisons, bound, "n = {}", n);
+            assert_eq!(binary_search_counted(&arr, value, SortOrder::Ascending).1, bound);
+            //No search, hit or miss, goes deeper than the bound
+            for target in -1..=2 * n {
+                let (result, comparisons) = binary_search_counted(&arr, target, SortOrder::Ascending);
+                assert_eq!(result, binary_search(&arr, target, SortOrder::Ascending));
+                assert!(comparisons <= bound && comparisons >= 1, "n = {} target {}", n, target);
+            }
+        }
+    }
+
+    #[test]
+    fn linear_search_within_times_out_found_and_not_found() {
+        let arr: Vec<i64> = (0..4 * TIMEOUT_CHECK_EVERY as i64).collect();
+        //A zero budget is spent after the first block, so an absent target stops there instead of scanning on
+        assert_eq!(linear_search_within(&arr, -1, Duration::ZERO), BoundedSearch::TimedOut { scanned: TIMEOUT_CHECK_EVERY });
+        assert_eq!(linear_search_within(&arr, 5, Duration::ZERO), BoundedSearch::Found(5));
+        let plenty = Duration::from_secs(60);
+        assert_eq!(linear_search_within(&arr, arr[arr.len() - 1], plenty), BoundedSearch::Found(arr.len() - 1));
+        assert_eq!(linear_search_within(&arr, -1, plenty), BoundedSearch::NotFound);
+        //The last block has nothing left to give up on
+        assert_eq!(linear_search_within(&arr[..10], -1, Duration::ZERO), BoundedSearch::NotFound);
+        assert_eq!(linear_search_within::<i64>(&[], 1, Duration::ZERO), BoundedSearch::NotFound);
+    }
+}
@@ -0,0 +1,361 @@
+//Shared pieces of the search benchmarks: the element type and the input array generation
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt::{Debug, Display};
+use std::str::FromStr;
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+//Random value generation
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand::distributions::uniform::SampleUniform;
+
+pub mod algorithms;
+pub mod keyed;
+pub mod persist;
+pub mod probes;
+
+//Element type of a benchmark array
+//Values are drawn from LOW..HIGH, the not-found probes sit just outside that range
+pub trait SearchKey: Copy + Ord + Debug + Display + FromStr + SampleUniform {
+    const NAME: &'static str;
+    const LOW: Self;
+    const HIGH: Self;
+
+    //Lossless widening, for arithmetic that can overflow Self (interpolation, distances)
+    fn widen(self) -> i128;
+    //Inverse of widen, only called with values that fit in Self
+    fn narrow(v: i128) -> Self;
+
+    //Fixed-width little-endian encoding, used by the array cache files
+    const BYTES: usize;
+    fn write_le(self, out: &mut Vec<u8>);
+    fn read_le(bytes: &[u8]) -> Self;
+
+    //Probe smaller than every generated value
+    fn below_range() -> Self {
+        Self::narrow(Self::LOW.widen() - 950)
+    }
+
+    //Probe larger than every generated value
+    fn above_range() -> Self {
+        Self::narrow(Self::HIGH.widen() + 6)
+    }
+}
+
+macro_rules! impl_search_key {
+    ($t:ty, $low:expr, $high:expr) => {
+        impl SearchKey for $t {
+            const NAME: &'static str = stringify!($t);
+            const LOW: Self = $low;
+            const HIGH: Self = $high;
+
+            fn widen(self) -> i128 {
+                self as i128
+            }
+
+            fn narrow(v: i128) -> Self {
+                v as $t
+            }
+
+            const BYTES: usize = std::mem::size_of::<$t>();
+
+            fn write_le(self, out: &mut Vec<u8>) {
+                out.extend_from_slice(&self.to_le_bytes());
+            }
+
+            fn read_le(bytes: &[u8]) -> Self {
+                <$t>::from_le_bytes(bytes.try_into().expect("slice of BYTES length"))
+            }
+        }
+    };
+}
+
+//i32 keeps the original 1000..10000 range so results stay comparable with the Python runs
+impl_search_key!(i32, 1000, 10000);
+//The 64-bit types use a wide range ending just below the type's max
+impl_search_key!(i64, 1_000_000_000_000_000_000, i64::MAX - 1000);
+impl_search_key!(u64, 10_000_000_000_000_000_000, u64::MAX - 1000);
+
+//Element type picked on the command line
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Dtype {
+    I32,
+    I64,
+    U64,
+}
+
+impl Dtype {
+    pub fn parse(s: &str) -> Option<Dtype> {
+        match s {
+            "i32" => Some(Dtype::I32),
+            "i64" => Some(Dtype::I64),
+            "u64" => Some(Dtype::U64),
+            _ => None,
+        }
+    }
+}
+
+//Order of the benchmark array; the order-aware searches flip their comparisons for Descending
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    Ascending,
+    Descending,
+}
+
+impl SortOrder {
+    pub fn parse(s: &str) -> Option<SortOrder> {
+        match s {
+            "asc" => Some(SortOrder::Ascending),
+            "desc" => Some(SortOrder::Descending),
+            _ => None,
+        }
+    }
+
+    //Compares as if the array were ascending: Less means `a` sits before `b`
+    pub fn cmp<T: SearchKey>(self, a: T, b: T) -> Ordering {
+        match self {
+            SortOrder::Ascending => a.cmp(&b),
+            SortOrder::Descending => b.cmp(&a),
+        }
+    }
+
+    //Widened value that increases along the array in either order, for interpolation arithmetic
+    pub fn key<T: SearchKey>(self, v: T) -> i128 {
+        match self {
+            SortOrder::Ascending => v.widen(),
+            SortOrder::Descending => -v.widen(),
+        }
+    }
+
+    pub fn is_sorted<T: SearchKey>(self, arr: &[T]) -> bool {
+        arr.windows(2).all(|w| self.cmp(w[0], w[1]) != Ordering::Greater)
+    }
+}
+
+//Untimed searches run before the measured probes, see `cold_warm`
+pub const DEFAULT_WARMUP: usize = 10;
+
+//Command line shared by the search binaries
+//  --dtype <i32|i64|u64>   element type (default i32)
+//  --warmup <n>            warmup searches before the measured probes (default DEFAULT_WARMUP)
+//  --order <asc|desc>      sort order of the generated array (default asc)
+//  --cache <path>          reuse the array saved at path, or save it there (see persist.rs)
+//  --unique <seed>         distinct values only, drawn reproducibly from seed (see generate_sorted_unique_array)
+//  --probes <v1,v2,..>     extra targets to time individually (see probes.rs)
+//  --random-probes <n>     n extra targets, alternating present and absent values
+//  --results <path>        CSV the extra probes are appended to (default probes::DEFAULT_RESULTS)
+//  --timeout-ms <ms>       give up a single search after this long (lin_search only, see keyed::linear_search_within)
+//  --crossover <path>      time binary vs interpolation search over growing arrays into this CSV (int_search only)
+//  --quiet, -q             one `label,elapsed_ms,memory_mb` line per checkpoint instead of the process block
+//                          (see bench_utils::Timer; QUIET=1 does the same)
+//  --worst-case            count the comparisons of the deepest hit and of both misses (bin_search only)
+//All also accept the `--flag=value` form; unknown arguments are ignored
+#[derive(Clone, Debug)]
+pub struct BenchArgs {
+    pub dtype: Dtype,
+    pub warmup: usize,
+    pub order: SortOrder,
+    pub cache: Option<String>,
+    pub unique: Option<u64>,
+    pub probes: Option<String>,
+    pub random_probes: Option<usize>,
+    pub results: String,
+    pub timeout: Option<Duration>,
+    pub crossover: Option<String>,
+    pub quiet: bool,
+    pub worst_case: bool,
+}
+
+impl BenchArgs {
+    pub fn from_args() -> Result<BenchArgs, String> {
+        let mut parsed = BenchArgs {
+            dtype: Dtype::I32,
+            warmup: DEFAULT_WARMUP,
+            order: SortOrder::Ascending,
+            cache: None,
+            unique: None,
+            probes: None,
+            random_probes: None,
+            results: probes::DEFAULT_RESULTS.to_string(),
+            timeout: None,
+            crossover: None,
+            quiet: false,
+            worst_case: false,
+        };
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            let (flag, inline) = match arg.split_once('=') {
+                Some((f, v)) => (f.to_string(), Some(v.to_string())),
+                None => (arg, None),
+            };
+            if matches!(flag.as_str(), "--quiet" | "-q") {
+                parsed.quiet = true;
+                continue;
+            }
+            if flag == "--worst-case" {
+                parsed.worst_case = true;
+                continue;
+            }
+            if !matches!(flag.as_str(), "--dtype" | "--warmup" | "--order" | "--cache" | "--unique" | "--probes" | "--random-probes" | "--results" | "--timeout-ms" | "--crossover") {
+                continue;
+            }
+            let value = match inline {
+                Some(v) => v,
+                None => args.next().ok_or(format!("{} needs a value", flag))?,
+            };
+            match flag.as_str() {
+                "--dtype" => parsed.dtype = Dtype::parse(&value).ok_or(format!("unknown --dtype '{}', expected i32, i64 or u64", value))?,
+                "--warmup" => parsed.warmup = value.parse().map_err(|_| format!("--warmup expects a count, got '{}'", value))?,
+                "--cache" => parsed.cache = Some(value),
+                "--probes" => parsed.probes = Some(value),
+                "--random-probes" => parsed.random_probes = Some(value.parse().map_err(|_| format!("--random-probes expects a count, got '{}'", value))?),
+                "--results" => parsed.results = value,
+                "--crossover" => parsed.crossover = Some(value),
+                "--timeout-ms" => parsed.timeout = Some(Duration::from_millis(value.parse().map_err(|_| format!("--timeout-ms expects milliseconds, got '{}'", value))?)),
+                "--unique" => parsed.unique = Some(value.parse().map_err(|_| format!("--unique expects a seed, got '{}'", value))?),
+                _ => parsed.order = SortOrder::parse(&value).ok_or(format!("unknown --order '{}', expected asc or desc", value))?,
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+pub fn generate_sorted_random_array<T: SearchKey>(n: usize, order: SortOrder) -> Vec<T> {
+    let mut rng = rand::thread_rng();
+    let mut arr: Vec<T> = (0..n).map(|_| rng.gen_range(T::LOW..T::HIGH)).collect();
+    arr.sort_by(|a, b| order.cmp(*a, *b));
+    arr
+}
+
+//n values from min..max (max excluded, duplicates allowed) sorted in `order`, reproducible when a seed is given
+//Errors when the range is empty
+pub fn generate_sorted_range_array<T: SearchKey>(n: usize, min: T, max: T, order: SortOrder, seed: Option<u64>) -> Result<Vec<T>, String> {
+    if min >= max {
+        return Err(format!("empty range {}..{}", min, max));
+    }
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut arr: Vec<T> = (0..n).map(|_| rng.gen_range(min..max)).collect();
+    arr.sort_by(|a, b| order.cmp(*a, *b));
+    Ok(arr)
+}
+
+//n distinct values from min..max (max excluded), ascending, reproducible for a given seed
+//Errors when the range holds fewer than n values
+pub fn generate_sorted_unique_array<T: SearchKey>(n: usize, min: T, max: T, seed: u64) -> Result<Vec<T>, String> {
+    let span = (max.widen() - min.widen()).max(0);
+    if n as i128 > span {
+        return Err(format!("{}..{} holds {} distinct values, {} requested", min, max, span, n));
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut arr: Vec<T> = if 2 * n as i128 <= span {
+        //Sparse: draw until n distinct values have been seen
+        let mut seen = HashSet::with_capacity(n);
+        while seen.len() < n {
+            seen.insert(rng.gen_range(min.widen()..max.widen()));
+        }
+        seen.into_iter().map(T::narrow).collect()
+    } else {
+        //Dense (span < 2n, so it fits in memory): pick n offsets without replacement
+        rand::seq::index::sample(&mut rng, span as usize, n).into_iter().map(|i| T::narrow(min.widen() + i as i128)).collect()
+    };
+    arr.sort();
+    Ok(arr)
+}
+
+//Benchmark input: duplicates allowed by default, distinct values from T::LOW..T::HIGH when a unique seed is given
+pub fn generate_array<T: SearchKey>(n: usize, order: SortOrder, unique: Option<u64>) -> Result<Vec<T>, String> {
+    let Some(seed) = unique else {
+        return Ok(generate_sorted_random_array(n, order));
+    };
+    let mut arr = generate_sorted_unique_array(n, T::LOW, T::HIGH, seed)?;
+    if order == SortOrder::Descending {
+        arr.reverse();
+    }
+    Ok(arr)
+}
+
+//Times `search` for `target` once cold, then runs `rounds` searches for targets spread across the array
+//(pulling the array and code into cache, faulting in pages) and times the same target again warm
+pub fn cold_warm<T: SearchKey, R>(arr: &[T], target: T, rounds: usize, search: impl Fn(&[T], T) -> R) -> (Duration, Duration) {
+    let start = Instant::now();
+    black_box(search(arr, black_box(target)));
+    let cold = start.elapsed();
+
+    let mut rng = rand::thread_rng();
+    for _ in 0..rounds {
+        let throwaway = arr[rng.gen_range(0..arr.len())];
+        black_box(search(arr, black_box(throwaway)));
+    }
+
+    let start = Instant::now();
+    black_box(search(arr, black_box(target)));
+    (cold, start.elapsed())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //widen/narrow and interpolation near each type's max, where Self arithmetic would overflow
+    fn assert_near_max<T: SearchKey>(max: T) {
+        let arr: Vec<T> = (0..64).rev().map(|i| T::narrow(max.widen() - i * 3)).collect();
+        assert_eq!(T::narrow(max.widen()), max);
+        assert_eq!(T::narrow(arr[0].widen()), arr[0]);
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            let mut arr = arr.clone();
+            if order == SortOrder::Descending {
+                arr.reverse();
+            }
+            for (i, &v) in arr.iter().enumerate() {
+                assert_eq!(keyed::interpolation_search(&arr, v, order), Ok(Some(i)), "{} {}", T::NAME, v);
+                assert_eq!(keyed::binary_search(&arr, v, order), Ok(Some(i)));
+            }
+            let absent = T::narrow(max.widen() - 1);
+            assert_eq!(keyed::interpolation_search(&arr, absent, order), Ok(None));
+            assert_eq!(keyed::binary_search(&arr, absent, order), Ok(None));
+        }
+    }
+
+    #[test]
+    fn search_keys_near_the_type_max() {
+        assert_near_max(i32::MAX);
+        assert_near_max(i64::MAX);
+        assert_near_max(u64::MAX);
+        assert_eq!(i64::MAX.widen() - i64::MIN.widen(), u64::MAX as i128);
+        assert_eq!(u64::MAX.widen(), u64::MAX as i128);
+    }
+
+    #[test]
+    fn probes_outside_the_range_fit_every_type() {
+        assert!(i32::below_range() < i32::LOW && i32::above_range() > i32::HIGH);
+        assert!(i64::below_range() < i64::LOW && i64::above_range() > i64::HIGH);
+        assert!(u64::below_range() < u64::LOW && u64::above_range() > u64::HIGH);
+    }
+
+    #[test]
+    fn unique_array_is_strictly_increasing_with_the_requested_length() {
+        //Sparse (rejection sampling) and dense (index sampling) paths, and a range used up exactly
+        for (n, min, max) in [(1_000usize, 0i64, 1_000_000), (900, 0, 1_000), (1_000, -500, 500), (0, 5, 6)] {
+            let arr = generate_sorted_unique_array(n, min, max, 42).unwrap();
+            assert_eq!(arr.len(), n);
+            assert!(arr.windows(2).all(|w| w[0] < w[1]));
+            assert!(arr.iter().all(|v| (min..max).contains(v)));
+        }
+        assert_eq!(generate_sorted_unique_array(100, 0i32, 1_000, 9), generate_sorted_unique_array(100, 0i32, 1_000, 9));
+        let wide = generate_sorted_unique_array(500, u64::MAX - 10_000, u64::MAX, 3).unwrap();
+        assert!(wide.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn unique_array_errors_when_the_range_is_too_small() {
+        assert!(generate_sorted_unique_array(1_001, 0i32, 1_000, 1).is_err());
+        assert!(generate_sorted_unique_array(1, 5i64, 5, 1).is_err());
+        assert!(generate_sorted_unique_array(1, 5i64, 4, 1).is_err());
+    }
+}
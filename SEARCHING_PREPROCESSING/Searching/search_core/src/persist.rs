@@ -0,0 +1,249 @@
+//Saving generated arrays to disk so large inputs (100M elements for lin_search) can be reused between runs
+//File layout: MAGIC, element type name (u8 length + bytes), element count (u64 LE), elements (LE, T::BYTES each)
+//The extension picks the compression: `.zst` (feature `zstd`), `.gz` (feature `gzip`), anything else is raw
+
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use crate::{generate_array, SearchKey, SortOrder};
+
+const MAGIC: &[u8; 8] = b"SRCHARR1";
+//Elements encoded/decoded per write/read call
+const CHUNK: usize = 64 * 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Compression {
+    None,
+    Zstd,
+    Gzip,
+}
+
+impl Compression {
+    fn from_path(path: &Path) -> Compression {
+        match path.extension().and_then(|e| e.to_str()) {
+            Some("zst") => Compression::Zstd,
+            Some("gz") => Compression::Gzip,
+            _ => Compression::None,
+        }
+    }
+}
+
+fn unsupported(c: Compression) -> io::Error {
+    let feature = match c {
+        Compression::Zstd => "zstd",
+        _ => "gzip",
+    };
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{:?} cache files need search_core built with --features search_core/{}", c, feature),
+    )
+}
+
+//Sizes from a save, for reporting the compression ratio
+#[derive(Clone, Copy, Debug)]
+pub struct SaveStats {
+    pub raw_bytes: u64,
+    pub file_bytes: u64,
+}
+
+impl SaveStats {
+    pub fn ratio(&self) -> f64 {
+        self.raw_bytes as f64 / self.file_bytes.max(1) as f64
+    }
+}
+
+fn header_len<T: SearchKey>() -> u64 {
+    (MAGIC.len() + 1 + T::NAME.len() + 8) as u64
+}
+
+fn write_array<T: SearchKey, W: Write>(w: &mut W, arr: &[T]) -> io::Result<()> {
+    w.write_all(MAGIC)?;
+    w.write_all(&[T::NAME.len() as u8])?;
+    w.write_all(T::NAME.as_bytes())?;
+    w.write_all(&(arr.len() as u64).to_le_bytes())?;
+    let mut buf = Vec::with_capacity(CHUNK * T::BYTES);
+    for chunk in arr.chunks(CHUNK) {
+        buf.clear();
+        for &v in chunk {
+            v.write_le(&mut buf);
+        }
+        w.write_all(&buf)?;
+    }
+    Ok(())
+}
+
+fn read_array<T: SearchKey, R: Read>(r: &mut R) -> io::Result<Vec<T>> {
+    let invalid = |msg: String| io::Error::new(io::ErrorKind::InvalidData, msg);
+    let mut magic = [0u8; 8];
+    r.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid(String::from("not an array cache file")));
+    }
+    let mut name_len = [0u8; 1];
+    r.read_exact(&mut name_len)?;
+    let mut name = vec![0u8; name_len[0] as usize];
+    r.read_exact(&mut name)?;
+    if name != T::NAME.as_bytes() {
+        return Err(invalid(format!("cache holds {} elements, expected {}", String::from_utf8_lossy(&name), T::NAME)));
+    }
+    let mut count = [0u8; 8];
+    r.read_exact(&mut count)?;
+    let count = u64::from_le_bytes(count) as usize;
+
+    let mut arr = Vec::with_capacity(count);
+    let mut buf = vec![0u8; CHUNK * T::BYTES];
+    while arr.len() < count {
+        let n = (count - arr.len()).min(CHUNK);
+        let bytes = &mut buf[..n * T::BYTES];
+        r.read_exact(bytes)?;
+        arr.extend(bytes.chunks_exact(T::BYTES).map(T::read_le));
+    }
+    Ok(arr)
+}
+
+pub fn save_array<T: SearchKey>(path: impl AsRef<Path>, arr: &[T]) -> io::Result<SaveStats> {
+    let path = path.as_ref();
+    let compression = Compression::from_path(path);
+    let file = BufWriter::new(File::create(path)?);
+    #[allow(unreachable_patterns)]
+    match compression {
+        Compression::None => {
+            let mut w = file;
+            write_array(&mut w, arr)?;
+            w.flush()?;
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => {
+            let mut enc = zstd::Encoder::new(file, 3)?;
+            write_array(&mut enc, arr)?;
+            enc.finish()?.flush()?;
+        }
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => {
+            let mut enc = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            write_array(&mut enc, arr)?;
+            enc.finish()?.flush()?;
+        }
+        other => {
+            drop(file);
+            let _ = std::fs::remove_file(path);
+            return Err(unsupported(other));
+        }
+    }
+    Ok(SaveStats {
+        raw_bytes: header_len::<T>() + (arr.len() * T::BYTES) as u64,
+        file_bytes: std::fs::metadata(path)?.len(),
+    })
+}
+
+pub fn load_array<T: SearchKey>(path: impl AsRef<Path>) -> io::Result<Vec<T>> {
+    let path = path.as_ref();
+    let file = BufReader::new(File::open(path)?);
+    #[allow(unreachable_patterns)]
+    match Compression::from_path(path) {
+        Compression::None => {
+            let mut r = file;
+            read_array(&mut r)
+        }
+        #[cfg(feature = "zstd")]
+        Compression::Zstd => read_array(&mut zstd::Decoder::with_buffer(file)?),
+        #[cfg(feature = "gzip")]
+        Compression::Gzip => read_array(&mut flate2::read::GzDecoder::new(file)),
+        other => Err(unsupported(other)),
+    }
+}
+
+//Loads the array from `cache` when it holds n elements in the requested order (and distinct, for `unique`),
+//otherwise generates a fresh one with generate_array and writes it to `cache` for next time.
+//No cache path means always generate
+pub fn load_or_generate<T: SearchKey>(n: usize, order: SortOrder, cache: Option<&str>, unique: Option<u64>) -> Result<Vec<T>, String> {
+    let Some(path) = cache else {
+        return generate_array(n, order, unique);
+    };
+    match load_array::<T>(path) {
+        Ok(arr) if arr.len() == n && order.is_sorted(&arr) && (unique.is_none() || arr.windows(2).all(|w| w[0] != w[1])) => {
+            println!("Loaded {} elements from {}", arr.len(), path);
+            return Ok(arr);
+        }
+        Ok(_) => println!("{} doesn't match the requested size/order/uniqueness, regenerating", path),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => println!("Could not load {} ({}), regenerating", path, e),
+    }
+    let arr = generate_array(n, order, unique)?;
+    match save_array(path, &arr) {
+        Ok(stats) => println!(
+            "Saved {} elements to {} : {} bytes raw , {} bytes on disk , ratio {:.2}x",
+            arr.len(), path, stats.raw_bytes, stats.file_bytes, stats.ratio()
+        ),
+        Err(e) => eprintln!("Could not save {} ({})", path, e),
+    }
+    Ok(arr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("search_core-persist-{}-{}", std::process::id(), name))
+    }
+
+    //Saves arr to `name` and loads it back, removing the file
+    fn round_trip<T: SearchKey>(name: &str, arr: &[T]) -> io::Result<(Vec<T>, SaveStats)> {
+        let path = scratch(name);
+        let result = save_array(&path, arr).and_then(|stats| Ok((load_array::<T>(&path)?, stats)));
+        let _ = std::fs::remove_file(&path);
+        result
+    }
+
+    fn sample() -> Vec<i64> {
+        //Long runs, so the compressed files come out smaller than the raw one
+        (0..3 * CHUNK as i64 + 17).map(|i| i / 100 * 7).collect()
+    }
+
+    #[test]
+    fn raw_round_trip() {
+        let arr = sample();
+        let (loaded, stats) = round_trip("raw.bin", &arr).unwrap();
+        assert_eq!(loaded, arr);
+        assert_eq!(stats.file_bytes, stats.raw_bytes);
+        assert_eq!(round_trip::<u64>("empty.bin", &[]).unwrap().0, Vec::<u64>::new());
+    }
+
+    #[test]
+    fn wrong_element_type_is_rejected() {
+        let path = scratch("typed.bin");
+        save_array(&path, &[1i32, 2, 3]).unwrap();
+        let loaded = load_array::<i64>(&path);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(loaded.unwrap_err().kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_round_trip_matches_raw() {
+        let arr = sample();
+        let (loaded, stats) = round_trip("zstd.bin.zst", &arr).unwrap();
+        assert_eq!(loaded, round_trip("zstd-raw.bin", &arr).unwrap().0);
+        assert!(stats.ratio() > 1.0);
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn gzip_round_trip_matches_raw() {
+        let arr = sample();
+        let (loaded, stats) = round_trip("gzip.bin.gz", &arr).unwrap();
+        assert_eq!(loaded, round_trip("gzip-raw.bin", &arr).unwrap().0);
+        assert!(stats.ratio() > 1.0);
+    }
+
+    //Without the feature the codec is refused and no half-written file is left behind
+    #[cfg(not(feature = "zstd"))]
+    #[test]
+    fn zstd_without_the_feature_is_unsupported() {
+        let path = scratch("nozstd.bin.zst");
+        assert_eq!(save_array(&path, &[1i64]).unwrap_err().kind(), io::ErrorKind::Unsupported);
+        assert!(!path.exists());
+    }
+}
@@ -0,0 +1,135 @@
+//User-chosen probe targets (--probes / --random-probes) on top of the fixed first/last/middle/<MIN/>MAX set
+//Each probe is timed on its own and appended as one row to the results CSV (--results, default probe_results.csv)
+
+use std::fs::OpenOptions;
+use std::hint::black_box;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use std::time::Instant;
+
+use rand::Rng;
+
+use crate::{BenchArgs, SearchKey, SortOrder};
+
+pub const DEFAULT_RESULTS: &str = "probe_results.csv";
+const HEADER: &str = "algorithm,dtype,order,array_size,target,present,found_index,time_ns";
+//Random in-range draws tried before falling back to a value outside the generated range
+const MISS_ATTEMPTS: usize = 32;
+
+//Whether target occurs in arr, which is sorted in `order`
+fn contains<T: SearchKey>(arr: &[T], target: T, order: SortOrder) -> bool {
+    arr.binary_search_by(|v| order.cmp(*v, target)).is_ok()
+}
+
+//A value not in arr: a random in-range value when one turns up, otherwise one just outside LOW..HIGH
+//(with i32's 9000 possible values a large array contains all of them)
+fn random_absent<T: SearchKey>(arr: &[T], order: SortOrder, rng: &mut impl Rng) -> T {
+    for _ in 0..MISS_ATTEMPTS {
+        let v = rng.gen_range(T::LOW..T::HIGH);
+        if !contains(arr, v, order) {
+            return v;
+        }
+    }
+    if rng.gen_bool(0.5) {
+        T::narrow(rng.gen_range(T::below_range().widen()..T::LOW.widen()))
+    } else {
+        T::narrow(rng.gen_range(T::HIGH.widen() + 1..=T::HIGH.widen() + 1000))
+    }
+}
+
+//`n` targets alternating between values taken from arr and values missing from it
+pub fn random_probes<T: SearchKey>(arr: &[T], n: usize, order: SortOrder) -> Vec<T> {
+    let mut rng = rand::thread_rng();
+    (0..n)
+        .map(|i| if i % 2 == 0 && !arr.is_empty() { arr[rng.gen_range(0..arr.len())] } else { random_absent(arr, order, &mut rng) })
+        .collect()
+}
+
+//Probe targets requested on the command line: the --probes list followed by --random-probes targets
+pub fn requested_probes<T: SearchKey>(args: &BenchArgs, arr: &[T]) -> Result<Vec<T>, String> {
+    let mut probes = Vec::new();
+    if let Some(list) = &args.probes {
+        for v in list.split(',').map(str::trim).filter(|v| !v.is_empty()) {
+            probes.push(v.parse::<T>().map_err(|_| format!("--probes value '{}' is not a valid {}", v, T::NAME))?);
+        }
+    }
+    if let Some(n) = args.random_probes {
+        probes.extend(random_probes(arr, n, args.order));
+    }
+    Ok(probes)
+}
+
+fn append_rows(path: &str, rows: &[String]) -> io::Result<()> {
+    let exists = Path::new(path).exists();
+    let mut w = BufWriter::new(OpenOptions::new().create(true).append(true).open(path)?);
+    if !exists {
+        writeln!(w, "{}", HEADER)?;
+    }
+    for row in rows {
+        writeln!(w, "{}", row)?;
+    }
+    w.flush()
+}
+
+//Times `search` once per requested probe, prints each result and appends them to the results CSV.
+//Does nothing and returns false when neither --probes nor --random-probes was given
+pub fn run_probes<T: SearchKey>(args: &BenchArgs, arr: &[T], algorithm: &str, search: impl Fn(&[T], T) -> Option<usize>) -> bool {
+    let probes = match requested_probes(args, arr) {
+        Ok(p) if p.is_empty() => return false,
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("{}", e);
+            return false;
+        }
+    };
+    let order = match args.order {
+        SortOrder::Ascending => "asc",
+        SortOrder::Descending => "desc",
+    };
+    let mut rows = Vec::with_capacity(probes.len());
+    for target in probes {
+        let present = contains(arr, target, args.order);
+        let start = Instant::now();
+        let found = black_box(search(arr, black_box(target)));
+        let elapsed = start.elapsed();
+        println!("Probe {} : {:?} , present : {} , {:#?}", target, found, present, elapsed);
+        rows.push(format!(
+            "{},{},{},{},{},{},{},{}",
+            algorithm, T::NAME, order, arr.len(), target, present,
+            found.map(|i| i.to_string()).unwrap_or_default(), elapsed.as_nanos()
+        ));
+    }
+    match append_rows(&args.results, &rows) {
+        Ok(()) => println!("Appended {} probe rows to {}", rows.len(), args.results),
+        Err(e) => eprintln!("Could not write {} ({})", args.results, e),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn random_probes_alternate_hits_and_misses() {
+        for order in [SortOrder::Ascending, SortOrder::Descending] {
+            let arr = crate::generate_sorted_random_array::<i64>(1_000, order);
+            let probes = random_probes(&arr, 50, order);
+            assert_eq!(probes.len(), 50);
+            for (i, &p) in probes.iter().enumerate() {
+                assert_eq!(contains(&arr, p, order), i % 2 == 0, "probe {} = {}", i, p);
+            }
+        }
+    }
+
+    //Every i32 value in LOW..HIGH is present, so the misses have to come from outside the range
+    #[test]
+    fn misses_fall_outside_a_full_range() {
+        let arr: Vec<i32> = (i32::LOW..i32::HIGH).collect();
+        let probes = random_probes(&arr, 20, SortOrder::Ascending);
+        for &p in probes.iter().skip(1).step_by(2) {
+            assert!(!contains(&arr, p, SortOrder::Ascending) && !(i32::LOW..i32::HIGH).contains(&p));
+        }
+        assert!(random_probes::<i32>(&[], 3, SortOrder::Ascending).iter().all(|&p| !contains(&[], p, SortOrder::Ascending)));
+    }
+}
@@ -0,0 +1,172 @@
+#![allow(unused)]
+#![allow(clippy::inconsistent_digit_grouping)]
+
+const ARRAY_SIZE : usize = 10_00_000;
+
+use std::time::{Duration, Instant};
+use bench_utils::Timer;
+
+//Random value generation
+use rand::Rng;
+use rayon::slice::ParallelSliceMut;
+
+use search_core::{BenchArgs, Dtype, SearchKey};
+
+//LSD radix sort, one 8-bit digit per pass. Flipping the sign bit maps i32 onto u32 in the same order, so
+//negative values land before positive ones. A pass where every value has the same digit would move nothing
+//and is skipped; the default 1000..10000 values only differ in the low two bytes. Returns the passes made
+fn radix_sort(arr: &mut [i32]) -> usize {
+    let digit = |v: i32, shift: u32| ((v as u32 ^ 0x8000_0000) >> shift) as usize & 0xff;
+    let mut buf = vec![0i32; arr.len()];
+    let (mut src, mut dst): (&mut [i32], &mut [i32]) = (arr, &mut buf);
+    let mut passes = 0;
+    for shift in (0..32).step_by(8) {
+        let mut counts = [0usize; 256];
+        for &v in src.iter() {
+            counts[digit(v, shift)] += 1;
+        }
+        if counts.contains(&src.len()) {
+            continue;
+        }
+        //Counts -> first slot of each digit; equal digits keep their order, which later passes rely on
+        let mut next = [0usize; 256];
+        let mut total = 0;
+        for (slot, &count) in next.iter_mut().zip(&counts) {
+            *slot = total;
+            total += count;
+        }
+        for &v in src.iter() {
+            let d = digit(v, shift);
+            dst[next[d]] = v;
+            next[d] += 1;
+        }
+        std::mem::swap(&mut src, &mut dst);
+        passes += 1;
+    }
+    //After an odd number of passes the result sits in the buffer
+    if passes % 2 == 1 {
+        dst.copy_from_slice(src);
+    }
+    passes
+}
+
+//Every variant in the order they are timed and compared; sort is the one array generation uses
+const VARIANTS: [&str; 4] = ["sort", "sort_unstable", "par_sort_unstable", "radix_sort"];
+
+fn sort_with(variant: &str, arr: &mut [i32]) {
+    match variant {
+        "sort" => arr.sort(),
+        "sort_unstable" => arr.sort_unstable(),
+        "par_sort_unstable" => arr.par_sort_unstable(),
+        _ => {
+            radix_sort(arr);
+        }
+    }
+}
+
+//Inputs the benchmark data never holds: both extremes, negatives, duplicates, already sorted, reversed,
+//one element and empty. Returns the names of the variants that disagree with sort on any of them
+fn edge_case_mismatches() -> Vec<&'static str> {
+    let cases: Vec<Vec<i32>> = vec![
+        vec![i32::MAX, 0, i32::MIN, -1, 1, i32::MIN, -256, 255, 256, -257],
+        (0..1000).map(|i| (i % 7) - 3).collect(),
+        (-500..500).collect(),
+        (-500..500).rev().collect(),
+        vec![42],
+        vec![],
+    ];
+    VARIANTS.iter().copied().filter(|&variant| {
+        cases.iter().any(|case| {
+            let mut expected = case.clone();
+            expected.sort();
+            let mut sorted = case.clone();
+            sort_with(variant, &mut sorted);
+            sorted != expected
+        })
+    }).collect()
+}
+
+fn main() {
+    let args = BenchArgs::from_args().unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(2);
+    });
+    if args.dtype != Dtype::I32 {
+        eprintln!("sort_bench only sorts i32 (the radix sort is written for it), ignoring --dtype");
+    }
+
+    // Start timer
+    let mut timer = Timer::new().with_quiet(args.quiet);
+    timer.process_info("Before Sort Bench");
+    println!("Element type : {}", <i32 as SearchKey>::NAME);
+    println!("Rayon threads : {}", rayon::current_num_threads());
+
+    //=====================================================================================================
+    //The same values generate_sorted_random_array draws, left unsorted
+    let mut rng = rand::thread_rng();
+    let input: Vec<i32> = (0..ARRAY_SIZE).map(|_| rng.gen_range(i32::LOW..i32::HIGH)).collect();
+    timer.process_info("Array Generation");
+    //=====================================================================================================
+    //Each variant sorts its own copy of the same input; the copy isn't timed
+    let mut results: Vec<(&str, Duration, Vec<i32>)> = Vec::new();
+    for variant in VARIANTS {
+        let mut arr = input.clone();
+        let start = Instant::now();
+        sort_with(variant, &mut arr);
+        let elapsed = start.elapsed();
+        println!("{} : {:#?} for {} elements", variant, elapsed, ARRAY_SIZE);
+        timer.process_info(variant);
+        results.push((variant, elapsed, arr));
+    }
+    //=====================================================================================================
+    println!("=================================");
+    let reference = &results[0].2;
+    let differing: Vec<&str> = results.iter().filter(|(_, _, arr)| arr != reference).map(|(name, _, _)| *name).collect();
+    let sorted = reference.windows(2).all(|w| w[0] <= w[1]);
+    println!("Outputs identical : {} , sorted : {}", differing.is_empty(), sorted);
+    let edge_failures = edge_case_mismatches();
+    println!("Edge cases : {}", if edge_failures.is_empty() { "all variants agree".to_string() } else { format!("{:?} disagree", edge_failures) });
+    let mut probe = input.clone();
+    println!("Radix passes : {} of 4", radix_sort(&mut probe));
+    //Relative to sort, the cost every generated benchmark array pays before the first search
+    let sort_time = results[0].1.as_secs_f64();
+    for (name, elapsed, _) in &results[1..] {
+        println!("Speedup over sort : {} {:.2}x", name, sort_time / elapsed.as_secs_f64());
+    }
+    if !differing.is_empty() || !sorted || !edge_failures.is_empty() {
+        std::process::exit(2);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_variant_sorts_random_input_like_sort() {
+        let mut rng = rand::thread_rng();
+        let input: Vec<i32> = (0..10_000).map(|_| rng.gen_range(i32::MIN..=i32::MAX)).collect();
+        let mut expected = input.clone();
+        expected.sort();
+        for variant in VARIANTS {
+            let mut arr = input.clone();
+            sort_with(variant, &mut arr);
+            assert_eq!(arr, expected, "{}", variant);
+        }
+    }
+
+    #[test]
+    fn edge_cases_all_agree() {
+        assert!(edge_case_mismatches().is_empty());
+    }
+
+    #[test]
+    fn radix_sort_skips_passes_with_a_single_digit() {
+        //Values in 0..256 only differ in the lowest byte
+        let mut arr: Vec<i32> = (0..256).rev().collect();
+        assert_eq!(radix_sort(&mut arr), 1);
+        assert!(arr.windows(2).all(|w| w[0] <= w[1]));
+        let mut empty: Vec<i32> = vec![];
+        assert_eq!(radix_sort(&mut empty), 0);
+    }
+}
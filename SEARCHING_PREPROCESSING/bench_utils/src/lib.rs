@@ -0,0 +1,233 @@
+//Checkpoint reporting shared by the searching and preprocessing benchmarks
+
+pub mod stats;
+
+use std::fmt;
+use std::sync::Once;
+use std::time::{Duration, Instant};
+use sysinfo::{Pid, System};
+
+//Unit memory figures are reported in; every bytes -> unit conversion goes through `from_bytes`
+//(1 KB = 1024 bytes, as sysinfo reports process memory in bytes)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryUnit {
+    KB,
+    MB,
+    GB,
+}
+
+impl MemoryUnit {
+    pub fn parse(s: &str) -> Option<MemoryUnit> {
+        match s.to_ascii_uppercase().as_str() {
+            "KB" => Some(MemoryUnit::KB),
+            "MB" => Some(MemoryUnit::MB),
+            "GB" => Some(MemoryUnit::GB),
+            _ => None,
+        }
+    }
+
+    //MEMORY_UNIT if set to KB, MB or GB, otherwise MB
+    pub fn from_env() -> MemoryUnit {
+        std::env::var("MEMORY_UNIT").ok().and_then(|s| MemoryUnit::parse(&s)).unwrap_or(MemoryUnit::MB)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MemoryUnit::KB => "KB",
+            MemoryUnit::MB => "MB",
+            MemoryUnit::GB => "GB",
+        }
+    }
+
+    pub fn bytes_per_unit(self) -> u64 {
+        match self {
+            MemoryUnit::KB => 1024,
+            MemoryUnit::MB => 1024 * 1024,
+            MemoryUnit::GB => 1024 * 1024 * 1024,
+        }
+    }
+
+    pub fn from_bytes(self, bytes: u64) -> f64 {
+        bytes as f64 / self.bytes_per_unit() as f64
+    }
+}
+
+//Why sysinfo couldn't give this process's memory, seen in sandboxes that hide /proc or the process list
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MemoryUnavailable {
+    //get_current_pid failed
+    NoPid,
+    //The process list doesn't contain our pid
+    ProcessNotFound,
+}
+
+impl fmt::Display for MemoryUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MemoryUnavailable::NoPid => write!(f, "sysinfo can't get the current pid"),
+            MemoryUnavailable::ProcessNotFound => write!(f, "sysinfo can't find the current process"),
+        }
+    }
+}
+
+impl std::error::Error for MemoryUnavailable {}
+
+//Warn on stderr the first time memory is unavailable, checkpoints after that just print "unavailable"
+fn warn_memory_unavailable(reason: MemoryUnavailable) {
+    static WARNED: Once = Once::new();
+    WARNED.call_once(|| eprintln!("Memory usage unavailable ({}), checkpoints report time only", reason));
+}
+
+//QUIET=1 (or true): every Timer starts quiet, as if the binary got --quiet
+pub fn quiet_from_env() -> bool {
+    std::env::var("QUIET").is_ok_and(|v| v == "1" || v == "true")
+}
+
+//A checkpoint label as one CSV field, quoted when it holds a comma or quote
+fn csv_field(label: &str) -> String {
+    if label.contains([',', '"', '\n']) {
+        format!("\"{}\"", label.replace('"', "\"\""))
+    } else {
+        label.to_string()
+    }
+}
+
+//Wall clock plus process memory at each benchmark checkpoint
+//Every checkpoint reports the cumulative time since start and the delta since the previous checkpoint
+//A quiet timer prints one `label,elapsed_ms,memory_mb` line per checkpoint instead of the process block
+pub struct Timer {
+    sys: System,
+    //None when sysinfo can't tell our pid
+    pid: Option<Pid>,
+    start: Instant,
+    last_checkpoint: Instant,
+    unit: MemoryUnit,
+    quiet: bool,
+}
+
+impl Timer {
+    pub fn new() -> Timer {
+        // Initialize system info
+        let mut sys = System::new_all();
+        sys.refresh_all();
+
+        // Get current process ID
+        let pid = sysinfo::get_current_pid().ok();
+        let start = Instant::now();
+        Timer { sys, pid, start, last_checkpoint: start, unit: MemoryUnit::from_env(), quiet: quiet_from_env() }
+    }
+
+    //Quiet checkpoints when `quiet` (a --quiet flag); QUIET=1 has already made the timer quiet either way
+    pub fn with_quiet(mut self, quiet: bool) -> Timer {
+        self.quiet |= quiet;
+        self
+    }
+
+    //Report memory in `unit` instead of the MEMORY_UNIT default
+    pub fn with_memory_unit(mut self, unit: MemoryUnit) -> Timer {
+        self.unit = unit;
+        self
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    //Current process memory in the timer's unit
+    pub fn memory(&mut self) -> Result<f64, MemoryUnavailable> {
+        let pid = self.pid.ok_or(MemoryUnavailable::NoPid)?;
+        self.sys.refresh_all();
+        let process = self.sys.process(pid).ok_or(MemoryUnavailable::ProcessNotFound)?;
+        Ok(self.unit.from_bytes(process.memory()))
+    }
+
+    //Print the process block for `label` and return (cumulative, delta since last checkpoint)
+    //Both come from the same clock reading, so the deltas always add up to the cumulative time
+    pub fn process_info(&mut self, label: &str) -> (Duration, Duration) {
+        if self.quiet {
+            return self.checkpoint_line(label);
+        }
+        println!("============={}================", label);
+        match self.memory() {
+            Ok(memory) => {
+                //memory() just refreshed the process list, so the lookup can't miss
+                if let Some(process) = self.pid.and_then(|pid| self.sys.process(pid)) {
+                    println!("Process name: {}", process.name());
+                    println!("Executable path: {:?}", process.exe());
+                }
+                println!("Memory usage: {:.2} {}", memory, self.unit.label());
+            }
+            Err(reason) => {
+                warn_memory_unavailable(reason);
+                println!("Memory usage: unavailable");
+            }
+        }
+        let now = Instant::now();
+        let total = now - self.start;
+        let delta = now - self.last_checkpoint;
+        self.last_checkpoint = now;
+        println!("Till -- {} : {:#?}", label, total);
+        println!("Δ since last : {:#?}", delta);
+        (total, delta)
+    }
+
+    //Quiet form of process_info: `label,elapsed_ms,memory_mb` with the cumulative time, memory in the timer's
+    //unit (MB unless MEMORY_UNIT says otherwise) and -1 when it's unavailable
+    fn checkpoint_line(&mut self, label: &str) -> (Duration, Duration) {
+        let memory = self.memory().unwrap_or_else(|reason| {
+            warn_memory_unavailable(reason);
+            -1.0
+        });
+        let now = Instant::now();
+        let total = now - self.start;
+        let delta = now - self.last_checkpoint;
+        self.last_checkpoint = now;
+        println!("{},{:.3},{:.2}", csv_field(label), total.as_secs_f64() * 1000.0, memory);
+        (total, delta)
+    }
+}
+
+impl Default for Timer {
+    fn default() -> Self {
+        Timer::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_bytes_uses_1024_per_step() {
+        assert_eq!(MemoryUnit::KB.from_bytes(1024), 1.0);
+        assert_eq!(MemoryUnit::MB.from_bytes(1024 * 1024), 1.0);
+        assert_eq!(MemoryUnit::GB.from_bytes(1024 * 1024 * 1024), 1.0);
+        assert_eq!(MemoryUnit::KB.from_bytes(1536), 1.5);
+        assert_eq!(MemoryUnit::MB.from_bytes(512 * 1024), 0.5);
+        assert_eq!(MemoryUnit::GB.from_bytes(3 * 1024 * 1024 * 1024 / 4), 0.75);
+        assert_eq!(MemoryUnit::MB.from_bytes(0), 0.0);
+    }
+
+    #[test]
+    fn parse_ignores_case_and_rejects_other_units() {
+        assert_eq!(MemoryUnit::parse("kb"), Some(MemoryUnit::KB));
+        assert_eq!(MemoryUnit::parse("Mb"), Some(MemoryUnit::MB));
+        assert_eq!(MemoryUnit::parse("GB"), Some(MemoryUnit::GB));
+        assert_eq!(MemoryUnit::parse("TB"), None);
+    }
+
+    #[test]
+    fn checkpoint_deltas_sum_to_the_total() {
+        let mut timer = Timer::new().with_quiet(true);
+        let mut sum = Duration::ZERO;
+        let mut last_total = Duration::ZERO;
+        for label in ["first", "second", "third,with comma"] {
+            std::thread::sleep(Duration::from_millis(2));
+            let (total, delta) = timer.process_info(label);
+            assert!(total >= last_total);
+            sum += delta;
+            last_total = total;
+        }
+        assert_eq!(sum, last_total);
+    }
+}
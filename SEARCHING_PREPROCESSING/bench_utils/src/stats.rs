@@ -0,0 +1,109 @@
+//Mean and standard deviation shared by the preprocessing z-score and the server's metrics summary
+//Both use Welford's running update: the naive sum-of-squares formula subtracts two huge, nearly equal
+//numbers when the values are large relative to their spread (reimbursement amounts, or timings with a
+//large offset) and can lose every significant digit of the variance
+
+//Running mean and variance, one value at a time, so a stream never has to be kept in memory
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Welford {
+    count: usize,
+    mean: f64,
+    //sum of squared deviations from the current mean
+    m2: f64,
+}
+
+impl Welford {
+    pub fn new() -> Self {
+        Welford::default()
+    }
+
+    pub fn push(&mut self, value: f64) {
+        self.count += 1;
+        let delta = value - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (value - self.mean);
+    }
+
+    pub fn count(&self) -> usize {
+        self.count
+    }
+
+    //NaN before the first value
+    pub fn mean(&self) -> f64 {
+        if self.count == 0 { f64::NAN } else { self.mean }
+    }
+
+    //Population variance (divides by n, like checkpoint spreads expect); NaN before the first value
+    pub fn variance(&self) -> f64 {
+        self.variance_ddof(0)
+    }
+
+    //Variance dividing by n - ddof: 0 is the population variance, 1 the sample variance that pandas'
+    //`.std()` and Polars' `std(1)` use. NaN when count <= ddof
+    pub fn variance_ddof(&self, ddof: usize) -> f64 {
+        if self.count <= ddof { f64::NAN } else { self.m2 / (self.count - ddof) as f64 }
+    }
+
+    pub fn std(&self) -> f64 {
+        self.variance().sqrt()
+    }
+}
+
+//(mean, standard deviation dividing by n - ddof) of `values`. The mean is NaN for an empty slice, the
+//standard deviation whenever there are no more than `ddof` values
+pub fn mean_std(values: &[f64], ddof: usize) -> (f64, f64) {
+    let mut w = Welford::new();
+    for &v in values {
+        w.push(v);
+    }
+    (w.mean(), w.variance_ddof(ddof).sqrt())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    //Mean first, then the squared deviations from it: exact enough to be the reference
+    fn two_pass(values: &[f64], ddof: usize) -> (f64, f64) {
+        let mean = values.iter().sum::<f64>() / values.len() as f64;
+        let ss: f64 = values.iter().map(|v| (v - mean) * (v - mean)).sum();
+        (mean, (ss / (values.len() - ddof) as f64).sqrt())
+    }
+
+    #[test]
+    fn large_offset_keeps_the_spread() {
+        //Variance of 4, 7, 13, 16 is 22.5 (ddof 0) and 30 (ddof 1) whatever the offset
+        let values: Vec<f64> = [4.0, 7.0, 13.0, 16.0].iter().map(|v| v + 1e9).collect();
+        let (mean, std0) = mean_std(&values, 0);
+        assert_eq!(mean, 1e9 + 10.0);
+        assert!((std0 - 22.5f64.sqrt()).abs() < 1e-6);
+        let (_, std1) = mean_std(&values, 1);
+        assert!((std1 - 30f64.sqrt()).abs() < 1e-6);
+    }
+
+    #[test]
+    fn matches_two_pass_reference_for_ddof_0_and_1() {
+        let mut state = 0x2545_f491_4f6c_dd1du64;
+        let values: Vec<f64> = (0..10_000).map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            1e8 + (state % 10_000) as f64 / 100.0
+        }).collect();
+        for ddof in [0, 1] {
+            let (mean, std) = mean_std(&values, ddof);
+            let (ref_mean, ref_std) = two_pass(&values, ddof);
+            assert!((mean - ref_mean).abs() < 1e-6, "ddof {}: mean {} vs {}", ddof, mean, ref_mean);
+            assert!((std - ref_std).abs() / ref_std < 1e-9, "ddof {}: std {} vs {}", ddof, std, ref_std);
+        }
+    }
+
+    #[test]
+    fn too_few_values_give_nan() {
+        assert!(mean_std(&[], 0).0.is_nan());
+        let (mean, std) = mean_std(&[5.0], 1);
+        assert_eq!(mean, 5.0);
+        assert!(std.is_nan());
+        assert_eq!(mean_std(&[5.0], 0).1, 0.0);
+    }
+}
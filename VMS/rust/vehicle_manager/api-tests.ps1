@@ -1,7 +1,19 @@
+#Root URL path serves static/index.html (STATIC_DIR), a page listing vehicles and adding new ones
 Invoke-RestMethod -Uri "http://localhost:3000/"
 
-#Root URL path fails since we dont a router handling it
+#Unknown paths that aren't files in the static directory are 404
+Invoke-WebRequest -Uri "http://localhost:3000/nope" -SkipHttpErrorCheck | Select-Object StatusCode
+
 Invoke-RestMethod -Uri "http://localhost:3000/vehicle/get_vehicle" -Method Get
 
 #This a POST handler
-Invoke-RestMethod -Uri "http://localhost:3000/vehicle/post_vehicle" -Method Post
\ No newline at end of file
+Invoke-RestMethod -Uri "http://localhost:3000/vehicle/post_vehicle" -Method Post
+
+#Add a vehicle, answered with 201 and the stored vehicle including its generated id
+Invoke-RestMethod -Uri "http://localhost:3000/vehicles" -Method Post -ContentType "application/json" -Body '{"maker":"Ford","model":"Model T","year":1927}'
+
+#Paged, sorted vehicle list: sort is year or maker, order asc or desc, limit 1-1000 (default 100)
+Invoke-RestMethod -Uri "http://localhost:3000/vehicles?sort=year&order=desc&limit=10&offset=0" -Method Get
+
+#With TLS_CERT/TLS_KEY set the server listens on HTTPS instead (-SkipCertificateCheck for a self-signed certificate)
+#Invoke-RestMethod -Uri "https://localhost:3000/" -SkipCertificateCheck
\ No newline at end of file
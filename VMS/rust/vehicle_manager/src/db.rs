@@ -1,6 +1,14 @@
 use rusqlite::{Result, Connection};
 use std::path::Path;
 
+//SQLite file the server opens at startup and the handlers reconnect to per request, unless DB_PATH is set
+pub const DB_PATH: &str = "vehicle_manager.db";
+
+//DB_PATH if set, otherwise vehicle_manager.db in the working directory
+pub fn db_path() -> String {
+    std::env::var("DB_PATH").unwrap_or_else(|_| String::from(DB_PATH))
+}
+
 pub fn init_db(db_path: &str) -> Result<Connection> {
     let is_new = !Path::new(db_path).exists();
 
@@ -15,6 +23,16 @@ pub fn init_db(db_path: &str) -> Result<Connection> {
         [],
     )?;
 
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS vehicles (
+            id TEXT PRIMARY KEY,
+            maker TEXT NOT NULL,
+            model TEXT NOT NULL,
+            year INTEGER NOT NULL
+        )",
+        [],
+    )?;
+
     if is_new {
         println!("Database created: {}", db_path);
     } else {
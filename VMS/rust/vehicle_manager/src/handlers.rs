@@ -1,12 +1,60 @@
-use axum::{debug_handler, Json};
+use axum::{debug_handler, Json, Router};
+use axum::extract::Query;
+use axum::routing::{get, post};
+use axum::http::{StatusCode, Uri};
+use rusqlite::{params, Connection};
+use tower_http::services::ServeDir;
+use serde::Deserialize;
+use crate::db::db_path;
 use crate::vehicle::Vehicle;
 
+//Page size when ?limit= is absent, and the largest one accepted
+const DEFAULT_LIMIT: u32 = 100;
+const MAX_LIMIT: u32 = 1000;
+
+//Body of POST /vehicles, the id is generated
+#[derive(Deserialize)]
+pub struct NewVehicle {
+    maker: String,
+    model: String,
+    year: u16,
+}
+
+//Query string of GET /vehicles
+#[derive(Deserialize, Default)]
+pub struct ListParams {
+    limit: Option<u32>,
+    offset: Option<u32>,
+    sort: Option<String>,
+    order: Option<String>,
+}
+
+//The API routes, without the static fallback
+pub fn api_routes() -> Router {
+    Router::new() //If we have same routes then we can chain them here
+    .route("/vehicle/get_vehicle",get(get_vehicle))
+    .route("/vehicles", get(list_vehicles).post(create_vehicle))
+    .route("/vehicle/post_vehicle", post(post_vehicle))
+}
+
+//The API plus everything else as a file from `static_dir` (index.html at /, same origin as the API so no CORS);
+//an unknown path is ServeDir's 404
+pub fn create_app(static_dir: &str) -> Router {
+    if std::path::Path::new(static_dir).is_dir() {
+        api_routes().fallback_service(ServeDir::new(static_dir))
+    } else {
+        eprintln!("Warning: static directory {} not found, only the API is served (set STATIC_DIR)", static_dir);
+        let static_dir = static_dir.to_string();
+        api_routes().fallback(move |uri| missing_static(static_dir.clone(), uri))
+    }
+}
+
 #[debug_handler]
 pub async fn get_vehicle() -> Json<Vehicle> {
     Json::from(Vehicle{
         maker   : "Toyota".to_string(),
         model: "Camry".to_string(),
-        id: uuid::Uuid::new_v4().to_string(),
+        id: uuid::Uuid::now_v7().to_string(),
         year: 2020,
     })
 }
@@ -14,4 +62,70 @@ pub async fn get_vehicle() -> Json<Vehicle> {
 #[debug_handler]
 pub async fn post_vehicle() -> Json<&'static str> {
     Json::from("Something is coming from route using post_vehicle")
-}
\ No newline at end of file
+}
+
+//GET /vehicles?limit=&offset=&sort=year|maker&order=asc|desc
+//The sort column comes from an allowlist since it can't be a bound parameter; ties are broken by id
+//so pages don't overlap. Without ?sort= the order is by id, i.e. creation order for v7 ids
+#[debug_handler]
+pub async fn list_vehicles(Query(params): Query<ListParams>) -> Result<Json<Vec<Vehicle>>, (StatusCode, &'static str)> {
+    let column = match params.sort.as_deref() {
+        None => "id",
+        Some("year") => "year",
+        Some("maker") => "maker",
+        Some(_) => return Err((StatusCode::BAD_REQUEST, "sort must be year or maker")),
+    };
+    let direction = match params.order.as_deref() {
+        None | Some("asc") => "ASC",
+        Some("desc") => "DESC",
+        Some(_) => return Err((StatusCode::BAD_REQUEST, "order must be asc or desc")),
+    };
+    let limit = params.limit.unwrap_or(DEFAULT_LIMIT);
+    if limit == 0 || limit > MAX_LIMIT {
+        return Err((StatusCode::BAD_REQUEST, "limit must be between 1 and 1000"));
+    }
+    let offset = params.offset.unwrap_or(0);
+
+    let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+    let sql = format!(
+        "SELECT id, maker, model, year FROM vehicles ORDER BY {} {}, id {} LIMIT ?1 OFFSET ?2",
+        column, direction, direction
+    );
+    let mut stmt = conn.prepare(&sql).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+    let vehicles = stmt
+        .query_map(params![limit, offset], |row| {
+            Ok(Vehicle { id: row.get(0)?, maker: row.get(1)?, model: row.get(2)?, year: row.get(3)? })
+        })
+        .and_then(|rows| rows.collect::<Result<Vec<_>, _>>())
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+    Ok(Json(vehicles))
+}
+
+//POST /vehicles
+//maker and model must be non-empty (after trimming), a missing field or a year outside 0-65535 is rejected
+//by the Json extractor before the handler runs
+#[debug_handler]
+pub async fn create_vehicle(Json(new): Json<NewVehicle>) -> Result<(StatusCode, Json<Vehicle>), (StatusCode, &'static str)> {
+    let (maker, model) = (new.maker.trim(), new.model.trim());
+    if maker.is_empty() || model.is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "maker and model must not be empty"));
+    }
+    let vehicle = Vehicle {
+        maker: maker.to_string(),
+        model: model.to_string(),
+        id: uuid::Uuid::now_v7().to_string(),
+        year: new.year,
+    };
+    let conn = Connection::open(db_path()).map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+    conn.execute(
+        "INSERT INTO vehicles (id, maker, model, year) VALUES (?1, ?2, ?3, ?4)",
+        params![vehicle.id, vehicle.maker, vehicle.model, vehicle.year],
+    )
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "DB error"))?;
+    Ok((StatusCode::CREATED, Json(vehicle)))
+}
+
+//Fallback when the static directory is missing: a 404 naming the directory, so a blank page isn't a mystery
+pub async fn missing_static(static_dir: String, uri: Uri) -> (StatusCode, String) {
+    (StatusCode::NOT_FOUND, format!("Not Found: {} (no static directory at {})", uri.path(), static_dir))
+}
@@ -1,24 +1,38 @@
-use axum::{Router, routing::get, routing::post};
-use vehicle_manager::handlers::{get_vehicle, post_vehicle};
+use axum_server::tls_rustls::RustlsConfig;
+use std::net::SocketAddr;
+use vehicle_manager::handlers::create_app;
+use vehicle_manager::utils::{static_dir, tls_paths};
 
 #[tokio::main]
 async fn main() {
     //Connection to database can be initialized here if needed
-    let _conn = vehicle_manager::db::init_db("vehicle_manager.db").expect("Failed to initialize database");
+    let _conn = vehicle_manager::db::init_db(&vehicle_manager::db::db_path()).expect("Failed to initialize database");
 
 
     //1 Create axum router
-    let router_1 = Router::new() //If we have same routes then we can chain them here
-    .route("/", get(|| async { "Hello, World!" }))
-    .route("/vehicle/get_vehicle",get(get_vehicle))
-    .route("/vehicle/post_vehicle", post(post_vehicle));
+    let router_1 = create_app(&static_dir());
 
     //2 Define the IP and port listener
     let address  = "127.0.0.1:3000";
-    let listener = tokio::net::TcpListener::bind(address).await.unwrap();
 
     //3 Start the server to launch the webserver
-    axum::serve(listener, router_1).await.unwrap();
+    //HTTPS when TLS_CERT/TLS_KEY are set (HTTP/2 negotiated through ALPN), plain HTTP otherwise
+    //(HTTP/2 by prior knowledge through axum's http2 feature)
+    match tls_paths() {
+        Some((cert, key)) => {
+            let config = RustlsConfig::from_pem_file(&cert, &key)
+                .await
+                .unwrap_or_else(|e| panic!("failed to load TLS_CERT {} / TLS_KEY {}: {}", cert, key, e));
+            let addr: SocketAddr = address.parse().unwrap();
+            println!("Listening on https://{}", addr);
+            axum_server::bind_rustls(addr, config).serve(router_1.into_make_service()).await.unwrap();
+        }
+        None => {
+            let listener = tokio::net::TcpListener::bind(address).await.unwrap();
+            println!("Listening on http://{}", address);
+            axum::serve(listener, router_1).await.unwrap();
+        }
+    }
 
 }
 
@@ -0,0 +1,14 @@
+//PEM certificate chain and private key for HTTPS: TLS_CERT and TLS_KEY, both required
+//Unset (or only one of them set) means plain HTTP
+pub fn tls_paths() -> Option<(String, String)> {
+    match (std::env::var("TLS_CERT"), std::env::var("TLS_KEY")) {
+        (Ok(cert), Ok(key)) if !cert.is_empty() && !key.is_empty() => Some((cert, key)),
+        _ => None,
+    }
+}
+
+//Directory the non-API routes are served from: STATIC_DIR if set, otherwise static
+//(VMS/rust/vehicle_manager/static when started with cargo run from the crate)
+pub fn static_dir() -> String {
+    std::env::var("STATIC_DIR").unwrap_or_else(|_| String::from("static"))
+}
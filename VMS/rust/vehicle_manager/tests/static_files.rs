@@ -0,0 +1,48 @@
+//The static fallback of create_app: files from the static directory, the API routes ahead of it
+use axum::body::Body;
+use axum::http::{header, Request, StatusCode};
+use http_body_util::BodyExt;
+use tower::ServiceExt;
+use vehicle_manager::handlers::create_app;
+
+const STATIC_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/static");
+
+async fn get(static_dir: &str, path: &str) -> (StatusCode, Option<String>, String) {
+    let res = create_app(static_dir).oneshot(Request::get(path).body(Body::empty()).unwrap()).await.unwrap();
+    let status = res.status();
+    let content_type = res.headers().get(header::CONTENT_TYPE).map(|v| v.to_str().unwrap().to_string());
+    let body = res.into_body().collect().await.unwrap().to_bytes();
+    (status, content_type, String::from_utf8_lossy(&body).into_owned())
+}
+
+#[tokio::test]
+async fn root_serves_the_index_page() {
+    let (status, content_type, body) = get(STATIC_DIR, "/").await;
+    assert_eq!(status, StatusCode::OK);
+    assert!(content_type.unwrap().starts_with("text/html"));
+    assert_eq!(body, std::fs::read_to_string(format!("{}/index.html", STATIC_DIR)).unwrap());
+    assert!(body.contains("<title>Vehicle Manager</title>"));
+}
+
+#[tokio::test]
+async fn unknown_paths_fall_back_to_a_404() {
+    let (status, _, _) = get(STATIC_DIR, "/nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    let (status, _, _) = get(STATIC_DIR, "/vehicle/nope").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn api_routes_take_precedence_over_the_fallback() {
+    let (status, content_type, body) = get(STATIC_DIR, "/vehicle/get_vehicle").await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(content_type.as_deref(), Some("application/json"));
+    assert!(body.contains(r#""maker":"Toyota""#));
+}
+
+#[tokio::test]
+async fn missing_static_directory_names_it_in_the_404() {
+    let (status, _, body) = get("no/such/static", "/").await;
+    assert_eq!(status, StatusCode::NOT_FOUND);
+    assert_eq!(body, "Not Found: / (no static directory at no/such/static)");
+}
@@ -0,0 +1,96 @@
+//GET/POST /vehicles against a fresh database. DB_PATH is read by every handler, so these tests get
+//their own process (this test binary) and share one database file set up once
+use axum::body::Body;
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use std::sync::OnceLock;
+use tower::ServiceExt;
+use vehicle_manager::handlers::api_routes;
+use vehicle_manager::vehicle::Vehicle;
+
+fn setup() {
+    static DB: OnceLock<()> = OnceLock::new();
+    DB.get_or_init(|| {
+        let path = std::env::temp_dir().join(format!("vehicle_manager_test_{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        //SAFETY: set once before any handler reads it; nothing else in this binary touches the environment
+        unsafe { std::env::set_var("DB_PATH", &path) };
+        vehicle_manager::db::init_db(path.to_str().unwrap()).unwrap();
+    });
+}
+
+async fn send(req: Request<Body>) -> (StatusCode, Vec<u8>) {
+    let res = api_routes().oneshot(req).await.unwrap();
+    let status = res.status();
+    (status, res.into_body().collect().await.unwrap().to_bytes().to_vec())
+}
+
+async fn create(maker: &str, model: &str, year: u16) -> Vehicle {
+    let body = format!(r#"{{"maker":"{}","model":"{}","year":{}}}"#, maker, model, year);
+    let req = Request::post("/vehicles").header("content-type", "application/json").body(Body::from(body)).unwrap();
+    let (status, body) = send(req).await;
+    assert_eq!(status, StatusCode::CREATED);
+    serde_json::from_slice(&body).unwrap()
+}
+
+async fn list(query: &str) -> Vec<Vehicle> {
+    let (status, body) = send(Request::get(format!("/vehicles{}", query)).body(Body::empty()).unwrap()).await;
+    assert_eq!(status, StatusCode::OK, "{}", String::from_utf8_lossy(&body));
+    serde_json::from_slice(&body).unwrap()
+}
+
+fn summary(vehicles: &[Vehicle]) -> Vec<(&str, u16)> {
+    vehicles.iter().map(|v| (v.maker.as_str(), v.year)).collect()
+}
+
+#[tokio::test]
+async fn list_sorts_and_pages_the_inserted_vehicles() {
+    setup();
+    let mut created = Vec::new();
+    for (maker, model, year) in [("Ford", "Model T", 1927), ("Audi", "A4", 2015), ("Kia", "Rio", 2010), ("BMW", "M3", 2015), ("Fiat", "500", 1957)] {
+        created.push(create(maker, model, year).await);
+    }
+
+    //Default order is by id, i.e. creation order for v7 ids
+    let ids: Vec<String> = list("").await.into_iter().map(|v| v.id).collect();
+    assert_eq!(ids, created.iter().map(|v| v.id.clone()).collect::<Vec<_>>());
+
+    assert_eq!(
+        summary(&list("?sort=year").await),
+        [("Ford", 1927), ("Fiat", 1957), ("Kia", 2010), ("Audi", 2015), ("BMW", 2015)]
+    );
+    assert_eq!(
+        summary(&list("?sort=maker&order=desc").await),
+        [("Kia", 2010), ("Ford", 1927), ("Fiat", 1957), ("BMW", 2015), ("Audi", 2015)]
+    );
+
+    //Pages of two tile the sorted list without gaps or overlap, ties included
+    let mut paged = Vec::new();
+    for offset in [0, 2, 4] {
+        let page = list(&format!("?sort=year&order=desc&limit=2&offset={}", offset)).await;
+        assert!(page.len() <= 2);
+        paged.extend(page);
+    }
+    assert_eq!(summary(&paged), summary(&list("?sort=year&order=desc").await));
+    assert_eq!(summary(&paged)[..2], [("BMW", 2015), ("Audi", 2015)]);
+    assert!(list("?offset=5").await.is_empty());
+}
+
+#[tokio::test]
+async fn list_rejects_bad_parameters() {
+    setup();
+    for query in ["?sort=model", "?sort=year%3BDROP%20TABLE%20vehicles", "?order=up", "?limit=0", "?limit=1001", "?limit=-1"] {
+        let (status, _) = send(Request::get(format!("/vehicles{}", query)).body(Body::empty()).unwrap()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST, "{}", query);
+    }
+}
+
+#[tokio::test]
+async fn create_rejects_blank_maker_or_model() {
+    setup();
+    let req = Request::post("/vehicles")
+        .header("content-type", "application/json")
+        .body(Body::from(r#"{"maker":"  ","model":"Rio","year":2010}"#))
+        .unwrap();
+    assert_eq!(send(req).await.0, StatusCode::BAD_REQUEST);
+}